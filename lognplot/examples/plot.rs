@@ -12,8 +12,8 @@ fn main() {
     let y = vec![9.0, 2.2, 5.5, 2.2, 1.2, 1.7];
 
     let mut buffer = File::create("plot.svg").unwrap();
-    let mut canvas = SvgOutput::new(&mut buffer);
-
     let size = Size::new(1000.0, 1000.0);
+    let mut canvas = SvgOutput::new(&mut buffer).with_size(size.clone());
+
     plot(&mut canvas, x, y, size);
 }
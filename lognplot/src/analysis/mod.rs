@@ -0,0 +1,7 @@
+//! Calculations on signals, such as their frequency content.
+
+mod spectrum;
+mod stats;
+
+pub use spectrum::{compute_spectrum, WindowFn};
+pub use stats::{region_stats, SeriesStats};
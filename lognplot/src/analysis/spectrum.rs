@@ -0,0 +1,179 @@
+//! Frequency spectrum of a signal, using a fast fourier transform.
+
+use std::f64::consts::PI;
+
+/// Window applied to the samples before the transform, to reduce leakage.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowFn {
+    /// No window, all samples are weighted equally.
+    Rectangular,
+    Hann,
+    Hamming,
+}
+
+impl WindowFn {
+    /// Weight of sample `index` out of `count` samples.
+    pub fn weight(self, index: usize, count: usize) -> f64 {
+        if count < 2 {
+            return 1.0;
+        }
+
+        let phase = 2.0 * PI * index as f64 / (count - 1) as f64;
+        match self {
+            WindowFn::Rectangular => 1.0,
+            WindowFn::Hann => 0.5 - 0.5 * phase.cos(),
+            WindowFn::Hamming => 0.54 - 0.46 * phase.cos(),
+        }
+    }
+}
+
+/// Calculate the amplitude spectrum of equally spaced samples.
+///
+/// Returns (frequency, magnitude) pairs, from 0 Hz up to half the sample
+/// rate. The samples are zero padded to a power of two. Magnitudes are
+/// corrected for the window, so a sine wave results in a peak with about
+/// the amplitude of the sine. The spectrum is empty when there are too few
+/// samples for the window, such as two samples with a Hann window, which
+/// weighs both of them zero.
+pub fn compute_spectrum(samples: &[f64], sample_rate: f64, window: WindowFn) -> Vec<(f64, f64)> {
+    if samples.is_empty() {
+        return vec![];
+    }
+
+    let size = samples.len().next_power_of_two();
+    let mut data: Vec<(f64, f64)> = vec![(0.0, 0.0); size];
+    let mut window_sum = 0.0;
+    for (index, sample) in samples.iter().enumerate() {
+        let weight = window.weight(index, samples.len());
+        window_sum += weight;
+        data[index].0 = sample * weight;
+    }
+
+    if window_sum <= 0.0 {
+        return vec![];
+    }
+
+    fft(&mut data);
+
+    (0..=size / 2)
+        .map(|k| {
+            let (re, im) = data[k];
+            let magnitude = (re * re + im * im).sqrt() / window_sum;
+
+            // Energy of negative frequencies is folded onto the positive ones:
+            let magnitude = if k == 0 || k == size / 2 {
+                magnitude
+            } else {
+                magnitude * 2.0
+            };
+            (k as f64 * sample_rate / size as f64, magnitude)
+        })
+        .collect()
+}
+
+/// In place, iterative radix 2 FFT of complex (re, im) values.
+///
+/// The length must be a power of two.
+fn fft(data: &mut [(f64, f64)]) {
+    let n = data.len();
+    if n < 2 {
+        return;
+    }
+
+    // Bit reversal permutation:
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let mut length = 2;
+    while length <= n {
+        let angle = -2.0 * PI / length as f64;
+        for start in (0..n).step_by(length) {
+            for k in 0..length / 2 {
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let (a_re, a_im) = data[start + k];
+                let (b_re, b_im) = data[start + k + length / 2];
+                let t_re = b_re * cos - b_im * sin;
+                let t_im = b_re * sin + b_im * cos;
+                data[start + k] = (a_re + t_re, a_im + t_im);
+                data[start + k + length / 2] = (a_re - t_re, a_im - t_im);
+            }
+        }
+        length *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compute_spectrum, fft, WindowFn};
+    use std::f64::consts::PI;
+
+    fn sine(frequency: f64, amplitude: f64, sample_rate: f64, count: usize) -> Vec<f64> {
+        (0..count)
+            .map(|i| amplitude * (2.0 * PI * frequency * i as f64 / sample_rate).sin())
+            .collect()
+    }
+
+    fn peak(spectrum: &[(f64, f64)]) -> (f64, f64) {
+        spectrum
+            .iter()
+            .cloned()
+            .fold((0.0, 0.0), |a, b| if b.1 > a.1 { b } else { a })
+    }
+
+    #[test]
+    fn pure_sine() {
+        // 50 Hz falls exactly on a frequency bin:
+        let samples = sine(50.0, 3.0, 1024.0, 1024);
+        for window in &[WindowFn::Rectangular, WindowFn::Hann, WindowFn::Hamming] {
+            let spectrum = compute_spectrum(&samples, 1024.0, *window);
+            assert_eq!(513, spectrum.len());
+            assert_eq!(512.0, spectrum.last().unwrap().0);
+
+            let (frequency, magnitude) = peak(&spectrum);
+            assert_eq!(50.0, frequency);
+            assert!((magnitude - 3.0).abs() < 0.1, "{:?}: {}", window, magnitude);
+        }
+    }
+
+    #[test]
+    fn non_power_of_two() {
+        let samples = sine(120.0, 1.0, 1000.0, 1000);
+        let spectrum = compute_spectrum(&samples, 1000.0, WindowFn::Hann);
+        assert_eq!(513, spectrum.len());
+
+        let (frequency, _) = peak(&spectrum);
+        assert!((frequency - 120.0).abs() <= 1000.0 / 1024.0);
+    }
+
+    #[test]
+    fn too_few_samples() {
+        assert!(compute_spectrum(&[], 100.0, WindowFn::Rectangular).is_empty());
+        assert!(compute_spectrum(&[1.0, 2.0], 100.0, WindowFn::Hann).is_empty());
+
+        // A single sample is not weighed by the window:
+        let spectrum = compute_spectrum(&[2.0], 100.0, WindowFn::Hann);
+        assert_eq!(vec![(0.0, 2.0)], spectrum);
+
+        let spectrum = compute_spectrum(&[1.0, 2.0], 100.0, WindowFn::Hamming);
+        assert!(spectrum.iter().all(|(_, magnitude)| magnitude.is_finite()));
+    }
+
+    #[test]
+    fn transform_of_impulse() {
+        let mut data = vec![(0.0, 0.0); 8];
+        data[0] = (1.0, 0.0);
+        fft(&mut data);
+        assert!(data.iter().all(|&(re, im)| re == 1.0 && im == 0.0));
+
+        assert!(compute_spectrum(&[], 100.0, WindowFn::Hann).is_empty());
+        assert_eq!(
+            vec![(0.0, 2.0)],
+            compute_spectrum(&[2.0], 100.0, WindowFn::Hann)
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Statistics over a part of a series.
+
+use crate::geometry::Range;
+use crate::render::Series;
+
+/// Aggregates over the y values of a series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesStats {
+    /// The amount of values included.
+    pub count: usize,
+
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+
+    /// Population standard deviation, since we have all values.
+    pub stddev: f64,
+
+    /// Root mean square.
+    pub rms: f64,
+}
+
+/// Calculate statistics over the points of a series with an x value in the given range.
+///
+/// NaN values are ignored. Returns `None` when no values remain.
+pub fn region_stats(series: &Series, x_range: Range<f64>) -> Option<SeriesStats> {
+    let mut count = 0;
+    let mut min = f64::INFINITY;
+    let mut max = f64::NEG_INFINITY;
+    let mut mean = 0.0;
+    let mut m2 = 0.0;
+    let mut sum_of_squares = 0.0;
+
+    for &(_, y) in series
+        .points
+        .iter()
+        .filter(|(x, y)| x_range.contains(*x) && !y.is_nan())
+    {
+        count += 1;
+        min = min.min(y);
+        max = max.max(y);
+        sum_of_squares += y * y;
+
+        // Welford online algorithm, like the metrics in the database:
+        let new_mean = mean + (y - mean) / count as f64;
+        m2 += (y - mean) * (y - new_mean);
+        mean = new_mean;
+    }
+
+    if count == 0 {
+        return None;
+    }
+
+    Some(SeriesStats {
+        count,
+        min,
+        max,
+        mean,
+        stddev: (m2 / count as f64).sqrt(),
+        rms: (sum_of_squares / count as f64).sqrt(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::region_stats;
+    use crate::geometry::Range;
+    use crate::render::Series;
+    use crate::style::Color;
+
+    fn assert_almost_eq(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1.0e-12,
+            "{} != {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn known_values() {
+        let points = vec![
+            (0.0, 100.0),
+            (1.0, 2.0),
+            (2.0, 4.0),
+            (3.0, 4.0),
+            (4.0, 4.0),
+            (5.0, 5.0),
+            (6.0, 5.0),
+            (7.0, 7.0),
+            (8.0, 9.0),
+        ];
+        let series = Series::new("signal", points, Color::red());
+        let stats = region_stats(&series, Range::new(1.0, 8.0)).unwrap();
+
+        assert_eq!(8, stats.count);
+        assert_eq!(2.0, stats.min);
+        assert_eq!(9.0, stats.max);
+        assert_almost_eq(5.0, stats.mean);
+        assert_almost_eq(2.0, stats.stddev);
+        assert_almost_eq(29.0_f64.sqrt(), stats.rms);
+    }
+
+    #[test]
+    fn with_nan() {
+        let points = vec![(0.0, 3.0), (1.0, f64::NAN), (2.0, -4.0), (3.0, f64::NAN)];
+        let series = Series::new("signal", points, Color::red());
+        let stats = region_stats(&series, Range::new(0.0, 10.0)).unwrap();
+
+        assert_eq!(2, stats.count);
+        assert_eq!(-4.0, stats.min);
+        assert_eq!(3.0, stats.max);
+        assert_almost_eq(-0.5, stats.mean);
+        assert_almost_eq(3.5, stats.stddev);
+        assert_almost_eq(12.5_f64.sqrt(), stats.rms);
+    }
+
+    #[test]
+    fn empty_region() {
+        let points = vec![(0.0, 3.0), (1.0, f64::NAN), (5.0, 1.0)];
+        let series = Series::new("signal", points, Color::red());
+        assert!(region_stats(&series, Range::new(0.5, 4.0)).is_none());
+        assert!(region_stats(&series, Range::new(10.0, 20.0)).is_none());
+    }
+}
@@ -1,6 +1,6 @@
 use super::util::{calc_tick_spacing, ceil_to_multiple_of, format_at_scale};
 use super::TickLabels;
-use chrono::TimeZone;
+use chrono::{Offset, TimeZone};
 
 /// Determine nice date time tick markers.
 ///
@@ -28,6 +28,67 @@ pub fn calc_date_ticks(begin: f64, end: f64, n_ticks: usize) -> (String, TickLab
     (prefix, ticks)
 }
 
+/// Natural time intervals to place ticks at, in seconds.
+const TIME_INTERVALS: &[f64] = &[
+    1.0, 2.0, 5.0, 10.0, 15.0, 30.0, // seconds
+    60.0, 120.0, 300.0, 600.0, 900.0, 1800.0, // minutes
+    3600.0, 7200.0, 10800.0, 21600.0, 43200.0, // hours
+    86400.0,
+];
+
+/// Determine tick markers at natural time boundaries, labeled with the time of day.
+///
+/// The interval is picked from seconds, minutes and hours, such that there
+/// are at most `n_ticks` ticks in the range. Ticks are placed at multiples of
+/// the interval in the given time zone, so hour and day ticks are at whole
+/// hours and midnight of the labels. The offset of the time zone at the
+/// begin of the range is used for all ticks. Intervals below one second use
+/// nice numbers, and get millisecond labels.
+pub fn calc_time_ticks<Tz>(begin: f64, end: f64, n_ticks: usize, tz: &Tz) -> TickLabels
+where
+    Tz: TimeZone,
+    Tz::Offset: std::fmt::Display,
+{
+    let span = end - begin;
+    let n_ticks = n_ticks.max(2);
+    let tick_step = if span / (n_ticks as f64) <= 0.5 {
+        calc_tick_spacing(span, n_ticks).1
+    } else {
+        let last = TIME_INTERVALS[TIME_INTERVALS.len() - 1];
+        let multiples_of_last = (span / (n_ticks as f64) / last).ceil() * last;
+        TIME_INTERVALS
+            .iter()
+            .cloned()
+            .find(|interval| span / interval <= n_ticks as f64)
+            .unwrap_or(multiples_of_last)
+    };
+
+    let offset = to_datetime(begin, tz).offset().fix().local_minus_utc() as f64;
+    let first_tick = ((begin + offset) / tick_step).ceil() as i64;
+    let last_tick = ((end + offset) / tick_step).floor() as i64;
+    (first_tick..=last_tick)
+        .map(|index| {
+            let x = index as f64 * tick_step - offset;
+            let timestamp = to_datetime(x, tz);
+            let label = if tick_step >= 86400.0 {
+                timestamp.format("%Y-%m-%d").to_string()
+            } else if tick_step >= 1.0 {
+                timestamp.format("%H:%M:%S").to_string()
+            } else {
+                let millis = ((x - x.floor()) * 1000.0).round() as u32 % 1000;
+                format!("{}.{:03}", timestamp.format("%H:%M:%S"), millis)
+            };
+            (x, label)
+        })
+        .collect()
+}
+
+fn to_datetime<Tz: TimeZone>(timestamp: f64, tz: &Tz) -> chrono::DateTime<Tz> {
+    let seconds = timestamp.floor() as i64;
+    let nanos = ((timestamp - timestamp.floor()) * 1e9) as u32;
+    tz.timestamp(seconds, nanos)
+}
+
 fn f64_to_datetime(timestamp: f64) -> chrono::DateTime<chrono::Local> {
     let seconds = timestamp.trunc() as i64;
     let nanos = (timestamp.fract() * 1e9) as u32;
@@ -37,7 +98,7 @@ fn f64_to_datetime(timestamp: f64) -> chrono::DateTime<chrono::Local> {
 #[cfg(test)]
 pub mod tests {
     use super::super::tests::compare_ticks;
-    use super::calc_date_ticks;
+    use super::{calc_date_ticks, calc_time_ticks};
 
     #[test]
     fn date_ticks() {
@@ -58,4 +119,53 @@ pub mod tests {
         println!("{:?}", ticks);
         compare_ticks(ticks, expected_ticks);
     }
+
+    #[test]
+    fn time_ticks_seconds() {
+        // 2020-02-13 16:18:02.3 until 16:18:07.3 UTC
+        let ticks = calc_time_ticks(1581610682.3, 1581610687.3, 6, &chrono::Utc);
+
+        let expected_ticks = vec![
+            (1581610683.0, "16:18:03".to_string()),
+            (1581610684.0, "16:18:04".to_string()),
+            (1581610685.0, "16:18:05".to_string()),
+            (1581610686.0, "16:18:06".to_string()),
+            (1581610687.0, "16:18:07".to_string()),
+        ];
+        compare_ticks(ticks, expected_ticks);
+    }
+
+    #[test]
+    fn time_ticks_hours() {
+        // 2020-02-13 10:01:40 until 13:01:40 UTC
+        let begin = 1581552000.0 + 10.0 * 3600.0 + 100.0;
+        let ticks = calc_time_ticks(begin, begin + 3.0 * 3600.0, 6, &chrono::Utc);
+
+        let expected_ticks = vec![
+            (1581552000.0 + 10.5 * 3600.0, "10:30:00".to_string()),
+            (1581552000.0 + 11.0 * 3600.0, "11:00:00".to_string()),
+            (1581552000.0 + 11.5 * 3600.0, "11:30:00".to_string()),
+            (1581552000.0 + 12.0 * 3600.0, "12:00:00".to_string()),
+            (1581552000.0 + 12.5 * 3600.0, "12:30:00".to_string()),
+            (1581552000.0 + 13.0 * 3600.0, "13:00:00".to_string()),
+        ];
+        compare_ticks(ticks, expected_ticks);
+    }
+
+    #[test]
+    fn time_ticks_in_time_zone() {
+        // 2020-02-13 05:31:40 until 11:31:40 at UTC+05:30:
+        let begin = 1581552000.0 + 100.0;
+        let tz = chrono::FixedOffset::east(5 * 3600 + 1800);
+        let ticks = calc_time_ticks(begin, begin + 6.0 * 3600.0, 6, &tz);
+
+        // Whole hours in the time zone, which are half hours in UTC:
+        let expected_ticks = (6..12)
+            .map(|hour| {
+                let x = 1581552000.0 + (hour as f64 - 5.5) * 3600.0;
+                (x, format!("{:02}:00:00", hour))
+            })
+            .collect();
+        compare_ticks(ticks, expected_ticks);
+    }
 }
@@ -8,6 +8,9 @@ mod options;
 mod util;
 mod value;
 
+pub use options::AxisScale;
+pub use util::log_ticks;
+pub(crate) use util::scale_power;
 pub use value::ValueAxis;
 
 type TickLabels = Vec<(f64, String)>;
@@ -1,3 +1,12 @@
+/// How values are spread out over an axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AxisScale {
+    Linear,
+
+    /// Logarithmic axis, only positive values can be shown.
+    Log10,
+}
+
 /// Axis options
 #[derive(Clone)]
 pub struct AxisOptions {
@@ -6,6 +15,9 @@ pub struct AxisOptions {
 
     /// Draw minor tick markers
     pub minor_ticks: bool,
+
+    /// Linear or logarithmic scale
+    pub scale: AxisScale,
 }
 
 /// Implement sensible default axis options.
@@ -14,6 +26,7 @@ impl Default for AxisOptions {
         AxisOptions {
             major_ticks: true,
             minor_ticks: false,
+            scale: AxisScale::Linear,
         }
     }
 }
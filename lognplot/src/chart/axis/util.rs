@@ -1,3 +1,5 @@
+use crate::geometry::Range;
+
 pub fn get_scale(domain: f64) -> i32 {
     domain.log10().floor() as i32
 }
@@ -42,6 +44,99 @@ pub fn ceil_to_multiple_of(x: f64, step: f64) -> f64 {
     }
 }
 
+/// Find a common offset and power of ten, which leave short tick labels
+/// when factored out of the values in the given range.
+///
+/// An offset is used when the values differ far less than their magnitude,
+/// and a power of ten when the differences are very small or very large.
+/// Returns None when the values can be labeled as they are.
+pub fn calc_offset(begin: f64, end: f64) -> Option<(f64, i32)> {
+    let low = begin.min(end);
+    let high = begin.max(end);
+    let domain = high - low;
+    if domain <= 0.0 || !domain.is_finite() {
+        return None;
+    }
+
+    let magnitude = low.abs().max(high.abs());
+    let offset = if magnitude / domain >= 1.0e4 {
+        // Round down to the power of ten just above the domain:
+        let exponent = domain.log10().ceil() as i32;
+        scale_power(scale_power(low, -exponent).floor(), exponent)
+    } else {
+        0.0
+    };
+
+    let scale = get_scale(domain);
+    let exponent = if !(-3..=5).contains(&scale) { scale } else { 0 };
+
+    if offset == 0.0 && exponent == 0 {
+        None
+    } else {
+        Some((offset, exponent))
+    }
+}
+
+/// Multiply a value with ten to the given power.
+///
+/// Negative powers divide by a positive power instead, which is exact for
+/// more values.
+pub fn scale_power(value: f64, exponent: i32) -> f64 {
+    if exponent < 0 {
+        value / 10.0_f64.powi(-exponent)
+    } else {
+        value * 10.0_f64.powi(exponent)
+    }
+}
+
+/// Calculate ticks for a logarithmic axis spanning the given range.
+///
+/// Returns the major ticks at each decade, and minor ticks at 2 and 5
+/// times each decade. Non-positive parts of the range are skipped.
+pub fn log_ticks(range: &Range<f64>) -> (Vec<f64>, Vec<f64>) {
+    let low = range.begin().min(range.end());
+    let high = range.begin().max(range.end());
+    let mut major = vec![];
+    let mut minor = vec![];
+    if high <= 0.0 || !high.is_finite() {
+        return (major, minor);
+    }
+
+    let low = if low > 0.0 { low } else { high / 1.0e6 };
+    let first_decade = low.log10().floor() as i32;
+    let last_decade = high.log10().ceil() as i32;
+    for exponent in first_decade..=last_decade {
+        for &mantissa in &[1.0, 2.0, 5.0] {
+            let value = scale_power(mantissa, exponent);
+
+            // Allow for some rounding on the edges:
+            if value < low * (1.0 - 1.0e-9) || value > high * (1.0 + 1.0e-9) {
+                continue;
+            }
+
+            if mantissa == 1.0 {
+                major.push(value);
+            } else {
+                minor.push(value);
+            }
+        }
+    }
+
+    (major, minor)
+}
+
+/// Format an offset and power of ten as found by `calc_offset`, such as `+1e0 ×1e-5`.
+pub fn format_offset(offset: f64, exponent: i32) -> String {
+    let mut parts = vec![];
+    if offset != 0.0 {
+        parts.push(format!("{:+e}", offset));
+    }
+    if exponent != 0 {
+        parts.push(format!("×1e{}", exponent));
+    }
+    parts.join(" ")
+}
+
 /// Format a number at with the proper amount of precision given the
 /// scale the number is in.
 pub fn format_at_scale(value: f64, scale: i32) -> String {
@@ -59,7 +154,38 @@ pub fn format_at_scale(value: f64, scale: i32) -> String {
 
 #[cfg(test)]
 pub mod tests {
-    use super::format_at_scale;
+    use super::{calc_offset, format_at_scale, format_offset, log_ticks};
+    use crate::geometry::Range;
+
+    #[test]
+    fn offsets() {
+        let (offset, exponent) = calc_offset(1.000023, 1.000041).unwrap();
+        assert_eq!(1.0, offset);
+        assert_eq!(-5, exponent);
+        assert_eq!("+1e0 ×1e-5", format_offset(offset, exponent));
+
+        assert_eq!(Some((1.0e6, 0)), calc_offset(1.0e6 + 2.0, 1.0e6 + 8.0));
+        assert_eq!(Some((0.0, -7)), calc_offset(1.0e-7, 5.0e-7));
+        assert_eq!(None, calc_offset(12.0, 87.0));
+        assert_eq!(None, calc_offset(3.0, 3.0));
+    }
+
+    #[test]
+    fn log_decades() {
+        let (major, minor) = log_ticks(&Range::new(1.0, 10000.0));
+        assert_eq!(vec![1.0, 10.0, 100.0, 1000.0, 10000.0], major);
+        assert_eq!(
+            vec![2.0, 5.0, 20.0, 50.0, 200.0, 500.0, 2000.0, 5000.0],
+            minor
+        );
+
+        let (major, minor) = log_ticks(&Range::new(0.03, 0.4));
+        assert_eq!(vec![0.1], major);
+        assert_eq!(vec![0.05, 0.2], minor);
+
+        let (major, minor) = log_ticks(&Range::new(-10.0, 0.0));
+        assert!(major.is_empty() && minor.is_empty());
+    }
 
     #[test]
     fn test_format_scale() {
@@ -1,10 +1,13 @@
-use super::date::calc_date_ticks;
-use super::options::AxisOptions;
+use super::date::{calc_date_ticks, calc_time_ticks};
+use super::options::{AxisOptions, AxisScale};
 use super::TickLabels;
 use crate::geometry::Range;
 use crate::time::{TimeSpan, TimeStamp};
 
-use super::util::{calc_tick_spacing, ceil_to_multiple_of, format_at_scale, get_scale};
+use super::util::{
+    calc_offset, calc_tick_spacing, ceil_to_multiple_of, format_at_scale, format_offset, get_scale,
+    log_ticks, scale_power,
+};
 
 #[derive(Clone)]
 pub struct ValueAxis {
@@ -57,6 +60,45 @@ impl ValueAxis {
         self.end() - self.begin()
     }
 
+    pub fn scale(&self) -> AxisScale {
+        self.options.scale
+    }
+
+    /// Determine where a value lies on the axis, 0.0 being the begin and 1.0 the end.
+    ///
+    /// Returns `None` for values which cannot be shown, such as non-positive
+    /// values on a logarithmic axis.
+    pub fn value_to_fraction(&self, value: f64) -> Option<f64> {
+        let (value, begin, end) = match self.options.scale {
+            AxisScale::Linear => (value, self.begin(), self.end()),
+            AxisScale::Log10 => {
+                if value <= 0.0 || self.begin() <= 0.0 || self.end() <= 0.0 {
+                    return None;
+                }
+                (value.log10(), self.begin().log10(), self.end().log10())
+            }
+        };
+
+        let domain = end - begin;
+        if domain == 0.0 {
+            None
+        } else {
+            Some((value - begin) / domain)
+        }
+    }
+
+    /// Inverse of `value_to_fraction`.
+    pub fn fraction_to_value(&self, fraction: f64) -> f64 {
+        match self.options.scale {
+            AxisScale::Linear => self.begin() + fraction * self.domain(),
+            AxisScale::Log10 => {
+                let begin = self.begin().log10();
+                let end = self.end().log10();
+                10.0_f64.powf(begin + fraction * (end - begin))
+            }
+        }
+    }
+
     /// Zoom the axis by a certain percentage, optionally centered around some value.
     pub fn zoom(&mut self, amount: f64, around: Option<f64>) {
         let domain = self.domain();
@@ -111,7 +153,45 @@ impl ValueAxis {
     }
 
     pub fn calc_tiks(&self, n_ticks: usize) -> TickLabels {
-        calc_tiks(self.range.begin(), self.range.end(), n_ticks)
+        match self.options.scale {
+            AxisScale::Linear => calc_tiks(self.range.begin(), self.range.end(), n_ticks),
+            AxisScale::Log10 => calc_log_tiks(&self.range),
+        }
+    }
+
+    /// Calculate tick markers, with a common offset and power of ten factored out of the labels.
+    ///
+    /// When the values on the axis share a large offset, or are very small
+    /// or large, this returns the factored out part as text, such as
+    /// `+1e0 ×1e-5`, to show once near the axis. The ticks are then labeled
+    /// relative to it. Otherwise, the ticks are the same as from `calc_tiks`.
+    pub fn calc_tiks_with_offset(&self, n_ticks: usize) -> (Option<String>, TickLabels) {
+        if let AxisScale::Linear = self.options.scale {
+            if let Some((offset, exponent)) = calc_offset(self.range.begin(), self.range.end()) {
+                let relative = |x: f64| scale_power(x - offset, -exponent);
+                let ticks = calc_tiks(
+                    relative(self.range.begin()),
+                    relative(self.range.end()),
+                    n_ticks,
+                )
+                .into_iter()
+                .map(|(x, label)| (scale_power(x, exponent) + offset, label))
+                .collect();
+                return (Some(format_offset(offset, exponent)), ticks);
+            }
+        }
+
+        (None, self.calc_tiks(n_ticks))
+    }
+
+    /// Calculate tick markers at natural time boundaries, labeled with the local time.
+    pub fn calc_time_tiks(&self, n_ticks: usize) -> TickLabels {
+        calc_time_ticks(
+            self.range.begin(),
+            self.range.end(),
+            n_ticks,
+            &chrono::Local,
+        )
     }
 
     /// Calculate date time tick markers.
@@ -153,6 +233,31 @@ fn calc_tiks(begin: f64, end: f64, n_ticks: usize) -> TickLabels {
     res
 }
 
+/// Calculate tick labels at each decade on a logarithmic axis.
+///
+/// When less than two decades are visible, the 2 and 5 multiples are labeled as well.
+fn calc_log_tiks(range: &Range<f64>) -> TickLabels {
+    let (major, minor) = log_ticks(range);
+    let mut tick_values = major.clone();
+    if major.len() < 2 {
+        tick_values.extend(minor);
+        tick_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    }
+
+    tick_values
+        .into_iter()
+        .map(|x| {
+            let exponent = x.log10().round() as i32;
+            let label = if (-3..=5).contains(&exponent) {
+                format!("{}", x)
+            } else {
+                format!("{:.0}e{}", x / 10.0_f64.powi(exponent), exponent)
+            };
+            (x, label)
+        })
+        .collect()
+}
+
 fn create_points(start: f64, end: f64, step: f64) -> Vec<f64> {
     let mut res = vec![];
     let mut x = start;
@@ -166,7 +271,7 @@ fn create_points(start: f64, end: f64, step: f64) -> Vec<f64> {
 #[cfg(test)]
 mod tests {
     use super::super::tests::compare_ticks;
-    use super::ValueAxis;
+    use super::{AxisScale, ValueAxis};
 
     #[test]
     fn tick_calculation() {
@@ -208,6 +313,56 @@ mod tests {
         compare_ticks(expected_ticks, ticks);
     }
 
+    #[test]
+    fn clustered_ticks() {
+        let mut axis = ValueAxis::default();
+        axis.set_limits(1.000023, 1.000041);
+        let (offset, ticks) = axis.calc_tiks_with_offset(7);
+
+        assert_eq!(Some("+1e0 ×1e-5".to_string()), offset);
+        assert!(ticks.len() >= 3, "{:?}", ticks);
+        for (value, label) in &ticks {
+            assert!(1.000023 <= *value && *value <= 1.000041, "{:?}", ticks);
+            assert!(label.len() <= 3, "{:?}", ticks);
+        }
+        assert!(axis.calc_tiks(7)[0].1.len() > 6);
+
+        // Nothing to factor out:
+        axis.set_limits(12.0, 87.0);
+        let (offset, ticks) = axis.calc_tiks_with_offset(7);
+        assert_eq!(None, offset);
+        assert_eq!(axis.calc_tiks(7), ticks);
+    }
+
+    #[test]
+    fn log_tick_calculation() {
+        let mut axis = ValueAxis::default();
+        axis.options.scale = AxisScale::Log10;
+        axis.set_limits(1.0, 10000.0);
+        let ticks = axis.calc_tiks(7);
+
+        let expected_ticks = vec![
+            (1.0, "1".to_string()),
+            (10.0, "10".to_string()),
+            (100.0, "100".to_string()),
+            (1000.0, "1000".to_string()),
+            (10000.0, "10000".to_string()),
+        ];
+        compare_ticks(expected_ticks, ticks);
+    }
+
+    #[test]
+    fn log_fractions() {
+        let mut axis = ValueAxis::default();
+        axis.options.scale = AxisScale::Log10;
+        axis.set_limits(1.0, 100.0);
+
+        assert!((axis.value_to_fraction(10.0).unwrap() - 0.5).abs() < 1.0e-9);
+        assert!((axis.fraction_to_value(0.5) - 10.0).abs() < 1.0e-9);
+        assert_eq!(None, axis.value_to_fraction(0.0));
+        assert_eq!(None, axis.value_to_fraction(-3.0));
+    }
+
     #[test]
     fn small_ticks() {
         let mut axis = ValueAxis::default();
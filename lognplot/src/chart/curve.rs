@@ -112,8 +112,13 @@ fn point_summary(points: &[Point]) -> Option<Summary> {
         }
         let timespan = TimeSpan::new(TimeStamp::new(xmin), TimeStamp::new(xmax));
 
-        let y_values: Vec<f64> = points.iter().map(|p| p.y()).collect();
-        let metrics = SampleMetrics::from_values(&y_values).unwrap();
+        // Skip gaps, such as NaN values, when fitting the y-axis:
+        let y_values: Vec<f64> = points
+            .iter()
+            .map(|p| p.y())
+            .filter(|y| y.is_finite())
+            .collect();
+        let metrics = SampleMetrics::from_values(&y_values)?;
 
         let count = points.len();
         let aggregation = Aggregation::new(timespan, metrics, count);
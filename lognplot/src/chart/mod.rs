@@ -7,7 +7,8 @@ mod axis;
 mod chart;
 mod curve;
 
-pub use axis::ValueAxis;
+pub(crate) use axis::scale_power;
+pub use axis::{log_ticks, AxisScale, ValueAxis};
 pub use chart::Chart;
 pub use curve::{Curve, CurveData};
 
@@ -1,3 +1,4 @@
+mod path;
 mod point;
 mod range;
 mod rectangle;
@@ -6,6 +7,7 @@ mod size;
 // A nice line!
 // struct Line {}
 
+pub use path::{Path, PathElement};
 pub use point::Point;
 pub use range::Range;
 pub use rectangle::Rect;
@@ -0,0 +1,60 @@
+use super::Point;
+
+/// A single element of a path.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathElement {
+    /// Start a new sub path at the given point.
+    MoveTo(Point),
+
+    /// Straight line to the given point.
+    LineTo(Point),
+
+    /// Quadratic bezier curve via a control point to the end point.
+    QuadTo(Point, Point),
+
+    /// Cubic bezier curve via two control points to the end point.
+    CubicTo(Point, Point, Point),
+
+    /// Close the current sub path.
+    Close,
+}
+
+/// A path made out of lines and bezier curves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Path {
+    elements: Vec<PathElement>,
+}
+
+impl Path {
+    /// Create a new path beginning at the given point.
+    pub fn new(start: Point) -> Self {
+        Path {
+            elements: vec![PathElement::MoveTo(start)],
+        }
+    }
+
+    pub fn move_to(&mut self, p: Point) {
+        self.elements.push(PathElement::MoveTo(p));
+    }
+
+    pub fn line_to(&mut self, p: Point) {
+        self.elements.push(PathElement::LineTo(p));
+    }
+
+    pub fn quad_to(&mut self, control: Point, end: Point) {
+        self.elements.push(PathElement::QuadTo(control, end));
+    }
+
+    pub fn curve_to(&mut self, control1: Point, control2: Point, end: Point) {
+        self.elements
+            .push(PathElement::CubicTo(control1, control2, end));
+    }
+
+    pub fn close(&mut self) {
+        self.elements.push(PathElement::Close);
+    }
+
+    pub fn elements(&self) -> &[PathElement] {
+        &self.elements
+    }
+}
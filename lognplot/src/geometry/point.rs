@@ -1,5 +1,7 @@
+use std::ops::{Add, Mul, Sub};
+
 /// 2D point.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Point {
     x: f64,
     y: f64,
@@ -17,4 +19,57 @@ impl Point {
     pub fn y(&self) -> f64 {
         self.y
     }
+
+    /// Euclidean distance between two points.
+    pub fn distance_to(&self, other: &Point) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Point {
+    type Output = Point;
+
+    fn mul(self, factor: f64) -> Point {
+        Point::new(self.x * factor, self.y * factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Point;
+
+    #[test]
+    fn arithmetic() {
+        let a = Point::new(1.0, 2.0);
+        let b = Point::new(4.0, -2.0);
+        assert_eq!(Point::new(5.0, 0.0), a + b);
+        assert_eq!(Point::new(3.0, -4.0), b - a);
+        assert_eq!(Point::new(2.5, 5.0), a * 2.5);
+        assert_eq!(a, a + b - b);
+    }
+
+    #[test]
+    fn distance() {
+        let a = Point::new(1.0, 2.0);
+        let b = Point::new(4.0, -2.0);
+        assert_eq!(5.0, a.distance_to(&b));
+        assert_eq!(5.0, b.distance_to(&a));
+        assert_eq!(0.0, a.distance_to(&a));
+    }
 }
@@ -1,4 +1,9 @@
-//// A certain range with a begining and an end.
+//! A certain range with a begining and an end.
+
+/// A range from a begin to an end value.
+///
+/// The end may be smaller than the begin, in which case the range is
+/// reversed. The methods take this into account.
 #[derive(Default, Clone, Debug)]
 pub struct Range<T>
 where
@@ -30,7 +35,111 @@ impl<T: Default + Copy + PartialOrd> Range<T> {
         self.end = end;
     }
 
+    /// Test if the value lies within this range, including its bounds.
     pub fn contains(&self, value: T) -> bool {
-        self.begin < value && value < self.end
+        let (low, high) = self.bounds();
+        low <= value && value <= high
+    }
+
+    /// Get the lowest and highest value of this range.
+    fn bounds(&self) -> (T, T) {
+        if self.end < self.begin {
+            (self.end, self.begin)
+        } else {
+            (self.begin, self.end)
+        }
+    }
+}
+
+impl Range<f64> {
+    /// The distance between begin and end, which is never negative.
+    pub fn length(&self) -> f64 {
+        (self.end - self.begin).abs()
+    }
+
+    /// Limit the value to this range.
+    pub fn clamp(&self, value: f64) -> f64 {
+        let (low, high) = self.bounds();
+        value.max(low).min(high)
+    }
+
+    /// Create the smallest range which spans both ranges.
+    ///
+    /// The result is never reversed.
+    pub fn union(&self, other: &Range<f64>) -> Range<f64> {
+        let (low, high) = self.bounds();
+        let (other_low, other_high) = other.bounds();
+        Range::new(low.min(other_low), high.max(other_high))
+    }
+
+    /// Grow this range on both sides by a fraction of its length.
+    ///
+    /// A reversed range stays reversed, and a range of zero length stays
+    /// the same.
+    pub fn expanded(&self, fraction: f64) -> Range<f64> {
+        let margin = self.length() * fraction;
+        if self.end < self.begin {
+            Range::new(self.begin + margin, self.end - margin)
+        } else {
+            Range::new(self.begin - margin, self.end + margin)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Range;
+
+    #[test]
+    fn length_and_contains() {
+        let range = Range::new(2.0, 6.0);
+        assert_eq!(4.0, range.length());
+        assert!(range.contains(2.0));
+        assert!(range.contains(4.0));
+        assert!(range.contains(6.0));
+        assert!(!range.contains(1.9));
+        assert!(!range.contains(6.1));
+    }
+
+    #[test]
+    fn reversed() {
+        let range = Range::new(6.0, 2.0);
+        assert_eq!(4.0, range.length());
+        assert!(range.contains(3.0));
+        assert!(!range.contains(7.0));
+        assert_eq!(2.0, range.clamp(-10.0));
+        assert_eq!(6.0, range.clamp(10.0));
+        assert_eq!(5.0, range.clamp(5.0));
+
+        let expanded = range.expanded(0.25);
+        assert_eq!((7.0, 1.0), (expanded.begin(), expanded.end()));
+
+        let union = range.union(&Range::new(8.0, 7.0));
+        assert_eq!((2.0, 8.0), (union.begin(), union.end()));
+    }
+
+    #[test]
+    fn zero_length() {
+        let range = Range::new(3.0, 3.0);
+        assert_eq!(0.0, range.length());
+        assert!(range.contains(3.0));
+        assert!(!range.contains(3.1));
+        assert_eq!(3.0, range.clamp(-1.0));
+
+        let expanded = range.expanded(0.5);
+        assert_eq!((3.0, 3.0), (expanded.begin(), expanded.end()));
+
+        let union = range.union(&Range::new(-1.0, -1.0));
+        assert_eq!((-1.0, 3.0), (union.begin(), union.end()));
+    }
+
+    #[test]
+    fn expand_and_union() {
+        let range = Range::new(0.0, 10.0);
+        let expanded = range.expanded(0.1);
+        assert_eq!((-1.0, 11.0), (expanded.begin(), expanded.end()));
+
+        let union = range.union(&Range::new(5.0, 20.0));
+        assert_eq!((0.0, 20.0), (union.begin(), union.end()));
     }
 }
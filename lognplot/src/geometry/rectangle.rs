@@ -1,4 +1,7 @@
-#[derive(Debug)]
+use super::Point;
+
+/// A rectangle, defined by its top left corner and its size.
+#[derive(Clone, Debug, PartialEq)]
 pub struct Rect {
     x: f64,
     y: f64,
@@ -15,4 +18,139 @@ impl Rect {
             height,
         }
     }
+
+    /// Create the rectangle spanned by two opposite corners.
+    pub fn from_points(p1: &Point, p2: &Point) -> Self {
+        let left = p1.x().min(p2.x());
+        let top = p1.y().min(p2.y());
+        Rect::new(
+            left,
+            top,
+            p1.x().max(p2.x()) - left,
+            p1.y().max(p2.y()) - top,
+        )
+    }
+
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    pub fn left(&self) -> f64 {
+        self.x
+    }
+
+    pub fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    pub fn top(&self) -> f64 {
+        self.y
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    /// Test if the given point lies within this rectangle.
+    pub fn contains(&self, point: &Point) -> bool {
+        self.left() <= point.x()
+            && point.x() <= self.right()
+            && self.top() <= point.y()
+            && point.y() <= self.bottom()
+    }
+
+    /// Test if the other rectangle lies entirely within this rectangle.
+    pub fn contains_rect(&self, other: &Rect) -> bool {
+        self.left() <= other.left()
+            && other.right() <= self.right()
+            && self.top() <= other.top()
+            && other.bottom() <= self.bottom()
+    }
+
+    /// Calculate the overlapping part of two rectangles.
+    ///
+    /// Returns None when the rectangles do not overlap. Rectangles which
+    /// only touch result in a rectangle of zero width or height.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let left = self.left().max(other.left());
+        let right = self.right().min(other.right());
+        let top = self.top().max(other.top());
+        let bottom = self.bottom().min(other.bottom());
+        if left <= right && top <= bottom {
+            Some(Rect::new(left, top, right - left, bottom - top))
+        } else {
+            None
+        }
+    }
+
+    /// Create the smallest rectangle which contains both rectangles.
+    pub fn union(&self, other: &Rect) -> Rect {
+        let left = self.left().min(other.left());
+        let top = self.top().min(other.top());
+        Rect::new(
+            left,
+            top,
+            self.right().max(other.right()) - left,
+            self.bottom().max(other.bottom()) - top,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rect;
+    use crate::geometry::Point;
+
+    #[test]
+    fn from_points() {
+        let rect = Rect::from_points(&Point::new(10.0, 5.0), &Point::new(2.0, 8.0));
+        assert_eq!(Rect::new(2.0, 5.0, 8.0, 3.0), rect);
+        assert!(rect.contains(&Point::new(2.0, 5.0)));
+        assert!(rect.contains(&Point::new(10.0, 8.0)));
+        assert!(!rect.contains(&Point::new(1.0, 6.0)));
+    }
+
+    #[test]
+    fn overlapping() {
+        let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::new(5.0, 2.0, 10.0, 20.0);
+        assert_eq!(Some(Rect::new(5.0, 2.0, 5.0, 8.0)), a.intersection(&b));
+        assert_eq!(a.intersection(&b), b.intersection(&a));
+        assert_eq!(Rect::new(0.0, 0.0, 15.0, 22.0), a.union(&b));
+    }
+
+    #[test]
+    fn disjoint() {
+        let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::new(20.0, 0.0, 10.0, 10.0);
+        assert_eq!(None, a.intersection(&b));
+        assert_eq!(Rect::new(0.0, 0.0, 30.0, 10.0), a.union(&b));
+
+        // Touching rectangles share an edge:
+        let c = Rect::new(10.0, 0.0, 5.0, 5.0);
+        assert_eq!(Some(Rect::new(10.0, 0.0, 0.0, 5.0)), a.intersection(&c));
+    }
+
+    #[test]
+    fn nested() {
+        let outer = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let inner = Rect::new(10.0, 20.0, 30.0, 40.0);
+        assert_eq!(Some(inner.clone()), outer.intersection(&inner));
+        assert_eq!(outer, outer.union(&inner));
+        assert!(outer.contains_rect(&inner));
+        assert!(outer.contains_rect(&outer));
+        assert!(!inner.contains_rect(&outer));
+    }
 }
@@ -0,0 +1,282 @@
+//! Comma separated values, with a column per series.
+//!
+//! The first row holds the column names. The first column holds the
+//! x values, usually time, and each following column the y values of
+//! a single series. An empty cell means there is no value.
+
+use crate::render::Series;
+use crate::style::Color;
+use std::io::{BufRead, BufReader, Read, Write};
+
+/// Things that can go wrong while reading CSV data.
+#[derive(Debug)]
+pub enum CsvError {
+    Io(std::io::Error),
+
+    /// There is no header row with column names.
+    MissingHeader,
+
+    /// A cell which is not a number, or a row with too many cells.
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl From<std::io::Error> for CsvError {
+    fn from(error: std::io::Error) -> Self {
+        CsvError::Io(error)
+    }
+}
+
+/// Read a header row and rows of values into a series per column.
+///
+/// Empty or absent cells become NaN values, which are drawn as gaps.
+/// Missing values before the first and after the last value of a column
+/// are left out, so series can have different lengths. The series are
+/// colored black, and can be restyled afterwards.
+pub fn read_csv<R>(reader: R) -> Result<Vec<Series>, CsvError>
+where
+    R: Read,
+{
+    let mut lines = BufReader::new(reader).lines().enumerate();
+
+    let header = loop {
+        match lines.next() {
+            Some((_, line)) => {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    break split_line(&line);
+                }
+            }
+            None => return Err(CsvError::MissingHeader),
+        }
+    };
+
+    let mut series: Vec<Series> = header
+        .iter()
+        .skip(1)
+        .map(|name| Series::new(name, vec![], Color::black()))
+        .collect();
+
+    for (index, line) in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let line_number = index + 1;
+        let cells = split_line(&line);
+        if cells.len() > header.len() {
+            return Err(CsvError::Parse {
+                line: line_number,
+                column: header.len() + 1,
+                message: format!("expected at most {} cells", header.len()),
+            });
+        }
+
+        let x = match parse_cell(&cells[0], line_number, 1)? {
+            Some(x) => x,
+            None => {
+                return Err(CsvError::Parse {
+                    line: line_number,
+                    column: 1,
+                    message: "missing x value".to_owned(),
+                });
+            }
+        };
+
+        for (column, s) in series.iter_mut().enumerate() {
+            let y = match cells.get(column + 1) {
+                Some(cell) => parse_cell(cell, line_number, column + 2)?,
+                None => None,
+            };
+            s.points.push((x, y.unwrap_or(f64::NAN)));
+        }
+    }
+
+    for s in &mut series {
+        let first = s.points.iter().position(|p| !p.1.is_nan());
+        let last = s.points.iter().rposition(|p| !p.1.is_nan());
+        s.points = match (first, last) {
+            (Some(first), Some(last)) => s.points[first..=last].to_vec(),
+            _ => vec![],
+        };
+    }
+
+    Ok(series)
+}
+
+/// Write series as CSV, with a row for each distinct x value.
+///
+/// The x column is named `time`. Series without a value at some x value,
+/// or with a NaN value there, get an empty cell.
+pub fn write_csv<W>(mut writer: W, series: &[Series]) -> std::io::Result<()>
+where
+    W: Write,
+{
+    let header: Vec<String> = std::iter::once("time")
+        .chain(series.iter().map(|s| s.name.as_str()))
+        .map(quote)
+        .collect();
+    writeln!(writer, "{}", header.join(","))?;
+
+    let sorted: Vec<Vec<(f64, f64)>> = series
+        .iter()
+        .map(|s| {
+            let mut points: Vec<(f64, f64)> = s
+                .points
+                .iter()
+                .filter(|p| p.0.is_finite())
+                .cloned()
+                .collect();
+            points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+            points
+        })
+        .collect();
+
+    let mut xs: Vec<f64> = sorted.iter().flatten().map(|p| p.0).collect();
+    xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    xs.dedup();
+
+    let mut positions = vec![0; sorted.len()];
+    for x in xs {
+        let mut row = vec![x.to_string()];
+        for (points, position) in sorted.iter().zip(positions.iter_mut()) {
+            let mut y = None;
+            while *position < points.len() && points[*position].0 == x {
+                y = Some(points[*position].1);
+                *position += 1;
+            }
+
+            row.push(match y {
+                Some(y) if !y.is_nan() => y.to_string(),
+                _ => String::new(),
+            });
+        }
+        writeln!(writer, "{}", row.join(","))?;
+    }
+
+    Ok(())
+}
+
+/// Split a line into cells, removing quotes around cells.
+///
+/// Within quotes, commas are part of the cell, and a doubled quote is a
+/// single quote character.
+fn split_line(line: &str) -> Vec<String> {
+    let mut cells = vec![];
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    cell.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' => quoted = true,
+            ',' if !quoted => cells.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell);
+    cells
+}
+
+fn parse_cell(cell: &str, line: usize, column: usize) -> Result<Option<f64>, CsvError> {
+    let cell = cell.trim();
+    if cell.is_empty() {
+        return Ok(None);
+    }
+
+    cell.parse::<f64>().map(Some).map_err(|_| CsvError::Parse {
+        line,
+        column,
+        message: format!("invalid number '{}'", cell),
+    })
+}
+
+/// Quote a name when it would otherwise be read differently.
+fn quote(name: &str) -> String {
+    if name.contains(',') || name.contains('"') || name.trim() != name {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    } else {
+        name.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_csv, write_csv, CsvError};
+    use crate::render::Series;
+    use crate::style::Color;
+
+    #[test]
+    fn roundtrip() {
+        let series = vec![
+            Series::new(
+                "voltage",
+                vec![(0.0, 1.5), (0.5, f64::NAN), (1.0, -2.0), (1.5, 3.25)],
+                Color::red(),
+            ),
+            Series::new(
+                "current, \"A\"",
+                vec![(0.5, 0.1), (1.0, 0.2)],
+                Color::black(),
+            ),
+        ];
+
+        let mut buffer: Vec<u8> = vec![];
+        write_csv(&mut buffer, &series).unwrap();
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        assert_eq!(
+            "time,voltage,\"current, \"\"A\"\"\"\n0,1.5,\n0.5,,0.1\n1,-2,0.2\n1.5,3.25,\n",
+            text
+        );
+
+        let result = read_csv(buffer.as_slice()).unwrap();
+        assert_eq!(2, result.len());
+        assert_eq!("voltage", result[0].name);
+        assert_eq!("current, \"A\"", result[1].name);
+        assert_eq!(vec![(0.5, 0.1), (1.0, 0.2)], result[1].points);
+
+        let voltage = &result[0].points;
+        assert_eq!(4, voltage.len());
+        assert_eq!((0.0, 1.5), voltage[0]);
+        assert_eq!(0.5, voltage[1].0);
+        assert!(voltage[1].1.is_nan());
+        assert_eq!(&[(1.0, -2.0), (1.5, 3.25)], &voltage[2..]);
+    }
+
+    #[test]
+    fn short_rows() {
+        let text = "t,a,b\n1,2\n2,3,4\n\n3\n";
+        let result = read_csv(text.as_bytes()).unwrap();
+        assert_eq!(vec![(1.0, 2.0), (2.0, 3.0)], result[0].points);
+        assert_eq!(vec![(2.0, 4.0)], result[1].points);
+    }
+
+    #[test]
+    fn invalid_input() {
+        match read_csv("".as_bytes()) {
+            Err(CsvError::MissingHeader) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        match read_csv("t,a\n1,2\n2,two\n".as_bytes()) {
+            Err(CsvError::Parse { line, column, .. }) => assert_eq!((3, 2), (line, column)),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        match read_csv("t,a\n1,2,3\n".as_bytes()) {
+            Err(CsvError::Parse { line, column, .. }) => assert_eq!((2, 3), (line, column)),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
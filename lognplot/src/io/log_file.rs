@@ -0,0 +1,599 @@
+//! Compact binary log files, to record samples quickly and replay them later.
+//!
+//! A file starts with a magic value and a version number, followed by
+//! frames. Each frame is prefixed with its length and starts with a kind
+//! byte, so that later versions can add kinds of frames and fields. All
+//! values are little endian.
+//!
+//! Frames either hold a single sample, a batch of samples of one channel,
+//! or the name of a channel. Version 1 logs have no kind byte, all their
+//! frames hold a single sample. These are still read, but no longer written.
+//!
+//! With the `gzip` feature, logs can be gzip compressed as a whole.
+//! Use `open_log` to read both compressed and uncompressed logs.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+
+/// The first bytes of gzip compressed data.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+const MAGIC: &[u8; 4] = b"LNPL";
+const VERSION: u16 = 2;
+
+/// The first version, of sample frames without a kind byte.
+const VERSION_1: u16 = 1;
+
+const KIND_SAMPLE: u8 = 0;
+const KIND_BATCH: u8 = 1;
+const KIND_CHANNEL_NAME: u8 = 2;
+
+/// Size of a single sample frame in bytes, without the length prefix.
+const SAMPLE_SIZE: usize = 1 + 8 + 2 + 8;
+
+/// Size of a batch frame header, and of each sample in a batch.
+const BATCH_HEADER_SIZE: usize = 1 + 2;
+const BATCH_SAMPLE_SIZE: usize = 8 + 8;
+
+/// The most samples which fit in a single batch frame.
+pub const MAX_BATCH_SAMPLES: usize = (u16::MAX as usize - BATCH_HEADER_SIZE) / BATCH_SAMPLE_SIZE;
+
+/// A single sample in a log file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogRecord {
+    pub timestamp: f64,
+    pub channel: u16,
+    pub value: f64,
+}
+
+/// Append records to a binary log.
+pub struct LogWriter<W>
+where
+    W: Write,
+{
+    writer: W,
+}
+
+impl<W> LogWriter<W>
+where
+    W: Write,
+{
+    /// Start a new log, by writing the file header.
+    pub fn new(mut writer: W) -> std::io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        Ok(LogWriter { writer })
+    }
+
+    /// Append a single record.
+    pub fn write(&mut self, timestamp: f64, channel: u16, value: f64) -> std::io::Result<()> {
+        let mut frame = Vec::with_capacity(2 + SAMPLE_SIZE);
+        frame.extend_from_slice(&(SAMPLE_SIZE as u16).to_le_bytes());
+        frame.push(KIND_SAMPLE);
+        frame.extend_from_slice(&timestamp.to_le_bytes());
+        frame.extend_from_slice(&channel.to_le_bytes());
+        frame.extend_from_slice(&value.to_le_bytes());
+        self.writer.write_all(&frame)
+    }
+
+    /// Append (timestamp, value) samples of a single channel.
+    ///
+    /// This takes less space than writing the samples one by one. Large
+    /// batches are split over multiple frames.
+    pub fn write_batch(&mut self, channel: u16, samples: &[(f64, f64)]) -> std::io::Result<()> {
+        for chunk in samples.chunks(MAX_BATCH_SAMPLES) {
+            let length = BATCH_HEADER_SIZE + chunk.len() * BATCH_SAMPLE_SIZE;
+            let mut frame = Vec::with_capacity(2 + length);
+            frame.extend_from_slice(&(length as u16).to_le_bytes());
+            frame.push(KIND_BATCH);
+            frame.extend_from_slice(&channel.to_le_bytes());
+            for (timestamp, value) in chunk {
+                frame.extend_from_slice(&timestamp.to_le_bytes());
+                frame.extend_from_slice(&value.to_le_bytes());
+            }
+            self.writer.write_all(&frame)?;
+        }
+        Ok(())
+    }
+
+    /// Give a channel a name. Names longer than a frame are cut off.
+    pub fn write_channel_name(&mut self, channel: u16, name: &str) -> std::io::Result<()> {
+        let mut name = name;
+        while 3 + name.len() > u16::MAX as usize {
+            let mut end = name.len() - 1;
+            while !name.is_char_boundary(end) {
+                end -= 1;
+            }
+            name = &name[..end];
+        }
+
+        let length = 1 + 2 + name.len();
+        let mut frame = Vec::with_capacity(2 + length);
+        frame.extend_from_slice(&(length as u16).to_le_bytes());
+        frame.push(KIND_CHANNEL_NAME);
+        frame.extend_from_slice(&channel.to_le_bytes());
+        frame.extend_from_slice(name.as_bytes());
+        self.writer.write_all(&frame)
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Access the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Stop writing, and get back the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// A log writer which gzip compresses the log.
+#[cfg(feature = "gzip")]
+pub type CompressedLogWriter<W> = LogWriter<flate2::write::GzEncoder<W>>;
+
+#[cfg(feature = "gzip")]
+impl<W> LogWriter<flate2::write::GzEncoder<W>>
+where
+    W: Write,
+{
+    /// Start a new compressed log.
+    ///
+    /// Each `flush` pushes out all records written so far, so a reader
+    /// can follow the log while it is being written. Call `finish` at the
+    /// end, to complete the compressed stream.
+    pub fn compressed(writer: W) -> std::io::Result<Self> {
+        let encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+        Self::new(encoder)
+    }
+
+    /// Complete the compressed stream, and get back the underlying writer.
+    pub fn finish(self) -> std::io::Result<W> {
+        self.writer.finish()
+    }
+}
+
+/// Start reading a log, which may be compressed.
+///
+/// Compression is detected from the first bytes of the log. Reading a
+/// compressed log without the `gzip` feature results in an error.
+pub fn open_log<'r, R>(reader: R) -> std::io::Result<LogReader<Box<dyn Read + 'r>>>
+where
+    R: Read + 'r,
+{
+    let mut reader = BufReader::new(reader);
+    let is_compressed = reader.fill_buf()?.starts_with(&GZIP_MAGIC);
+    if is_compressed {
+        #[cfg(feature = "gzip")]
+        {
+            LogReader::new(Box::new(flate2::read::GzDecoder::new(reader)))
+        }
+
+        #[cfg(not(feature = "gzip"))]
+        {
+            Err(invalid_data(
+                "reading compressed logs requires the gzip feature",
+            ))
+        }
+    } else {
+        LogReader::new(Box::new(reader))
+    }
+}
+
+/// Iterate over the records of a binary log.
+///
+/// Batches are split into separate records, and channel names are
+/// remembered, see `channel_name`. A frame which is cut off at the end, for
+/// example because the program writing the log was stopped, ends the
+/// iteration without an error. Use `is_truncated` to find out whether this
+/// happened.
+pub struct LogReader<R>
+where
+    R: Read,
+{
+    reader: R,
+    version: u16,
+    pending: VecDeque<LogRecord>,
+    names: HashMap<u16, String>,
+    truncated: bool,
+    done: bool,
+}
+
+impl<R> LogReader<R>
+where
+    R: Read,
+{
+    /// Start reading a log, by checking the file header.
+    pub fn new(mut reader: R) -> std::io::Result<Self> {
+        let mut header = [0u8; 6];
+        reader.read_exact(&mut header)?;
+        if &header[0..4] != MAGIC {
+            return Err(invalid_data("not a lognplot log file"));
+        }
+
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        if version != VERSION && version != VERSION_1 {
+            return Err(invalid_data(&format!(
+                "unsupported log file version {}",
+                version
+            )));
+        }
+
+        Ok(LogReader {
+            reader,
+            version,
+            pending: VecDeque::new(),
+            names: HashMap::new(),
+            truncated: false,
+            done: false,
+        })
+    }
+
+    /// Test if the log ended halfway a frame.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// The name of a channel, if it was given one so far.
+    pub fn channel_name(&self, channel: u16) -> Option<&str> {
+        self.names.get(&channel).map(|name| name.as_str())
+    }
+
+    /// Read frames until there are records, or the log ends.
+    fn read_frames(&mut self) -> std::io::Result<()> {
+        while self.pending.is_empty() {
+            let mut prefix = [0u8; 2];
+            let size = self.read_full(&mut prefix)?;
+            if size == 0 {
+                return Ok(());
+            } else if size < prefix.len() {
+                self.truncated = true;
+                return Ok(());
+            }
+
+            let length = u16::from_le_bytes(prefix) as usize;
+            let mut data = vec![0u8; length];
+            if self.read_full(&mut data)? < length {
+                self.truncated = true;
+                return Ok(());
+            }
+
+            self.parse_frame(&data)?;
+        }
+        Ok(())
+    }
+
+    /// Read until the buffer is full or the log ends, returning the amount of bytes read.
+    ///
+    /// A decompressor reports a cut off stream as an unexpected end, even
+    /// between frames, so this marks the log as truncated.
+    fn read_full(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let mut size = 0;
+        while size < buffer.len() {
+            match self.reader.read(&mut buffer[size..]) {
+                Ok(0) => break,
+                Ok(n) => size += n,
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(ref e) if e.kind() == ErrorKind::UnexpectedEof => {
+                    self.truncated = true;
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(size)
+    }
+
+    fn parse_frame(&mut self, data: &[u8]) -> std::io::Result<()> {
+        if self.version == VERSION_1 {
+            if data.len() < SAMPLE_SIZE - 1 {
+                return Err(too_short(data));
+            }
+
+            self.pending.push_back(LogRecord {
+                timestamp: f64_at(data, 0),
+                channel: u16_at(data, 8),
+                value: f64_at(data, 10),
+            });
+            return Ok(());
+        }
+
+        match data.first() {
+            Some(&KIND_SAMPLE) => {
+                if data.len() < SAMPLE_SIZE {
+                    return Err(too_short(data));
+                }
+
+                // Newer versions may append fields, which we skip:
+                self.pending.push_back(LogRecord {
+                    timestamp: f64_at(data, 1),
+                    channel: u16_at(data, 9),
+                    value: f64_at(data, 11),
+                });
+            }
+            Some(&KIND_BATCH) => {
+                if data.len() < BATCH_HEADER_SIZE {
+                    return Err(too_short(data));
+                }
+
+                let channel = u16_at(data, 1);
+                for sample in data[BATCH_HEADER_SIZE..].chunks_exact(BATCH_SAMPLE_SIZE) {
+                    self.pending.push_back(LogRecord {
+                        timestamp: f64_at(sample, 0),
+                        channel,
+                        value: f64_at(sample, 8),
+                    });
+                }
+            }
+            Some(&KIND_CHANNEL_NAME) => {
+                if data.len() < 3 {
+                    return Err(too_short(data));
+                }
+
+                let name = String::from_utf8_lossy(&data[3..]).into_owned();
+                self.names.insert(u16_at(data, 1), name);
+            }
+            Some(kind) => {
+                debug!("Skipping frame of unknown kind {}", kind);
+            }
+            None => {
+                return Err(too_short(data));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R> Iterator for LogReader<R>
+where
+    R: Read,
+{
+    type Item = std::io::Result<LogRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        if let Err(err) = self.read_frames() {
+            self.done = true;
+            return Some(Err(err));
+        }
+
+        let record = self.pending.pop_front();
+        if record.is_none() {
+            self.done = true;
+        }
+        record.map(Ok)
+    }
+}
+
+fn u16_at(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn f64_at(data: &[u8], offset: usize) -> f64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    f64::from_le_bytes(bytes)
+}
+
+/// Error for a frame which is too short for its contents.
+fn too_short(data: &[u8]) -> std::io::Error {
+    invalid_data(&format!("frame of {} bytes too short", data.len()))
+}
+
+fn invalid_data(message: &str) -> std::io::Error {
+    std::io::Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open_log, LogReader, LogRecord, LogWriter};
+
+    fn example_log() -> Vec<u8> {
+        let mut writer = LogWriter::new(vec![]).unwrap();
+        writer.write(0.0, 1, 3.5).unwrap();
+        writer.write(0.001, 2, -1.0e9).unwrap();
+        writer.write(0.002, 65535, f64::NAN).unwrap();
+        writer.into_inner()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let data = example_log();
+        assert_eq!(6 + 3 * 21, data.len());
+
+        let mut reader = LogReader::new(data.as_slice()).unwrap();
+        let records: Vec<LogRecord> = reader.by_ref().map(|r| r.unwrap()).collect();
+        assert!(!reader.is_truncated());
+        assert_eq!(3, records.len());
+        assert_eq!(
+            LogRecord {
+                timestamp: 0.0,
+                channel: 1,
+                value: 3.5
+            },
+            records[0]
+        );
+        assert_eq!(
+            LogRecord {
+                timestamp: 0.001,
+                channel: 2,
+                value: -1.0e9
+            },
+            records[1]
+        );
+        assert_eq!(65535, records[2].channel);
+        assert!(records[2].value.is_nan());
+    }
+
+    #[test]
+    fn truncated_file() {
+        let data = example_log();
+        for cut in &[1, 5, 19] {
+            let data = &data[..data.len() - cut];
+            let mut reader = LogReader::new(data).unwrap();
+            let records: Vec<LogRecord> = reader.by_ref().map(|r| r.unwrap()).collect();
+            assert_eq!(2, records.len());
+            assert!(reader.is_truncated());
+        }
+
+        for cut in &[1, 20] {
+            let data = &data[..6 + cut];
+            let mut reader = LogReader::new(data).unwrap();
+            assert!(reader.next().is_none());
+            assert!(reader.is_truncated());
+        }
+
+        let reader = LogReader::new(&data[..6]).unwrap();
+        assert_eq!(0, reader.count());
+    }
+
+    /// Reader which fails like a decompressor does on a cut off stream.
+    struct CutOff<'a>(&'a [u8]);
+
+    impl std::io::Read for CutOff<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            self.0.read(buffer)
+        }
+    }
+
+    #[test]
+    fn cut_off_stream() {
+        let data = example_log();
+
+        // Cut off between frames, and halfway a frame:
+        for cut in &[0, 5] {
+            let mut reader = LogReader::new(CutOff(&data[..data.len() - cut])).unwrap();
+            let records: Vec<LogRecord> = reader.by_ref().map(|r| r.unwrap()).collect();
+            assert_eq!(if *cut == 0 { 3 } else { 2 }, records.len());
+            assert!(reader.is_truncated());
+        }
+    }
+
+    #[test]
+    fn batches_and_names() {
+        let samples: Vec<(f64, f64)> = (0..10_000).map(|i| (i as f64 * 0.1, i as f64)).collect();
+        let mut writer = LogWriter::new(vec![]).unwrap();
+        writer.write_channel_name(3, "temperature").unwrap();
+        writer.write_batch(3, &samples).unwrap();
+        writer.write(5.0, 4, 1.0).unwrap();
+        writer.write_batch(4, &[]).unwrap();
+        let data = writer.into_inner();
+
+        let mut reader = LogReader::new(data.as_slice()).unwrap();
+        let records: Vec<LogRecord> = reader.by_ref().map(|r| r.unwrap()).collect();
+        assert_eq!(10_001, records.len());
+        assert!(records[..10_000]
+            .iter()
+            .zip(samples.iter())
+            .all(|(r, s)| r.channel == 3 && (r.timestamp, r.value) == *s));
+        assert_eq!(4, records[10_000].channel);
+        assert_eq!(Some("temperature"), reader.channel_name(3));
+        assert_eq!(None, reader.channel_name(4));
+
+        // Unknown kinds of frames are skipped:
+        let mut data = data;
+        data.extend_from_slice(&[3, 0, 99, 1, 2]);
+        let reader = LogReader::new(data.as_slice()).unwrap();
+        assert_eq!(10_001, reader.count());
+    }
+
+    #[test]
+    fn open_uncompressed() {
+        let data = example_log();
+        let plain: Vec<LogRecord> = LogReader::new(data.as_slice())
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        let opened: Vec<LogRecord> = open_log(data.as_slice())
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(format!("{:?}", plain), format!("{:?}", opened));
+
+        assert!(open_log(&b"\x1f\x8bnot a log"[..]).is_err());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn compressed_roundtrip() {
+        fn fill<W: std::io::Write>(writer: &mut LogWriter<W>) {
+            let samples: Vec<(f64, f64)> = (0..1000).map(|i| (i as f64, (i % 10) as f64)).collect();
+            writer.write_channel_name(1, "counter").unwrap();
+            writer.write_batch(1, &samples).unwrap();
+            writer.flush().unwrap();
+            writer.write(2000.0, 2, 1.0).unwrap();
+        }
+
+        let mut plain = LogWriter::new(vec![]).unwrap();
+        fill(&mut plain);
+        let plain = plain.into_inner();
+        let mut compressed = LogWriter::compressed(vec![]).unwrap();
+        fill(&mut compressed);
+        let compressed = compressed.finish().unwrap();
+
+        let expected: Vec<LogRecord> = open_log(plain.as_slice())
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        let mut reader = open_log(compressed.as_slice()).unwrap();
+        let records: Vec<LogRecord> = reader.by_ref().map(|r| r.unwrap()).collect();
+        assert_eq!(1001, records.len());
+        assert_eq!(expected, records);
+        assert_eq!(Some("counter"), reader.channel_name(1));
+    }
+
+    #[test]
+    fn version_1() {
+        // Sample frames without a kind byte, the second with an extra field:
+        let mut data = b"LNPL\x01\x00".to_vec();
+        for &(timestamp, channel, value, length) in &[(0.5, 3u16, 2.0, 18u16), (1.0, 4, -1.0, 20)] {
+            data.extend_from_slice(&length.to_le_bytes());
+            data.extend_from_slice(&f64::to_le_bytes(timestamp));
+            data.extend_from_slice(&channel.to_le_bytes());
+            data.extend_from_slice(&f64::to_le_bytes(value));
+            data.resize(data.len() + length as usize - 18, 0xff);
+        }
+
+        let records: Vec<LogRecord> = LogReader::new(data.as_slice())
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(
+            vec![
+                LogRecord {
+                    timestamp: 0.5,
+                    channel: 3,
+                    value: 2.0
+                },
+                LogRecord {
+                    timestamp: 1.0,
+                    channel: 4,
+                    value: -1.0
+                },
+            ],
+            records
+        );
+
+        let mut short = b"LNPL\x01\x00".to_vec();
+        short.extend_from_slice(&[1, 0, 0]);
+        let mut reader = LogReader::new(short.as_slice()).unwrap();
+        assert!(reader.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn invalid_header() {
+        assert!(LogReader::new(&b"LNPX\x01\x00"[..]).is_err());
+        assert!(LogReader::new(&b"LNPL\x00\x00"[..]).is_err());
+        assert!(LogReader::new(&b"LNPL\x03\x00"[..]).is_err());
+        assert!(LogReader::new(&b"LN"[..]).is_err());
+    }
+}
@@ -0,0 +1,10 @@
+//! Reading and writing data in file formats shared with other tools.
+
+mod csv;
+mod log_file;
+
+pub use csv::{read_csv, write_csv, CsvError};
+pub use log_file::{open_log, LogReader, LogRecord, LogWriter};
+
+#[cfg(feature = "gzip")]
+pub use log_file::CompressedLogWriter;
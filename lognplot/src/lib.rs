@@ -10,8 +10,10 @@
 #[macro_use]
 extern crate log;
 
+pub mod analysis;
 pub mod chart;
 pub mod geometry;
+pub mod io;
 pub mod net;
 pub mod render;
 pub mod style;
@@ -11,6 +11,8 @@
 
 mod client;
 mod payload;
+mod sample_encoder;
+mod sample_server;
 
 #[cfg(feature = "server")]
 mod peer;
@@ -23,3 +25,5 @@ mod server;
 pub use server::run_server;
 
 pub use client::TcpClient;
+pub use sample_encoder::{SampleEncoder, TooManyChannels};
+pub use sample_server::{start_server, SampleServer};
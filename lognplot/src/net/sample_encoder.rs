@@ -0,0 +1,144 @@
+//! Encode samples for the sample server.
+
+use crate::io::LogWriter;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// All channel ids are in use, no more channels can be added.
+#[derive(Debug, PartialEq)]
+pub struct TooManyChannels;
+
+/// Turn named samples into the bytes expected by the sample server.
+///
+/// Each channel name is assigned an id, and the name is sent once, when
+/// the channel is first used. Samples are collected per channel, and sent
+/// as batches when taking the encoded frames. The first frames also
+/// include the stream header, so send everything taken from a single
+/// encoder over a single connection.
+pub struct SampleEncoder {
+    writer: LogWriter<Vec<u8>>,
+    channels: HashMap<String, u16>,
+    pending: Vec<Vec<(f64, f64)>>,
+}
+
+impl Default for SampleEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SampleEncoder {
+    pub fn new() -> Self {
+        SampleEncoder {
+            writer: LogWriter::new(vec![]).unwrap(),
+            channels: HashMap::new(),
+            pending: vec![],
+        }
+    }
+
+    /// Add a single sample to the next batch of its channel.
+    pub fn add(
+        &mut self,
+        channel: &str,
+        timestamp: f64,
+        value: f64,
+    ) -> Result<(), TooManyChannels> {
+        let id = self.channel_id(channel)?;
+        self.pending[id as usize].push((timestamp, value));
+        Ok(())
+    }
+
+    /// Add (timestamp, value) samples to the next batch of a channel.
+    pub fn add_batch(
+        &mut self,
+        channel: &str,
+        samples: &[(f64, f64)],
+    ) -> Result<(), TooManyChannels> {
+        let id = self.channel_id(channel)?;
+        self.pending[id as usize].extend_from_slice(samples);
+        Ok(())
+    }
+
+    /// Encode all samples added so far, and take the resulting bytes.
+    pub fn take_frames(&mut self) -> Vec<u8> {
+        for (id, samples) in self.pending.iter_mut().enumerate() {
+            if !samples.is_empty() {
+                self.writer.write_batch(id as u16, samples).unwrap();
+                samples.clear();
+            }
+        }
+        std::mem::take(self.writer.get_mut())
+    }
+
+    /// Get the id of a channel, registering the channel on first use.
+    fn channel_id(&mut self, channel: &str) -> Result<u16, TooManyChannels> {
+        if let Some(id) = self.channels.get(channel) {
+            return Ok(*id);
+        }
+
+        let id = u16::try_from(self.pending.len()).map_err(|_| TooManyChannels)?;
+        self.writer.write_channel_name(id, channel).unwrap();
+        self.channels.insert(channel.to_owned(), id);
+        self.pending.push(vec![]);
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SampleEncoder, TooManyChannels};
+    use crate::io::{LogReader, LogRecord};
+
+    #[test]
+    fn decode_encoded_samples() {
+        let mut encoder = SampleEncoder::new();
+        encoder.add("voltage", 0.0, 3.3).unwrap();
+        encoder.add("current", 0.0, 0.1).unwrap();
+        encoder.add("voltage", 0.1, 3.2).unwrap();
+        let mut data = encoder.take_frames();
+
+        encoder
+            .add_batch("current", &[(0.1, 0.2), (0.2, 0.3)])
+            .unwrap();
+        encoder.add("power", 0.2, 1.0).unwrap();
+        data.extend(encoder.take_frames());
+        assert!(encoder.take_frames().is_empty());
+
+        let mut reader = LogReader::new(data.as_slice()).unwrap();
+        let records: Vec<LogRecord> = reader.by_ref().map(|r| r.unwrap()).collect();
+        let named: Vec<(&str, f64, f64)> = records
+            .iter()
+            .map(|r| {
+                (
+                    reader.channel_name(r.channel).unwrap(),
+                    r.timestamp,
+                    r.value,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("voltage", 0.0, 3.3),
+                ("voltage", 0.1, 3.2),
+                ("current", 0.0, 0.1),
+                ("current", 0.1, 0.2),
+                ("current", 0.2, 0.3),
+                ("power", 0.2, 1.0),
+            ],
+            named
+        );
+        assert!(!reader.is_truncated());
+    }
+
+    #[test]
+    fn too_many_channels() {
+        let mut encoder = SampleEncoder::new();
+        for channel in 0..=u16::MAX as usize {
+            encoder.add(&channel.to_string(), 0.0, 1.0).unwrap();
+        }
+        assert_eq!(Err(TooManyChannels), encoder.add("one more", 0.0, 1.0));
+
+        // Existing channels can still be used:
+        assert_eq!(Ok(()), encoder.add("0", 1.0, 2.0));
+    }
+}
@@ -0,0 +1,441 @@
+//! A plain TCP server receiving samples in the binary log format.
+//!
+//! Each client sends a stream which looks exactly like a log file written
+//! by `LogWriter`: a small header followed by length prefixed frames of
+//! single samples, batches of samples, or channel names. Every client is
+//! served in its own thread, so this server does not need the async
+//! runtime. Use a `SampleEncoder` to produce such a stream from named
+//! samples.
+//!
+//! Channel ids are chosen by each client, so two clients may use the same
+//! id for different channels. The server numbers the channels of all
+//! clients: a channel keeps the id of its client when no other client uses
+//! that id, and gets the lowest free id otherwise.
+
+use crate::io::{LogReader, LogRecord};
+use crate::render::Series;
+use crate::style::Color;
+use std::collections::{BTreeMap, HashMap};
+use std::io::ErrorKind;
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// The most received samples which are buffered, before clients have to wait.
+const MAX_PENDING_SAMPLES: usize = 100_000;
+
+/// How often the listener checks whether the server is stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A running sample server. Received samples can be taken from this handle.
+pub struct SampleServer {
+    local_addr: SocketAddr,
+    samples: mpsc::Receiver<LogRecord>,
+    running: Arc<AtomicBool>,
+    clients: Arc<Mutex<HashMap<usize, TcpStream>>>,
+    channels: Arc<Mutex<Channels>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+/// The channels of all clients, with server wide ids.
+#[derive(Default)]
+struct Channels {
+    /// The client, and the id used by that client, of each server channel.
+    owners: HashMap<u16, (usize, u16)>,
+    names: HashMap<u16, String>,
+}
+
+impl Channels {
+    /// Give a channel of a client a server wide id, if any id is free.
+    fn assign(&mut self, client: usize, channel: u16) -> Option<u16> {
+        let id = if self.owners.contains_key(&channel) {
+            (0..=u16::MAX).find(|id| !self.owners.contains_key(id))?
+        } else {
+            channel
+        };
+        self.owners.insert(id, (client, channel));
+        Some(id)
+    }
+
+    /// Free the ids of a disconnected client, for use by other clients.
+    ///
+    /// The names are kept, until the ids are assigned again.
+    fn release(&mut self, client: usize) {
+        self.owners.retain(|_, owner| owner.0 != client);
+    }
+}
+
+/// Start listening for clients at the given address.
+///
+/// Use port 0 to pick a free port, and `local_addr` to find out which.
+/// At most 100000 received samples are buffered. When the samples are not
+/// taken fast enough, clients are not read until there is room again.
+pub fn start_server<A>(addr: A) -> std::io::Result<SampleServer>
+where
+    A: ToSocketAddrs,
+{
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+
+    // Poll for clients, so the listener can notice that the server stops:
+    listener.set_nonblocking(true)?;
+    let (sink, samples) = mpsc::sync_channel(MAX_PENDING_SAMPLES);
+    let running = Arc::new(AtomicBool::new(true));
+    let clients: Arc<Mutex<HashMap<usize, TcpStream>>> = Arc::new(Mutex::new(HashMap::new()));
+    let channels: Arc<Mutex<Channels>> = Arc::new(Mutex::new(Channels::default()));
+
+    let thread = {
+        let running = running.clone();
+        let clients = clients.clone();
+        let channels = channels.clone();
+        thread::spawn(move || {
+            info!("Sample server listening on {}", local_addr);
+            let mut next_client = 0;
+            while running.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        if let Err(err) = stream.set_nonblocking(false) {
+                            warn!("Failed to set up client: {}", err);
+                            continue;
+                        }
+
+                        let client = next_client;
+                        next_client += 1;
+                        if let Ok(clone) = stream.try_clone() {
+                            clients.lock().unwrap().insert(client, clone);
+                        }
+                        let sink = sink.clone();
+                        let clients = clients.clone();
+                        let channels = channels.clone();
+                        thread::spawn(move || {
+                            serve_client(client, stream, sink, channels);
+                            clients.lock().unwrap().remove(&client);
+                        });
+                    }
+                    Err(ref err) if err.kind() == ErrorKind::WouldBlock => {
+                        thread::sleep(POLL_INTERVAL);
+                    }
+                    Err(err) => {
+                        warn!("Failed to accept client: {}", err);
+                    }
+                }
+            }
+            info!("Sample server stopped");
+        })
+    };
+
+    Ok(SampleServer {
+        local_addr,
+        samples,
+        running,
+        clients,
+        channels,
+        thread: Some(thread),
+    })
+}
+
+/// Read samples from a single client, until it disconnects.
+///
+/// The channels of the client are given server wide ids, see `Channels`.
+fn serve_client(
+    client: usize,
+    stream: TcpStream,
+    sink: mpsc::SyncSender<LogRecord>,
+    channels: Arc<Mutex<Channels>>,
+) {
+    let peer = stream.peer_addr().ok();
+    info!("Client {:?} connected", peer);
+    let mut reader = match LogReader::new(stream) {
+        Ok(reader) => reader,
+        Err(err) => {
+            warn!("Client {:?} sent an invalid header: {}", peer, err);
+            return;
+        }
+    };
+
+    // The server wide id of each channel of this client, if it got one:
+    let mut ids: HashMap<u16, Option<u16>> = HashMap::new();
+    while let Some(record) = reader.next() {
+        match record {
+            Ok(mut record) => {
+                let mut channels = channels.lock().unwrap();
+                let id = *ids.entry(record.channel).or_insert_with(|| {
+                    let id = channels.assign(client, record.channel);
+                    if id.is_none() {
+                        warn!("No free channel id left for client {:?}", peer);
+                    }
+                    id
+                });
+                let id = match id {
+                    Some(id) => id,
+                    None => continue,
+                };
+
+                if let Some(name) = reader.channel_name(record.channel) {
+                    if channels.names.get(&id).map(|n| n.as_str()) != Some(name) {
+                        channels.names.insert(id, name.to_owned());
+                    }
+                }
+                drop(channels);
+
+                record.channel = id;
+                if sink.send(record).is_err() {
+                    // The server is gone.
+                    break;
+                }
+            }
+            Err(err) => {
+                warn!("Error receiving from client {:?}: {}", peer, err);
+                break;
+            }
+        }
+    }
+    channels.lock().unwrap().release(client);
+    info!("Client {:?} disconnected", peer);
+}
+
+impl SampleServer {
+    /// The address at which the server listens.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Take a received sample, if any.
+    pub fn try_recv(&self) -> Option<LogRecord> {
+        self.samples.try_recv().ok()
+    }
+
+    /// Wait at most the given time for a sample.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<LogRecord> {
+        self.samples.recv_timeout(timeout).ok()
+    }
+
+    /// Append all received samples to a series per channel.
+    ///
+    /// The series are keyed by the server wide channel ids. Series for new
+    /// channels are named as sent by the client, or like `channel 3` for
+    /// unnamed channels. They can be restyled as desired.
+    pub fn drain_into(&self, series: &mut BTreeMap<u16, Series>) {
+        while let Some(record) = self.try_recv() {
+            series
+                .entry(record.channel)
+                .or_insert_with(|| {
+                    let name = match self.channels.lock().unwrap().names.get(&record.channel) {
+                        Some(name) => name.clone(),
+                        None => format!("channel {}", record.channel),
+                    };
+                    Series::new(&name, vec![], Color::black())
+                })
+                .points
+                .push((record.timestamp, record.value));
+        }
+    }
+
+    /// Stop accepting clients, and disconnect the connected ones.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                warn!("Sample server thread panicked");
+            }
+        }
+
+        // No more clients are accepted now, disconnect the remaining ones:
+        for (_, client) in self.clients.lock().unwrap().drain() {
+            let _ = client.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+impl Drop for SampleServer {
+    fn drop(&mut self) {
+        if self.thread.is_some() {
+            self.shutdown();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::start_server;
+    use crate::io::{LogRecord, LogWriter};
+    use crate::net::SampleEncoder;
+    use std::collections::BTreeMap;
+    use std::io::Write;
+    use std::net::TcpStream;
+    use std::time::Duration;
+
+    fn receive(server: &super::SampleServer, count: usize) -> Vec<LogRecord> {
+        (0..count)
+            .map(|_| {
+                server
+                    .recv_timeout(Duration::from_secs(5))
+                    .expect("a sample")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn local_clients() {
+        let server = start_server("127.0.0.1:0").unwrap();
+
+        let mut first = LogWriter::new(TcpStream::connect(server.local_addr()).unwrap()).unwrap();
+        let mut second = LogWriter::new(TcpStream::connect(server.local_addr()).unwrap()).unwrap();
+        first.write(0.0, 1, 1.5).unwrap();
+        second.write(0.5, 2, -3.0).unwrap();
+        first.write(1.0, 1, 2.5).unwrap();
+        first.flush().unwrap();
+        second.flush().unwrap();
+
+        let mut records = receive(&server, 3);
+        records.sort_by(|a, b| a.timestamp.partial_cmp(&b.timestamp).unwrap());
+        assert_eq!(
+            vec![
+                LogRecord {
+                    timestamp: 0.0,
+                    channel: 1,
+                    value: 1.5
+                },
+                LogRecord {
+                    timestamp: 0.5,
+                    channel: 2,
+                    value: -3.0
+                },
+                LogRecord {
+                    timestamp: 1.0,
+                    channel: 1,
+                    value: 2.5
+                },
+            ],
+            records
+        );
+
+        server.stop();
+    }
+
+    #[test]
+    fn named_channels() {
+        let server = start_server("127.0.0.1:0").unwrap();
+        let mut encoder = SampleEncoder::new();
+        encoder
+            .add_batch("voltage", &[(0.0, 1.0), (1.0, 2.0)])
+            .unwrap();
+        encoder.add("current", 0.5, 0.25).unwrap();
+
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        stream.write_all(&encoder.take_frames()).unwrap();
+        stream.flush().unwrap();
+
+        let mut series = BTreeMap::new();
+        for _ in 0..500 {
+            server.drain_into(&mut series);
+            if series.values().map(|s| s.points.len()).sum::<usize>() == 3 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let names: Vec<&str> = series.values().map(|s| s.name.as_str()).collect();
+        assert_eq!(vec!["voltage", "current"], names);
+        assert_eq!(vec![(0.0, 1.0), (1.0, 2.0)], series[&0].points);
+    }
+
+    #[test]
+    fn same_channel_ids() {
+        let server = start_server("127.0.0.1:0").unwrap();
+
+        // Both clients send their first channel with id 0:
+        let mut streams = vec![];
+        for (name, value) in &[("voltage", 1.0), ("current", 2.0)] {
+            let mut encoder = SampleEncoder::new();
+            encoder.add(name, 0.0, *value).unwrap();
+            let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+            stream.write_all(&encoder.take_frames()).unwrap();
+            stream.flush().unwrap();
+            streams.push(stream);
+        }
+
+        let mut series = BTreeMap::new();
+        for _ in 0..500 {
+            server.drain_into(&mut series);
+            if series.len() == 2 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let mut named: Vec<(&str, Vec<(f64, f64)>)> = series
+            .values()
+            .map(|s| (s.name.as_str(), s.points.clone()))
+            .collect();
+        named.sort_by_key(|(name, _)| *name);
+        assert_eq!(
+            vec![("current", vec![(0.0, 2.0)]), ("voltage", vec![(0.0, 1.0)])],
+            named
+        );
+        assert!(series.contains_key(&0));
+    }
+
+    #[test]
+    fn disconnected_clients() {
+        let server = start_server("0.0.0.0:0").unwrap();
+        let port = server.local_addr().port();
+
+        let mut client = LogWriter::new(TcpStream::connect(("127.0.0.1", port)).unwrap()).unwrap();
+        client.write(0.0, 1, 1.0).unwrap();
+        client.flush().unwrap();
+        receive(&server, 1);
+        assert_eq!(1, server.clients.lock().unwrap().len());
+
+        drop(client);
+        for _ in 0..500 {
+            if server.clients.lock().unwrap().is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(server.clients.lock().unwrap().is_empty());
+
+        // Stopping does not depend on connecting to the listen address:
+        server.stop();
+    }
+
+    #[test]
+    fn partial_writes() {
+        let server = start_server("127.0.0.1:0").unwrap();
+
+        let mut data = LogWriter::new(vec![]).unwrap();
+        data.write(3.0, 7, 42.0).unwrap();
+        data.write(4.0, 7, 43.0).unwrap();
+        let data = data.into_inner();
+
+        // Send the data in small pieces, splitting header and records:
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        for chunk in data.chunks(5) {
+            stream.write_all(chunk).unwrap();
+            stream.flush().unwrap();
+            std::thread::sleep(Duration::from_millis(2));
+        }
+
+        let records = receive(&server, 2);
+        assert_eq!(43.0, records[1].value);
+
+        // Repeat the first record, and collect it into a series:
+        stream.write_all(&data[6..27]).unwrap();
+        let mut series = BTreeMap::new();
+        for _ in 0..500 {
+            server.drain_into(&mut series);
+            if !series.is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(vec![(3.0, 42.0)], series[&7].points);
+        assert_eq!("channel 7", series[&7].name);
+    }
+}
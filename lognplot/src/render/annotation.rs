@@ -0,0 +1,203 @@
+//! Annotations drawn on top of the plot, such as selections.
+
+use super::canvas::{HorizontalAnchor, VerticalAnchor};
+use super::{Canvas, ChartLayout, Transform};
+use crate::geometry::Point;
+use crate::style::{Color, Stroke};
+
+/// The axis along which an annotation is placed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+/// Space between a marker line and its label.
+const LABEL_PADDING: f64 = 3.0;
+
+/// Opacity of the shaded selection band.
+const SELECTION_ALPHA: f64 = 0.3;
+
+/// Shade a band across the height of the plot, between two x values.
+///
+/// The band is clipped to the plot area, and edge lines are drawn where
+/// the band starts and ends inside the plot. The x values may be given
+/// in any order.
+pub fn draw_x_selection<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    x0: f64,
+    x1: f64,
+    color: &Color,
+) where
+    C: Canvas,
+{
+    let plot_rect = layout.plot_rect();
+    let px0 = transform.apply_point(Point::new(x0, 0.0)).x();
+    let px1 = transform.apply_point(Point::new(x1, 0.0)).x();
+    if !px0.is_finite() || !px1.is_finite() {
+        return;
+    }
+
+    let (start, end) = if px0 <= px1 { (px0, px1) } else { (px1, px0) };
+    let left = start.max(plot_rect.left());
+    let right = end.min(plot_rect.right());
+    if left > right {
+        return;
+    }
+
+    canvas.save();
+    canvas.set_clip_rect(plot_rect.clone());
+    canvas.set_pen(color.clone(), SELECTION_ALPHA);
+    canvas.fill_rect(left, plot_rect.top(), right - left, plot_rect.height());
+
+    canvas.set_pen(color.clone(), 1.0);
+    canvas.set_line_width(1.0);
+    for edge in &[start, end] {
+        if plot_rect.left() <= *edge && *edge <= plot_rect.right() {
+            canvas.draw_line(&[
+                Point::new(*edge, plot_rect.top()),
+                Point::new(*edge, plot_rect.bottom()),
+            ]);
+        }
+    }
+
+    canvas.restore();
+}
+
+/// Draw a reference line across the plot, at a value on the given axis.
+///
+/// A value on the x axis results in a vertical line, for example to mark an
+/// event, with the label at the top. A value on the y axis results in a
+/// horizontal line, for example at an alarm threshold, with the label at
+/// the right. Nothing is drawn when the value is outside of the plot.
+pub fn draw_marker_line<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    axis: Axis,
+    value: f64,
+    label: Option<&str>,
+    stroke: &Stroke,
+) where
+    C: Canvas,
+{
+    let plot_rect = layout.plot_rect();
+    let (start, end, label_position, h_anchor, v_anchor) = match axis {
+        Axis::X => {
+            let x = transform.apply_point(Point::new(value, 0.0)).x();
+            if !(plot_rect.left() <= x && x <= plot_rect.right()) {
+                return;
+            }
+            (
+                Point::new(x, plot_rect.top()),
+                Point::new(x, plot_rect.bottom()),
+                Point::new(x + LABEL_PADDING, plot_rect.top() + LABEL_PADDING),
+                HorizontalAnchor::Left,
+                VerticalAnchor::Top,
+            )
+        }
+        Axis::Y => {
+            let y = transform.apply_point(Point::new(0.0, value)).y();
+            if !(plot_rect.top() <= y && y <= plot_rect.bottom()) {
+                return;
+            }
+            (
+                Point::new(plot_rect.left(), y),
+                Point::new(plot_rect.right(), y),
+                Point::new(plot_rect.right() - LABEL_PADDING, y - LABEL_PADDING),
+                HorizontalAnchor::Right,
+                VerticalAnchor::Bottom,
+            )
+        }
+    };
+
+    canvas.save();
+    canvas.set_clip_rect(plot_rect);
+    canvas.set_stroke(stroke, 1.0);
+    canvas.draw_line(&[start, end]);
+
+    if let Some(label) = label {
+        canvas.print_text(&label_position, h_anchor, v_anchor, label);
+    }
+    canvas.restore();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_marker_line, draw_x_selection, Axis};
+    use crate::render::test_util::{layout_and_transform, render_svg};
+    use crate::style::{Color, Stroke};
+
+    fn render(x0: f64, x1: f64) -> String {
+        let (layout, transform) = layout_and_transform((0.0, 10.0), (0.0, 1.0));
+        render_svg(|canvas| {
+            draw_x_selection(canvas, &layout, &transform, x0, x1, &Color::new(0, 0, 255))
+        })
+    }
+
+    #[test]
+    fn band_between_values() {
+        // The plot spans 140 to 790 pixels horizontally, and 10 to 510 vertically.
+        let svg = render(6.0, 2.0);
+
+        assert!(
+            svg.contains(r#"<polygon points="270,10 530,10 530,510 270,510""#),
+            "{}",
+            svg
+        );
+        assert!(svg.contains("fill-opacity:0.3"));
+        assert!(svg.contains("M 270 10 L 270 510"));
+        assert!(svg.contains("M 530 10 L 530 510"));
+    }
+
+    fn render_marker(axis: Axis, value: f64, label: Option<&str>, stroke: &Stroke) -> String {
+        let (layout, transform) = layout_and_transform((0.0, 10.0), (0.0, 1.0));
+        render_svg(|canvas| {
+            draw_marker_line(canvas, &layout, &transform, axis, value, label, stroke)
+        })
+    }
+
+    #[test]
+    fn threshold() {
+        let stroke = Stroke::new(Color::red(), 1.0);
+        let svg = render_marker(Axis::Y, 0.75, Some("alarm"), &stroke);
+
+        assert!(svg.contains("M 140 135 L 790 135"), "{}", svg);
+        assert!(svg.contains(">alarm</text>"));
+
+        let svg = render_marker(Axis::Y, 1.5, Some("alarm"), &stroke);
+        assert!(!svg.contains("<path"));
+        assert!(!svg.contains("alarm"));
+    }
+
+    #[test]
+    fn dashed_event_marker() {
+        let stroke = Stroke::new(Color::red(), 1.0).with_dash(&[10.0, 15.0]);
+        let svg = render_marker(Axis::X, 2.0, None, &stroke);
+
+        assert!(
+            svg.contains("M 270 10 L 270 510\" style=\"stroke:rgb(255,0,0);stroke-width:1;stroke-dasharray:10,15"),
+            "{}",
+            svg
+        );
+        assert!(!svg.contains("</text>"));
+
+        // A pattern which cannot be drawn gives a solid line:
+        let stroke = Stroke::new(Color::red(), 1.0).with_dash(&[10.0, 0.0]);
+        let svg = render_marker(Axis::X, 2.0, None, &stroke);
+        assert!(svg.contains("M 270 10 L 270 510"), "{}", svg);
+        assert!(!svg.contains("stroke-dasharray"));
+    }
+
+    #[test]
+    fn band_outside_plot() {
+        let svg = render(8.0, 20.0);
+        assert!(svg.contains(r#"<polygon points="660,10 790,10 790,510 660,510""#));
+        assert_eq!(1, svg.matches("<path").count());
+
+        let svg = render(12.0, 20.0);
+        assert!(!svg.contains("<polygon"));
+    }
+}
@@ -0,0 +1,213 @@
+//! Determine axis ranges which fit a set of traces.
+
+use super::{AxisSide, Series};
+use crate::geometry::Range;
+
+/// Default padding, as a fraction of the data range, added on both sides.
+const DEFAULT_PADDING: f64 = 0.05;
+
+/// Margin, as a fraction of the value, on both sides of a range of equal values.
+const EQUAL_VALUES_MARGIN: f64 = 0.05;
+
+/// Calculate the x and y ranges which fit all given traces.
+///
+/// The ranges are padded by 5% on each side, so the extreme values are
+/// not drawn on the edge of the plot.
+pub fn autoscale(traces: &[&[(f64, f64)]]) -> (Range<f64>, Range<f64>) {
+    autoscale_with_padding(traces, DEFAULT_PADDING)
+}
+
+/// Calculate the x and y ranges which fit all given traces, padded by
+/// the given fraction of the data range on each side.
+///
+/// Non-finite values, such as NaN, are skipped.
+/// When there is no data at all, a unit range is returned. When all values
+/// are equal, or only differ by rounding errors, the range extends by 5% of
+/// the value on both sides instead.
+pub fn autoscale_with_padding(traces: &[&[(f64, f64)]], padding: f64) -> (Range<f64>, Range<f64>) {
+    let mut x_range: Option<Range<f64>> = None;
+    let mut y_range: Option<Range<f64>> = None;
+    for &(x, y) in traces.iter().flat_map(|trace| trace.iter()) {
+        extend(&mut x_range, x);
+        extend(&mut y_range, y);
+    }
+
+    (pad(x_range, padding), pad(y_range, padding))
+}
+
+/// Ranges which fit series plotted against a left and a right y axis.
+#[derive(Clone, Debug)]
+pub struct AxisRanges {
+    /// The x range, shared by the series on both sides.
+    pub x: Range<f64>,
+    pub left: Range<f64>,
+    pub right: Range<f64>,
+}
+
+/// Calculate the ranges which fit the series, with a y range per axis side.
+///
+/// Like `autoscale`, the ranges are padded by 5% on each side.
+pub fn autoscale_series(series: &[Series]) -> AxisRanges {
+    autoscale_series_with_padding(series, DEFAULT_PADDING)
+}
+
+/// Calculate the ranges which fit the series, padded by the given fraction.
+///
+/// The y values of a series only extend the range of its own axis, so the
+/// series on the other side do not influence it. Invisible series are
+/// skipped. A side without data gets a unit range, like `autoscale_with_padding`.
+pub fn autoscale_series_with_padding(series: &[Series], padding: f64) -> AxisRanges {
+    let mut x_range: Option<Range<f64>> = None;
+    let mut left_range: Option<Range<f64>> = None;
+    let mut right_range: Option<Range<f64>> = None;
+    for series in series.iter().filter(|s| s.visible) {
+        let y_range = match series.axis {
+            AxisSide::Left => &mut left_range,
+            AxisSide::Right => &mut right_range,
+        };
+        for &(x, y) in &series.points {
+            extend(&mut x_range, x);
+            extend(y_range, y);
+        }
+    }
+
+    AxisRanges {
+        x: pad(x_range, padding),
+        left: pad(left_range, padding),
+        right: pad(right_range, padding),
+    }
+}
+
+fn extend(range: &mut Option<Range<f64>>, value: f64) {
+    if !value.is_finite() {
+        return;
+    }
+
+    let value = Range::new(value, value);
+    *range = Some(match range {
+        Some(range) => range.union(&value),
+        None => value,
+    });
+}
+
+fn pad(range: Option<Range<f64>>, padding: f64) -> Range<f64> {
+    match range {
+        Some(range) => {
+            let magnitude = range.begin().abs().max(range.end().abs());
+            let range = if range.length() <= f64::EPSILON * magnitude {
+                let center = (range.begin() + range.end()) / 2.0;
+                let margin = (center.abs() * EQUAL_VALUES_MARGIN).max(f64::MIN_POSITIVE);
+                Range::new(center - margin, center + margin)
+            } else {
+                range
+            };
+            range.expanded(padding)
+        }
+        None => Range::new(0.0, 1.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{autoscale, autoscale_series_with_padding, autoscale_with_padding};
+    use crate::render::{AxisSide, Series};
+    use crate::style::Color;
+
+    #[test]
+    fn fit_traces() {
+        let a = [(0.0, 1.0), (5.0, -3.0)];
+        let b = [(10.0, 7.0)];
+        let (x, y) = autoscale(&[&a, &b]);
+
+        assert!((x.begin() + 0.5).abs() < 1.0e-12);
+        assert!((x.end() - 10.5).abs() < 1.0e-12);
+        assert!((y.begin() + 3.5).abs() < 1.0e-12);
+        assert!((y.end() - 7.5).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn without_padding() {
+        let a = [(2.0, 3.0), (4.0, 9.0)];
+        let (x, y) = autoscale_with_padding(&[&a], 0.0);
+        assert_eq!((2.0, 4.0), (x.begin(), x.end()));
+        assert_eq!((3.0, 9.0), (y.begin(), y.end()));
+    }
+
+    #[test]
+    fn empty_input() {
+        let (x, y) = autoscale(&[]);
+        assert_eq!((0.0, 1.0), (x.begin(), x.end()));
+        assert_eq!((0.0, 1.0), (y.begin(), y.end()));
+
+        let (x, y) = autoscale(&[&[], &[]]);
+        assert_eq!((0.0, 1.0), (x.begin(), x.end()));
+        assert_eq!((0.0, 1.0), (y.begin(), y.end()));
+    }
+
+    #[test]
+    fn skip_non_finite() {
+        let a = [
+            (0.0, 1.0),
+            (1.0, f64::NAN),
+            (f64::INFINITY, 5.0),
+            (2.0, -1.0),
+        ];
+        let (x, y) = autoscale_with_padding(&[&a], 0.0);
+        assert_eq!((0.0, 2.0), (x.begin(), x.end()));
+        assert_eq!((-1.0, 5.0), (y.begin(), y.end()));
+
+        let (x, y) = autoscale(&[&[(f64::NAN, f64::NEG_INFINITY)]]);
+        assert_eq!((0.0, 1.0), (x.begin(), x.end()));
+        assert_eq!((0.0, 1.0), (y.begin(), y.end()));
+    }
+
+    #[test]
+    fn per_axis_side() {
+        let left = Series::new("left", vec![(0.0, 0.0), (5.0, 10.0)], Color::red());
+        let mut right = Series::new("right", vec![(2.0, 0.0), (8.0, 1000.0)], Color::green());
+        right.axis = AxisSide::Right;
+        let mut hidden = Series::new("hidden", vec![(-10.0, -1.0e6)], Color::red());
+        hidden.visible = false;
+
+        let ranges = autoscale_series_with_padding(&[left.clone(), right, hidden], 0.0);
+        assert_eq!((0.0, 8.0), (ranges.x.begin(), ranges.x.end()));
+        assert_eq!((0.0, 10.0), (ranges.left.begin(), ranges.left.end()));
+        assert_eq!((0.0, 1000.0), (ranges.right.begin(), ranges.right.end()));
+
+        // Without series on the right:
+        let ranges = autoscale_series_with_padding(&[left], 0.0);
+        assert_eq!((0.0, 10.0), (ranges.left.begin(), ranges.left.end()));
+        assert_eq!((0.0, 1.0), (ranges.right.begin(), ranges.right.end()));
+    }
+
+    #[test]
+    fn equal_values() {
+        let a = [(3.0, 42.0), (3.0, 42.0)];
+        let (x, y) = autoscale_with_padding(&[&a], 0.0);
+        assert!((x.begin() - 2.85).abs() < 1.0e-12);
+        assert!((x.end() - 3.15).abs() < 1.0e-12);
+        assert!((y.begin() - 39.9).abs() < 1.0e-12);
+        assert!((y.end() - 44.1).abs() < 1.0e-12);
+
+        // Zero still gets a range:
+        let (_, y) = autoscale_with_padding(&[&[(0.0, 0.0)]], 0.0);
+        assert!(y.begin() < 0.0 && y.end() > 0.0);
+    }
+
+    #[test]
+    fn equal_large_values() {
+        // Adding 0.5 to these values is lost in rounding:
+        let a = [(0.0, 1.0e16), (1.0, 1.0e16)];
+        let (_, y) = autoscale_with_padding(&[&a], 0.0);
+        assert!(y.begin() < 1.0e16 && y.end() > 1.0e16);
+        assert!((y.length() - 1.0e15).abs() < 1.0);
+    }
+
+    #[test]
+    fn tiny_range() {
+        // A real range, which is not mistaken for equal values:
+        let a = [(0.0, 1.0e-18), (1.0, 2.0e-18)];
+        let (_, y) = autoscale_with_padding(&[&a], 0.0);
+        assert_eq!((1.0e-18, 2.0e-18), (y.begin(), y.end()));
+    }
+}
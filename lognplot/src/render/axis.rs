@@ -0,0 +1,356 @@
+//! Axis along the plot area, with tick marks and labels.
+
+use super::canvas::{HorizontalAnchor, VerticalAnchor};
+use super::{format_tick, Axis, Canvas, ChartLayout, ChartOptions, TickFormat, Transform};
+use crate::geometry::Point;
+use crate::style::Color;
+
+/// Opacity of the minor tick marks.
+const MINOR_TICK_ALPHA: f64 = 0.5;
+
+/// Which way the tick marks point, seen from the plot area.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TickDirection {
+    /// Into the plot area.
+    In,
+
+    /// Into the legend space, away from the data.
+    #[default]
+    Out,
+
+    /// Across the axis, half in and half out.
+    Cross,
+}
+
+/// Direction and length of the tick marks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TickStyle {
+    pub direction: TickDirection,
+    pub length: f64,
+}
+
+impl TickStyle {
+    pub fn new(direction: TickDirection, length: f64) -> Self {
+        TickStyle { direction, length }
+    }
+
+    /// Start and end of a tick mark, as distances from the axis away from the plot area.
+    pub(crate) fn span(&self, length: f64) -> (f64, f64) {
+        match self.direction {
+            TickDirection::In => (0.0, -length),
+            TickDirection::Out => (0.0, length),
+            TickDirection::Cross => (-length / 2.0, length / 2.0),
+        }
+    }
+}
+
+impl Default for TickStyle {
+    fn default() -> Self {
+        TickStyle::new(TickDirection::default(), 7.0)
+    }
+}
+
+/// Styling of the axis drawn by `draw_x_axis` and `draw_y_axis`.
+#[derive(Debug, Clone)]
+pub struct AxisOptions {
+    /// Color of the axis line and the tick marks.
+    pub line_color: Color,
+
+    /// Color of the tick labels.
+    pub text_color: Color,
+
+    /// Direction and length of the tick marks.
+    pub tick_style: TickStyle,
+
+    /// Distance between the plot area and the tick labels.
+    pub label_offset: f64,
+
+    /// Length of the minor tick marks, which point like the major ones, but are drawn lighter.
+    pub minor_tick_size: f64,
+
+    /// Draw the axis line along the edge of the plot area.
+    pub spine: bool,
+
+    /// Draw the tick labels, and not only the marks.
+    pub labels: bool,
+}
+
+impl AxisOptions {
+    /// Axis in the colors of the theme, with the ticks of the chart options.
+    ///
+    /// The labels are placed at twice the tick size, in the space which the
+    /// chart layout reserves for them.
+    pub fn from_options(options: &ChartOptions) -> Self {
+        AxisOptions {
+            line_color: options.theme.axis.clone(),
+            text_color: options.theme.text.clone(),
+            tick_style: options.tick_style,
+            label_offset: options.tick_size * 2.0,
+            minor_tick_size: options.tick_style.length / 2.0,
+            spine: true,
+            labels: true,
+        }
+    }
+}
+
+impl Default for AxisOptions {
+    fn default() -> Self {
+        AxisOptions::from_options(&ChartOptions::default())
+    }
+}
+
+/// Draw the x axis below the plot area.
+///
+/// The ticks are given in data coordinates, and ticks outside of the plot
+/// are skipped. Labels are placed below the tick marks, in the space reserved
+/// for the x axis legend, at the label offset from the plot area.
+pub fn draw_x_axis<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    ticks: &[f64],
+    format: TickFormat,
+    options: &AxisOptions,
+) where
+    C: Canvas,
+{
+    let y = layout.plot_bottom;
+    canvas.set_line_width(1.0);
+    if options.spine {
+        canvas.set_pen(options.line_color.clone(), 1.0);
+        canvas.draw_line(&[
+            Point::new(layout.plot_left, y),
+            Point::new(layout.plot_right, y),
+        ]);
+    }
+
+    let (start, end) = options.tick_style.span(options.tick_style.length);
+    for tick in ticks {
+        let x = transform.apply_point(Point::new(*tick, 0.0)).x();
+        if !is_within(x, layout.plot_left, layout.plot_right) {
+            continue;
+        }
+
+        canvas.set_pen(options.line_color.clone(), 1.0);
+        canvas.draw_line(&[Point::new(x, y + start), Point::new(x, y + end)]);
+        if options.labels {
+            canvas.set_pen(options.text_color.clone(), 1.0);
+            canvas.print_text(
+                &Point::new(x, y + options.label_offset),
+                HorizontalAnchor::Middle,
+                VerticalAnchor::Top,
+                &format_tick(*tick, format),
+            );
+        }
+    }
+}
+
+/// Draw the y axis on the left of the plot area.
+///
+/// Like `draw_x_axis`, but the labels are placed left of the tick marks,
+/// in the space reserved for the y axis legend.
+pub fn draw_y_axis<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    ticks: &[f64],
+    format: TickFormat,
+    options: &AxisOptions,
+) where
+    C: Canvas,
+{
+    let x = layout.plot_left;
+    canvas.set_line_width(1.0);
+    if options.spine {
+        canvas.set_pen(options.line_color.clone(), 1.0);
+        canvas.draw_line(&[
+            Point::new(x, layout.plot_top),
+            Point::new(x, layout.plot_bottom),
+        ]);
+    }
+
+    let (start, end) = options.tick_style.span(options.tick_style.length);
+    for tick in ticks {
+        let y = transform.apply_point(Point::new(0.0, *tick)).y();
+        if !is_within(y, layout.plot_top, layout.plot_bottom) {
+            continue;
+        }
+
+        canvas.set_pen(options.line_color.clone(), 1.0);
+        canvas.draw_line(&[Point::new(x - start, y), Point::new(x - end, y)]);
+        if options.labels {
+            canvas.set_pen(options.text_color.clone(), 1.0);
+            canvas.print_text(
+                &Point::new(x - options.label_offset, y),
+                HorizontalAnchor::Right,
+                VerticalAnchor::Middle,
+                &format_tick(*tick, format),
+            );
+        }
+    }
+}
+
+/// Draw unlabeled minor tick marks along the x or y axis.
+///
+/// The marks are shorter than the major tick marks, and half transparent.
+pub fn draw_minor_ticks<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    axis: Axis,
+    ticks: &[f64],
+    options: &AxisOptions,
+) where
+    C: Canvas,
+{
+    if ticks.is_empty() {
+        return;
+    }
+
+    canvas.set_line_width(1.0);
+    canvas.set_pen(options.line_color.clone(), MINOR_TICK_ALPHA);
+    let (start, end) = options.tick_style.span(options.minor_tick_size);
+    for tick in ticks {
+        match axis {
+            Axis::X => {
+                let x = transform.apply_point(Point::new(*tick, 0.0)).x();
+                let y = layout.plot_bottom;
+                if is_within(x, layout.plot_left, layout.plot_right) {
+                    canvas.draw_line(&[Point::new(x, y + start), Point::new(x, y + end)]);
+                }
+            }
+            Axis::Y => {
+                let y = transform.apply_point(Point::new(0.0, *tick)).y();
+                let x = layout.plot_left;
+                if is_within(y, layout.plot_top, layout.plot_bottom) {
+                    canvas.draw_line(&[Point::new(x - start, y), Point::new(x - end, y)]);
+                }
+            }
+        }
+    }
+}
+
+/// Test if a pixel lies between two edges, allowing for some rounding.
+fn is_within(pixel: f64, low: f64, high: f64) -> bool {
+    pixel >= low - 1.0e-6 && pixel <= high + 1.0e-6
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        draw_minor_ticks, draw_x_axis, draw_y_axis, AxisOptions, TickDirection, TickStyle,
+    };
+    use crate::geometry::Point;
+    use crate::render::test_util::{layout_and_transform, render_svg};
+    use crate::render::{Axis, RecordingCanvas, TickFormat};
+
+    fn render(x_ticks: &[f64], y_ticks: &[f64], options: &AxisOptions) -> String {
+        let (layout, transform) = layout_and_transform((0.0, 10.0), (0.0, 100.0));
+        render_svg(|canvas| {
+            let format = TickFormat::Fixed(1);
+            draw_x_axis(canvas, &layout, &transform, x_ticks, format, options);
+            draw_y_axis(canvas, &layout, &transform, y_ticks, format, options);
+        })
+    }
+
+    fn count(svg: &str, pattern: &str) -> usize {
+        svg.lines().filter(|l| l.contains(pattern)).count()
+    }
+
+    #[test]
+    fn ticks_and_labels() {
+        let x_ticks = [0.0, 2.5, 5.0, 7.5, 10.0, 12.5];
+        let y_ticks = [-50.0, 0.0, 50.0, 100.0];
+        let svg = render(&x_ticks, &y_ticks, &AxisOptions::default());
+
+        // Ticks outside of the plot are skipped, the spines are extra lines.
+        // Touching lines are merged into a single path, so count the segments:
+        assert_eq!(5 + 3 + 2, svg.matches(" L ").count());
+        assert_eq!(5 + 3, count(&svg, "<text"));
+        assert!(svg.contains(">7.5<"));
+        assert!(svg.contains(">100.0<"));
+        assert!(!svg.contains(">12.5<"));
+    }
+
+    #[test]
+    fn inward_ticks_without_labels() {
+        let options = AxisOptions {
+            tick_style: TickStyle::new(TickDirection::In, 7.0),
+            spine: false,
+            labels: false,
+            ..AxisOptions::default()
+        };
+        let svg = render(&[5.0], &[], &options);
+
+        // The tick at x = 5 points up from the bottom of the plot:
+        assert!(svg.contains("M 465 510 L 465 503"), "{}", svg);
+        assert_eq!(1, count(&svg, "<path"));
+        assert_eq!(0, count(&svg, "<text"));
+    }
+
+    #[test]
+    fn tick_directions() {
+        let (layout, transform) = layout_and_transform((0.0, 10.0), (0.0, 100.0));
+
+        let tick_lines = |direction: TickDirection| {
+            let options = AxisOptions {
+                tick_style: TickStyle::new(direction, 6.0),
+                spine: false,
+                labels: false,
+                ..AxisOptions::default()
+            };
+            let mut canvas = RecordingCanvas::new();
+            let format = TickFormat::default();
+            draw_x_axis(&mut canvas, &layout, &transform, &[5.0], format, &options);
+            draw_y_axis(&mut canvas, &layout, &transform, &[50.0], format, &options);
+            let lines: Vec<Vec<Point>> = canvas.polylines().iter().map(|l| l.to_vec()).collect();
+            lines
+        };
+
+        // The plot area spans from (140, 10) to (790, 510):
+        let outward = tick_lines(TickDirection::Out);
+        assert_eq!(
+            vec![Point::new(465.0, 510.0), Point::new(465.0, 516.0)],
+            outward[0]
+        );
+        assert_eq!(
+            vec![Point::new(140.0, 260.0), Point::new(134.0, 260.0)],
+            outward[1]
+        );
+
+        let inward = tick_lines(TickDirection::In);
+        assert_eq!(
+            vec![Point::new(465.0, 510.0), Point::new(465.0, 504.0)],
+            inward[0]
+        );
+        assert_eq!(
+            vec![Point::new(140.0, 260.0), Point::new(146.0, 260.0)],
+            inward[1]
+        );
+
+        let cross = tick_lines(TickDirection::Cross);
+        assert_eq!(
+            vec![Point::new(465.0, 507.0), Point::new(465.0, 513.0)],
+            cross[0]
+        );
+        assert_eq!(
+            vec![Point::new(143.0, 260.0), Point::new(137.0, 260.0)],
+            cross[1]
+        );
+    }
+
+    #[test]
+    fn minor_ticks() {
+        let (layout, transform) = layout_and_transform((0.0, 10.0), (0.0, 100.0));
+        let svg = render_svg(|canvas| {
+            let options = AxisOptions::default();
+            let ticks = [1.0, 2.0, 20.0];
+            draw_minor_ticks(canvas, &layout, &transform, Axis::X, &ticks, &options);
+        });
+
+        // Half the length of a major tick mark:
+        assert!(svg.contains("M 205 510 L 205 513.5"), "{}", svg);
+        assert_eq!(2, svg.matches(" L ").count());
+        assert_eq!(0, count(&svg, "<text"));
+    }
+}
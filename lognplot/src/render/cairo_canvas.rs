@@ -1,15 +1,26 @@
-use super::canvas::{HorizontalAnchor, VerticalAnchor};
+use super::canvas::{HorizontalAnchor, TextOrientation, VerticalAnchor};
+use super::image;
 use super::Canvas;
-use crate::geometry::{Point, Size};
-use crate::style::Color;
+use crate::geometry::{Path, PathElement, Point, Rect, Size};
+use crate::style::{Color, Gradient, GradientKind};
 
 pub struct CairoCanvas<'a> {
     cr: &'a cairo::Context,
+
+    /// Gradient to use when filling.
+    gradient: Option<Gradient>,
+
+    /// Saved gradients, one for each saved state on the cairo context.
+    saved_gradients: Vec<Option<Gradient>>,
 }
 
 impl<'a> CairoCanvas<'a> {
     pub fn new(cr: &'a cairo::Context) -> Self {
-        Self { cr }
+        Self {
+            cr,
+            gradient: None,
+            saved_gradients: vec![],
+        }
     }
 }
 
@@ -23,16 +34,61 @@ impl<'a> CairoCanvas<'a> {
             self.cr.line_to(p.x(), p.y());
         }
     }
+
+    /// Fill the current path, using the gradient if we have one.
+    fn fill_path(&self) {
+        if let Some(gradient) = &self.gradient {
+            self.cr.save();
+            match &gradient.kind {
+                GradientKind::Linear { start, end } => {
+                    let pattern =
+                        cairo::LinearGradient::new(start.x(), start.y(), end.x(), end.y());
+                    add_color_stops(&pattern, gradient);
+                    self.cr.set_source(&pattern);
+                }
+                GradientKind::Radial { center, radius } => {
+                    let pattern = cairo::RadialGradient::new(
+                        center.x(),
+                        center.y(),
+                        0.0,
+                        center.x(),
+                        center.y(),
+                        *radius,
+                    );
+                    add_color_stops(&pattern, gradient);
+                    self.cr.set_source(&pattern);
+                }
+            }
+            self.cr.fill();
+            self.cr.restore();
+        } else {
+            self.cr.fill();
+        }
+    }
+}
+
+fn add_color_stops(pattern: &cairo::Gradient, gradient: &Gradient) {
+    for stop in &gradient.stops {
+        pattern.add_color_stop_rgba(
+            stop.offset,
+            stop.color.r() as f64 / 255.0,
+            stop.color.g() as f64 / 255.0,
+            stop.color.b() as f64 / 255.0,
+            stop.alpha,
+        );
+    }
 }
 
 impl<'a> Canvas for CairoCanvas<'a> {
     fn set_pen(&mut self, color: Color, alpha: f64) {
+        self.gradient = None;
         self.cr.set_source_rgba(
             color.r() as f64 / 255.0,
             color.g() as f64 / 255.0,
             color.b() as f64 / 255.0,
             alpha,
         );
+        self.cr.set_dash(&[], 0.0);
         // TODO: using a different filter might give better performance?
         // let pattern = self.cr.get_source();
         // println!("Filter {:?}", pattern.get_filter());
@@ -43,6 +99,10 @@ impl<'a> Canvas for CairoCanvas<'a> {
         self.cr.set_line_width(width);
     }
 
+    fn set_dash(&mut self, dash: &[f64]) {
+        self.cr.set_dash(dash, 0.0);
+    }
+
     fn draw_circle(&mut self, center: &Point, radius: f64) {
         self.cr.new_path();
         self.cr.arc(
@@ -55,6 +115,105 @@ impl<'a> Canvas for CairoCanvas<'a> {
         self.cr.stroke();
     }
 
+    fn draw_image(
+        &mut self,
+        top_left: Point,
+        width: f64,
+        height: f64,
+        rgba: &[u8],
+        img_w: u32,
+        img_h: u32,
+    ) {
+        if !image::is_valid_rgba(rgba, img_w, img_h) {
+            warn!("Invalid image data for a {}x{} image", img_w, img_h);
+            return;
+        }
+
+        let mut surface =
+            match cairo::ImageSurface::create(cairo::Format::ARgb32, img_w as i32, img_h as i32) {
+                Ok(surface) => surface,
+                Err(err) => {
+                    warn!("Unable to create image surface: {:?}", err);
+                    return;
+                }
+            };
+
+        // Cairo uses premultiplied alpha, in native endian 32 bit words:
+        let stride = surface.get_stride() as usize;
+        match surface.get_data() {
+            Ok(mut data) => {
+                for (y, row) in rgba.chunks(img_w as usize * 4).enumerate() {
+                    for (x, pixel) in row.chunks(4).enumerate() {
+                        let alpha = pixel[3] as u32;
+                        let premultiply = |value: u8| (value as u32 * alpha + 127) / 255;
+                        let word = (alpha << 24)
+                            | (premultiply(pixel[0]) << 16)
+                            | (premultiply(pixel[1]) << 8)
+                            | premultiply(pixel[2]);
+                        let index = y * stride + x * 4;
+                        data[index..index + 4].copy_from_slice(&word.to_ne_bytes());
+                    }
+                }
+            }
+            Err(err) => {
+                warn!("Unable to access image surface: {:?}", err);
+                return;
+            }
+        }
+
+        self.cr.save();
+        self.cr.translate(top_left.x(), top_left.y());
+        self.cr.scale(width / img_w as f64, height / img_h as f64);
+        self.cr.set_source_surface(&surface, 0.0, 0.0);
+
+        // Show the pixels as blocks, like the other canvases do:
+        self.cr.get_source().set_filter(cairo::Filter::Nearest);
+        self.cr.paint();
+        self.cr.restore();
+    }
+
+    fn draw_path(&mut self, path: &Path) {
+        self.cr.new_path();
+        let mut current = Point::new(0.0, 0.0);
+        let mut start = current;
+        for element in path.elements() {
+            match element {
+                PathElement::MoveTo(p) => {
+                    self.cr.move_to(p.x(), p.y());
+                    current = *p;
+                    start = *p;
+                }
+                PathElement::LineTo(p) => {
+                    self.cr.line_to(p.x(), p.y());
+                    current = *p;
+                }
+                PathElement::QuadTo(c, p) => {
+                    // Cairo has no quadratic curves, so elevate it to a cubic one.
+                    let c1x = current.x() + 2.0 / 3.0 * (c.x() - current.x());
+                    let c1y = current.y() + 2.0 / 3.0 * (c.y() - current.y());
+                    let c2x = p.x() + 2.0 / 3.0 * (c.x() - p.x());
+                    let c2y = p.y() + 2.0 / 3.0 * (c.y() - p.y());
+                    self.cr.curve_to(c1x, c1y, c2x, c2y, p.x(), p.y());
+                    current = *p;
+                }
+                PathElement::CubicTo(c1, c2, p) => {
+                    self.cr
+                        .curve_to(c1.x(), c1.y(), c2.x(), c2.y(), p.x(), p.y());
+                    current = *p;
+                }
+                PathElement::Close => {
+                    self.cr.close_path();
+                    current = start;
+                }
+            }
+        }
+        self.cr.stroke();
+    }
+
+    fn set_font_size(&mut self, size: f64) {
+        self.cr.set_font_size(size);
+    }
+
     fn text_size(&self, text: &str) -> Size {
         let extents = self.cr.text_extents(text);
         Size::new(extents.width, extents.height)
@@ -91,6 +250,26 @@ impl<'a> Canvas for CairoCanvas<'a> {
         self.cr.show_text(text);
     }
 
+    fn print_text_oriented(
+        &mut self,
+        p: &Point,
+        horizontal_anchor: HorizontalAnchor,
+        vertical_anchor: VerticalAnchor,
+        orientation: TextOrientation,
+        text: &str,
+    ) {
+        self.cr.save();
+        self.cr.translate(p.x(), p.y());
+        self.cr.rotate(orientation.radians());
+        self.print_text(
+            &Point::new(0.0, 0.0),
+            horizontal_anchor,
+            vertical_anchor,
+            text,
+        );
+        self.cr.restore();
+    }
+
     fn draw_line(&mut self, points: &[Point]) {
         if points.len() > 1 {
             self.make_path(points);
@@ -110,7 +289,72 @@ impl<'a> Canvas for CairoCanvas<'a> {
         if points.len() > 1 {
             self.make_path(points);
             self.cr.close_path();
-            self.cr.fill();
+            self.fill_path();
         }
     }
+
+    fn fill_circle(&mut self, center: &Point, radius: f64) {
+        self.cr.new_path();
+        self.cr.arc(
+            center.x(),
+            center.y(),
+            radius,
+            0.0,
+            2.0 * std::f64::consts::PI,
+        );
+        self.fill_path();
+    }
+
+    fn set_gradient(&mut self, gradient: &Gradient) {
+        self.gradient = Some(gradient.clone());
+    }
+
+    fn set_clip_rect(&mut self, rect: Rect) {
+        self.cr.reset_clip();
+        self.cr.new_path();
+        self.cr
+            .rectangle(rect.x(), rect.y(), rect.width(), rect.height());
+        self.cr.clip();
+    }
+
+    fn clear_clip(&mut self) {
+        self.cr.reset_clip();
+    }
+
+    fn save(&mut self) {
+        self.saved_gradients.push(self.gradient.clone());
+        self.cr.save();
+    }
+
+    fn restore(&mut self) {
+        // Cairo treats an unbalanced restore as an error, so guard against it.
+        if let Some(gradient) = self.saved_gradients.pop() {
+            self.gradient = gradient;
+            self.cr.restore();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CairoCanvas;
+    use crate::render::Canvas;
+    use crate::style::{Color, Stroke};
+
+    #[test]
+    fn dashed_stroke() {
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 20, 20).unwrap();
+        let cr = cairo::Context::new(&surface);
+        let mut canvas = CairoCanvas::new(&cr);
+
+        canvas.set_stroke(&Stroke::new(Color::red(), 1.0).with_dash(&[4.0, 2.0]), 1.0);
+        assert_eq!((vec![4.0, 2.0], 0.0), cr.get_dash());
+
+        // The next pen draws solid lines again:
+        canvas.set_pen(Color::red(), 1.0);
+        assert_eq!((vec![], 0.0), cr.get_dash());
+
+        canvas.set_stroke(&Stroke::new(Color::red(), 1.0).with_dash(&[4.0, 0.0]), 1.0);
+        assert_eq!((vec![], 0.0), cr.get_dash());
+    }
 }
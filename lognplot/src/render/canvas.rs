@@ -1,23 +1,77 @@
-use crate::geometry::{Point, Size};
-use crate::style::Color;
+use crate::geometry::{Path, Point, Rect, Size};
+use crate::style::{Color, Gradient, Stroke};
 
+/// Which side of the text lies at the given point, vertically.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum VerticalAnchor {
     Top,
     Middle,
     Bottom,
 }
 
+/// Which side of the text lies at the given point, horizontally.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum HorizontalAnchor {
     Left,
     Middle,
     Right,
 }
 
+/// Direction in which text is written.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextOrientation {
+    /// Normal, left to right text.
+    Horizontal,
+
+    /// Rotated a quarter turn counter clockwise, reading from bottom to top.
+    VerticalUp,
+
+    /// Rotated a quarter turn clockwise, reading from top to bottom.
+    VerticalDown,
+}
+
+impl TextOrientation {
+    /// The rotation angle on screen, in radians clockwise.
+    pub fn radians(self) -> f64 {
+        match self {
+            TextOrientation::Horizontal => 0.0,
+            TextOrientation::VerticalUp => -std::f64::consts::FRAC_PI_2,
+            TextOrientation::VerticalDown => std::f64::consts::FRAC_PI_2,
+        }
+    }
+}
+
 /// A generic canvas trait. Implement this trait to
 /// become a drawing canvas.
+///
+/// All render functions in this module are generic over the canvas, so
+/// implementing this trait is enough to draw charts with another backend,
+/// such as the canvas of a GUI toolkit. Coordinates are in pixels, with the
+/// origin in the top left corner; the render functions map data onto pixels
+/// with a `Transform` before drawing. The provided methods are built on the
+/// required ones, and can be overridden when the backend has a faster way.
 pub trait Canvas {
     fn set_pen(&mut self, color: Color, alpha: f64);
     fn set_line_width(&mut self, width: f64);
+
+    /// Dash the following lines, with alternating dash and gap lengths in pixels.
+    ///
+    /// An empty pattern draws solid lines, and so does the next `set_pen`.
+    /// The pattern must be valid, see `Stroke::is_dashed`. Canvases which
+    /// cannot draw dashes ignore this.
+    fn set_dash(&mut self, _dash: &[f64]) {}
+
+    /// Set the size of the font used by `print_text` and `text_size`.
+    ///
+    /// Canvases with a fixed font ignore this.
+    fn set_font_size(&mut self, _size: f64) {}
+
+    /// Attach a title to the elements drawn next, such as the name of a series.
+    ///
+    /// SVG output shows the title as a tooltip. Other canvases ignore this.
+    /// The title is part of the state kept by `save` and `restore`.
+    fn set_title(&mut self, _title: Option<&str>) {}
+
     fn print_text(
         &mut self,
         p: &Point,
@@ -25,12 +79,82 @@ pub trait Canvas {
         vertical_anchor: VerticalAnchor,
         text: &str,
     );
+
+    /// Print text in the given orientation.
+    ///
+    /// The anchors position the text as if it was horizontal, after which
+    /// the text is rotated around the given point. Canvases which cannot
+    /// rotate text print it horizontally, which is what the default does.
+    fn print_text_oriented(
+        &mut self,
+        p: &Point,
+        horizontal_anchor: HorizontalAnchor,
+        vertical_anchor: VerticalAnchor,
+        _orientation: TextOrientation,
+        text: &str,
+    ) {
+        self.print_text(p, horizontal_anchor, vertical_anchor, text);
+    }
+
     fn text_size(&self, text: &str) -> Size;
     fn draw_line(&mut self, points: &[Point]);
     fn draw_polygon(&mut self, points: &[Point]);
     fn draw_circle(&mut self, center: &Point, radius: f64);
+
+    /// Stroke a path made of lines and bezier curves.
+    fn draw_path(&mut self, path: &Path);
+
+    /// Fill a polygon with the current pen, including its alpha, or gradient.
+    ///
+    /// The polygon is closed implicitly, and filled using the
+    /// non-zero winding rule, so self intersecting polygons are
+    /// filled completely.
     fn fill_polygon(&mut self, points: &[Point]);
 
+    /// Fill a circle with the current pen or gradient.
+    fn fill_circle(&mut self, center: &Point, radius: f64);
+
+    /// Draw an image, scaled to the given width and height.
+    ///
+    /// The image is given as RGBA bytes, row by row, with a size in pixels
+    /// of `img_w` by `img_h`. Nothing is drawn when the amount of bytes
+    /// does not match this size.
+    fn draw_image(
+        &mut self,
+        top_left: Point,
+        width: f64,
+        height: f64,
+        rgba: &[u8],
+        img_w: u32,
+        img_h: u32,
+    );
+
+    /// Fill the following shapes with the given gradient, until the next `set_pen`.
+    fn set_gradient(&mut self, gradient: &Gradient);
+
+    /// Restrict all following drawing operations to the given rectangle.
+    fn set_clip_rect(&mut self, rect: Rect);
+
+    /// Remove the clipping region, so we can draw on the whole canvas again.
+    fn clear_clip(&mut self);
+
+    /// Push the current drawing state (pen, line width, dashes and clip) onto a stack.
+    fn save(&mut self);
+
+    /// Pop a drawing state saved with `save`.
+    ///
+    /// Restoring without a matching save is a no-op.
+    fn restore(&mut self);
+
+    /// Use the color, width, opacity and dashes of a stroke for the following lines.
+    ///
+    /// The opacity of the stroke is multiplied with the given alpha.
+    fn set_stroke(&mut self, stroke: &Stroke, alpha: f64) {
+        self.set_pen(stroke.color.clone(), alpha * stroke.opacity);
+        self.set_line_width(stroke.width);
+        self.set_dash(stroke.dash_pattern());
+    }
+
     fn draw_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
         let path = make_rect(x, y, width, height);
         self.draw_polygon(&path);
@@ -40,6 +164,36 @@ pub trait Canvas {
         let path = make_rect(x, y, width, height);
         self.fill_polygon(&path);
     }
+
+    /// Fill and/or stroke a rectangle with rounded corners.
+    ///
+    /// The radius is limited to half the width or height of the rectangle,
+    /// and a radius of 0 gives sharp corners. The pen is left at the last
+    /// used color.
+    fn draw_rounded_rect(
+        &mut self,
+        rect: &Rect,
+        radius: f64,
+        stroke: Option<Stroke>,
+        fill: Option<Color>,
+    ) {
+        let points = make_rounded_rect(rect, radius);
+        if let Some(color) = fill {
+            self.set_pen(color, 1.0);
+            self.fill_polygon(&points);
+        }
+        if let Some(stroke) = stroke {
+            self.set_stroke(&stroke, 1.0);
+            self.draw_polygon(&points);
+        }
+    }
+
+    /// Draw a cubic bezier curve from start to end via two control points.
+    fn draw_bezier(&mut self, start: &Point, control1: &Point, control2: &Point, end: &Point) {
+        let mut path = Path::new(*start);
+        path.curve_to(*control1, *control2, *end);
+        self.draw_path(&path);
+    }
 }
 
 /// Create a path from a rectangle definition.
@@ -51,3 +205,41 @@ fn make_rect(x: f64, y: f64, width: f64, height: f64) -> Vec<Point> {
 
     vec![top_left, top_right, bottom_right, bottom_left]
 }
+
+/// Amount of straight segments used for each rounded corner.
+const CORNER_SEGMENTS: usize = 8;
+
+/// Limit a corner radius to what fits in the rectangle.
+pub(crate) fn clamp_radius(rect: &Rect, radius: f64) -> f64 {
+    radius
+        .min(rect.width() / 2.0)
+        .min(rect.height() / 2.0)
+        .max(0.0)
+}
+
+/// Create a polygon for a rectangle with rounded corners.
+fn make_rounded_rect(rect: &Rect, radius: f64) -> Vec<Point> {
+    let radius = clamp_radius(rect, radius);
+    if radius == 0.0 {
+        return make_rect(rect.x(), rect.y(), rect.width(), rect.height());
+    }
+
+    // Corner centers, with the start angle of each corner, clockwise on screen:
+    let corners = [
+        (rect.right() - radius, rect.top() + radius, -0.5),
+        (rect.right() - radius, rect.bottom() - radius, 0.0),
+        (rect.left() + radius, rect.bottom() - radius, 0.5),
+        (rect.left() + radius, rect.top() + radius, 1.0),
+    ];
+    let mut points = Vec::with_capacity(4 * (CORNER_SEGMENTS + 1));
+    for (cx, cy, start) in &corners {
+        for step in 0..=CORNER_SEGMENTS {
+            let angle = (start + 0.5 * step as f64 / CORNER_SEGMENTS as f64) * std::f64::consts::PI;
+            points.push(Point::new(
+                cx + radius * angle.cos(),
+                cy + radius * angle.sin(),
+            ));
+        }
+    }
+    points
+}
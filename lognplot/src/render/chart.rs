@@ -3,7 +3,7 @@
 use super::canvas::{HorizontalAnchor, VerticalAnchor};
 use super::transform;
 use super::Canvas;
-use super::{ChartLayout, ChartOptions};
+use super::{snap_to_pixel, ChartLayout, ChartOptions};
 use crate::chart::{Chart, Cursor, Curve};
 use crate::geometry::Point;
 use crate::style::Color;
@@ -32,10 +32,10 @@ pub fn draw_chart<C>(
 }
 
 /// How much space there appears between x axis ticks
-const PIXELS_PER_X_TICK: usize = 100;
+pub(crate) const PIXELS_PER_X_TICK: usize = 100;
 
 /// How much pixels approximately to have between y ticks.
-const PIXELS_PER_Y_TICK: usize = 60;
+pub(crate) const PIXELS_PER_Y_TICK: usize = 60;
 
 /// Divide the width of the plot by this value, and draw at least that many data points.
 const PIXELS_PER_AGGREGATION: usize = 5;
@@ -86,6 +86,7 @@ where
 
     fn draw(&mut self) {
         self.fetch_curve_data();
+        self.canvas.set_font_size(self.options.theme.font_size);
         self.draw_axis();
         self.draw_box();
         self.draw_curves();
@@ -110,42 +111,59 @@ where
             self.canvas
                 .fill_rect(x, y - square_size / 2.0, square_size, square_size);
             let p = Point::new(x + dy, y);
-            self.canvas.set_pen(Color::black(), 1.0);
+            self.canvas.set_pen(self.options.theme.text.clone(), 1.0);
             self.canvas
                 .print_text(&p, HorizontalAnchor::Left, VerticalAnchor::Middle, &name);
             y += dy;
         }
     }
 
+    /// The title of the chart, or else the title of the options.
+    fn title(&self) -> Option<&'a String> {
+        let chart: &'a Chart = self.chart;
+        let options: &'a ChartOptions = self.options;
+        chart.title.as_ref().or(options.title.as_ref())
+    }
+
     /// Print title of chart
     fn draw_title(&mut self) {
-        if let Some(title) = &self.chart.title {
-            self.canvas.set_pen(Color::black(), 1.0);
-            let top_center = Point::new(self.layout.width / 2.0, self.options.padding);
+        if let Some(title) = self.title() {
+            self.canvas.set_pen(self.options.theme.text.clone(), 1.0);
+            self.canvas.set_font_size(self.options.title_font_size);
+            let top_center = Point::new(self.layout.width / 2.0, self.options.margins.top);
             self.canvas.print_text(
                 &top_center,
                 HorizontalAnchor::Middle,
                 VerticalAnchor::Top,
                 title,
             );
+            self.canvas.set_font_size(self.options.theme.font_size);
         }
     }
 
     /// Draw x and y axis with tick markers.
     fn draw_axis(&mut self) {
-        self.layout.layout(&self.options);
-
-        if let Some(title) = &self.chart.title {
+        // Reserve room for the title before the first layout, so the tick
+        // count is based on the actual plot height.
+        if let Some(title) = self.title() {
+            self.canvas.set_font_size(self.options.title_font_size);
             self.layout.title_height = self.canvas.text_size(title).height + self.options.padding;
+            self.canvas.set_font_size(self.options.theme.font_size);
         } else {
             self.layout.title_height = 0.0;
         }
 
+        self.layout.layout(self.options);
+
         let n_x_ticks = (self.layout.plot_width as usize / PIXELS_PER_X_TICK).max(2);
         let (prefix, x_ticks) = self.chart.x_axis.calc_date_tiks(n_x_ticks);
 
         let n_y_ticks = (self.layout.plot_height as usize / PIXELS_PER_Y_TICK).max(2);
-        let y_ticks = self.chart.y_axis.calc_tiks(n_y_ticks);
+        let (y_prefix, y_ticks) = self.chart.y_axis.calc_tiks_with_offset(n_y_ticks);
+        self.layout.y_axis_prefix_height = match &y_prefix {
+            Some(prefix) => self.canvas.text_size(prefix).height,
+            None => 0.0,
+        };
 
         // Now we have the ticks, calculate space taken by the ticks and re-layout!
         let y_labels_max_width = y_ticks
@@ -166,7 +184,11 @@ where
         self.layout.layout(&self.options);
 
         // Now we are ready to paint!
-        self.canvas.set_pen(Color::white(), 1.0);
+        let theme = &self.options.theme;
+        self.canvas.set_pen(theme.background.clone(), 1.0);
+        self.canvas
+            .fill_rect(0.0, 0.0, self.layout.width, self.layout.height);
+        self.canvas.set_pen(theme.plot_background.clone(), 1.0);
         self.canvas.fill_rect(
             self.layout.plot_left,
             self.layout.plot_top,
@@ -180,7 +202,7 @@ where
             .collect();
         self.draw_x_axis(prefix, &x_ticks);
 
-        self.draw_y_axis(&y_ticks);
+        self.draw_y_axis(y_prefix, &y_ticks);
 
         // Draw grid
         self.draw_grid(&x_ticks, &y_ticks);
@@ -188,7 +210,7 @@ where
 
     // X axis:
     fn draw_x_axis(&mut self, prefix: Option<String>, x_ticks: &[(TimeStamp, String)]) {
-        self.canvas.set_pen(Color::black(), 1.0);
+        self.canvas.set_pen(self.options.theme.text.clone(), 1.0);
         self.canvas.set_line_width(1.0);
 
         if let Some(title) = &self.chart.x_axis.label {
@@ -214,25 +236,36 @@ where
 
         // self.canvas.draw_line(&baseline);
 
+        let tick_style = self.options.tick_style;
+        let (start, end) = tick_style.span(tick_style.length);
         for (p, label) in x_ticks.iter() {
             let x = self.x_domain_to_pixel(p);
             let p1 = Point::new(x, y + self.options.tick_size * 2.0);
-            let p2 = Point::new(x, y);
-            let p3 = Point::new(x, y + self.options.tick_size);
+            let p2 = Point::new(x, y + start);
+            let p3 = Point::new(x, y + end);
             let horizontal_anchor = HorizontalAnchor::Middle;
 
+            self.canvas.set_pen(self.options.theme.text.clone(), 1.0);
             self.canvas
                 .print_text(&p1, horizontal_anchor, VerticalAnchor::Top, label);
             let line = vec![p2, p3];
+            self.canvas.set_pen(self.options.theme.axis.clone(), 1.0);
             self.canvas.draw_line(&line);
         }
     }
 
     // y axis:
-    fn draw_y_axis(&mut self, y_ticks: &[(f64, String)]) {
-        self.canvas.set_pen(Color::black(), 1.0);
+    fn draw_y_axis(&mut self, prefix: Option<String>, y_ticks: &[(f64, String)]) {
+        self.canvas.set_pen(self.options.theme.text.clone(), 1.0);
         self.canvas.set_line_width(1.0);
 
+        // Offset factored out of the tick labels, just above the top of the axis:
+        if let Some(prefix) = prefix {
+            let p = Point::new(self.layout.plot_left, self.layout.plot_top);
+            self.canvas
+                .print_text(&p, HorizontalAnchor::Right, VerticalAnchor::Bottom, &prefix);
+        }
+
         if let Some(title) = &self.chart.y_axis.label {
             let p = Point::new(10.0, self.layout.height / 2.0);
             self.canvas
@@ -247,26 +280,30 @@ where
 
         // self.canvas.draw_line(&baseline);
 
+        let tick_style = self.options.tick_style;
+        let (start, end) = tick_style.span(tick_style.length);
         for (p, label) in y_ticks.iter() {
             let y = self.y_domain_to_pixel(*p);
             let p1 = Point::new(x - self.options.tick_size * 2.0, y);
-            let p2 = Point::new(x, y);
-            let p3 = Point::new(x - self.options.tick_size, y);
+            let p2 = Point::new(x - start, y);
+            let p3 = Point::new(x - end, y);
+            self.canvas.set_pen(self.options.theme.text.clone(), 1.0);
             self.canvas
                 .print_text(&p1, HorizontalAnchor::Right, VerticalAnchor::Middle, label);
             let line = vec![p2, p3];
+            self.canvas.set_pen(self.options.theme.axis.clone(), 1.0);
             self.canvas.draw_line(&line);
         }
     }
 
     fn draw_grid(&mut self, x_ticks: &[(TimeStamp, String)], y_ticks: &[(f64, String)]) {
-        self.canvas.set_pen(Color::gray(), 1.0);
+        self.canvas.set_pen(self.options.theme.grid.clone(), 1.0);
         self.canvas.set_line_width(1.0);
 
         if self.chart.grid {
             // vertical grid lines:
             for (p, _) in x_ticks.iter() {
-                let x = self.x_domain_to_pixel(p);
+                let x = self.snap_grid_line(self.x_domain_to_pixel(p));
                 let p1 = Point::new(x, self.layout.plot_top);
                 let p2 = Point::new(x, self.layout.plot_bottom);
                 let line = vec![p1, p2];
@@ -275,7 +312,7 @@ where
 
             // horizontal grid lines:
             for (p, _) in y_ticks.iter() {
-                let y = self.y_domain_to_pixel(*p);
+                let y = self.snap_grid_line(self.y_domain_to_pixel(*p));
                 let p1 = Point::new(self.layout.plot_left, y);
                 let p2 = Point::new(self.layout.plot_right, y);
                 let line = vec![p1, p2];
@@ -284,6 +321,15 @@ where
         }
     }
 
+    /// Place a gridline on whole pixels, when enabled in the options.
+    fn snap_grid_line(&self, position: f64) -> f64 {
+        if self.options.snap_to_pixels {
+            snap_to_pixel(position, 1.0)
+        } else {
+            position
+        }
+    }
+
     /// Draw chart box
     fn draw_box(&mut self) {
         let top_left = Point::new(self.layout.plot_left, self.layout.plot_top);
@@ -292,7 +338,7 @@ where
         let bottom_right = Point::new(self.layout.plot_right, self.layout.plot_bottom);
 
         // Draw four lines:
-        self.canvas.set_pen(Color::black(), 1.0);
+        self.canvas.set_pen(self.options.theme.axis.clone(), 1.0);
         self.canvas.set_line_width(2.0);
         let outline = vec![top_left, top_right, bottom_right, bottom_left];
         self.canvas.draw_polygon(&outline);
@@ -329,6 +375,7 @@ where
                 self.layout.width - self.options.padding,
                 self.layout.height - self.options.padding,
             );
+            self.canvas.set_pen(self.options.theme.text.clone(), 1.0);
             self.canvas
                 .print_text(&p, HorizontalAnchor::Right, VerticalAnchor::Bottom, &text);
         }
@@ -341,7 +388,7 @@ where
         let bottom = Point::new(x, self.layout.plot_bottom);
         let points = vec![top, bottom];
 
-        self.canvas.set_pen(Color::black(), 1.0);
+        self.canvas.set_pen(self.options.theme.axis.clone(), 1.0);
         self.canvas.set_line_width(2.0);
         self.canvas.draw_line(&points);
 
@@ -349,6 +396,7 @@ where
             // cursor label:
             let label_top = Point::new(x, self.layout.height - self.options.padding);
             let label = self.chart.x_axis.get_cursor_label(&cursor);
+            self.canvas.set_pen(self.options.theme.text.clone(), 1.0);
             self.canvas.print_text(
                 &label_top,
                 HorizontalAnchor::Middle,
@@ -448,10 +496,11 @@ where
             background_width += padding * 3.0 + square_size;
             background_height += padding;
 
-            self.canvas.set_pen(Color::white(), 1.0);
+            self.canvas
+                .set_pen(self.options.theme.plot_background.clone(), 1.0);
             self.canvas
                 .fill_rect(x, y, background_width, background_height);
-            self.canvas.set_pen(Color::black(), 1.0);
+            self.canvas.set_pen(self.options.theme.axis.clone(), 1.0);
             self.canvas
                 .draw_rect(x, y, background_width, background_height);
 
@@ -464,7 +513,7 @@ where
                     .fill_rect(x + padding, y + padding, square_size, square_size);
 
                 // Draw labels:
-                self.canvas.set_pen(Color::black(), 1.0);
+                self.canvas.set_pen(self.options.theme.text.clone(), 1.0);
                 for label in labels {
                     let p = Point::new(
                         x + square_size + padding * 2.0,
@@ -486,6 +535,9 @@ where
     fn draw_curves(&mut self) {
         let pixels: usize = self.layout.plot_width as usize;
 
+        // Do not draw curves outside of the plot area:
+        self.canvas.set_clip_rect(self.layout.plot_rect());
+
         for curve in &self.chart.curves {
             // trace!("Plotting curve {:?}", curve);
 
@@ -523,6 +575,8 @@ where
                 }
             }
         }
+
+        self.canvas.clear_clip();
     }
 
     /// Fetch curve data from backing data store.
@@ -547,27 +601,31 @@ where
         color: Color,
         draw_markers: bool,
     ) {
-        let points: Vec<Point> = observations
-            .iter()
-            .map(|o| {
-                Point::new(
-                    self.x_domain_to_pixel(&o.timestamp),
-                    self.y_domain_to_pixel(o.value.value),
-                )
-            })
-            .collect();
-        trace!("Drawing {} points", points.len());
-
         self.canvas.set_pen(color, 1.0);
         self.canvas.set_line_width(2.0);
-        self.canvas.draw_line(&points);
 
-        // Draw markers as small solid square dots
-        // Idea from pulseview (sigrok application)
-        if draw_markers {
-            for point in points {
-                self.canvas
-                    .fill_rect(point.x() - 4.0, point.y() - 4.0, 8.0, 8.0);
+        // Non-finite values, such as dropouts, are gaps in the line:
+        for segment in split_at_gaps(observations, |o| o.value.value) {
+            let points: Vec<Point> = segment
+                .iter()
+                .map(|o| {
+                    Point::new(
+                        self.x_domain_to_pixel(&o.timestamp),
+                        self.y_domain_to_pixel(o.value.value),
+                    )
+                })
+                .collect();
+            trace!("Drawing {} points", points.len());
+
+            self.canvas.draw_line(&points);
+
+            // Draw markers as small solid square dots
+            // Idea from pulseview (sigrok application)
+            if draw_markers {
+                for point in points {
+                    self.canvas
+                        .fill_rect(point.x() - 4.0, point.y() - 4.0, 8.0, 8.0);
+                }
             }
         }
     }
@@ -842,6 +900,17 @@ where
     }
 }
 
+/// Split things into runs of finite values, dropping non-finite values.
+fn split_at_gaps<T, F>(things: &[T], f: F) -> Vec<&[T]>
+where
+    F: Fn(&T) -> f64,
+{
+    things
+        .split(|t| !f(t).is_finite())
+        .filter(|run| !run.is_empty())
+        .collect()
+}
+
 /// Find the last observation at the given time in a sorted list of observations.
 fn find_last_observation<'o, V>(
     observations: &'o [Observation<V>],
@@ -900,3 +969,161 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_chart, split_at_gaps};
+    use crate::chart::{Chart, Curve, CurveData};
+    use crate::geometry::Size;
+    use crate::render::{ChartLayout, ChartOptions, SvgOutput};
+    use crate::style::Theme;
+
+    #[test]
+    fn title_reserves_space() {
+        let options = ChartOptions::default();
+        let mut chart = Chart::default();
+        let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+        let mut buffer: Vec<u8> = vec![];
+        draw_chart(
+            &chart,
+            &mut SvgOutput::new(&mut buffer),
+            &mut layout,
+            &options,
+        );
+        assert_eq!(0.0, layout.title_height);
+        let plot_top = layout.plot_top;
+
+        chart.set_title("Measurements");
+        let mut buffer: Vec<u8> = vec![];
+        draw_chart(
+            &chart,
+            &mut SvgOutput::new(&mut buffer),
+            &mut layout,
+            &options,
+        );
+        let svg = String::from_utf8(buffer).unwrap();
+
+        // The SVG output uses a font height of 16 pixels:
+        assert_eq!(16.0 + options.padding, layout.title_height);
+        assert!((plot_top + layout.title_height - layout.plot_top).abs() < 1.0e-6);
+        assert!(svg.contains(">Measurements</text>"));
+
+        // Without a title of its own, the chart uses the title of the options:
+        let options = ChartOptions::builder().title("Sensors").build().unwrap();
+        let mut buffer: Vec<u8> = vec![];
+        draw_chart(
+            &Chart::default(),
+            &mut SvgOutput::new(&mut buffer),
+            &mut layout,
+            &options,
+        );
+        let svg = String::from_utf8(buffer).unwrap();
+        assert_eq!(16.0 + options.padding, layout.title_height);
+        assert!(svg.contains(">Sensors</text>"));
+    }
+
+    #[test]
+    fn dark_theme() {
+        let render = |theme: Theme| {
+            let options = ChartOptions {
+                theme,
+                ..ChartOptions::default()
+            };
+            let mut chart = Chart::default();
+            chart.add_curve(Curve::new(
+                CurveData::points(vec![0.0, 1.0], vec![0.0, 1.0]),
+                "red",
+            ));
+            chart.autoscale();
+            let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+            let mut buffer: Vec<u8> = vec![];
+            draw_chart(
+                &chart,
+                &mut SvgOutput::new(&mut buffer),
+                &mut layout,
+                &options,
+            );
+            String::from_utf8(buffer).unwrap()
+        };
+
+        let light = render(Theme::light());
+        assert!(light.contains("fill:rgb(255,255,255)"));
+        assert!(light.contains("stroke:rgb(120,120,120)"));
+
+        let dark = render(Theme::dark());
+        assert!(dark.contains("fill:rgb(30,30,30)"), "{}", dark);
+        assert!(dark.contains("fill:rgb(20,20,20)"));
+        assert!(dark.contains("stroke:rgb(70,70,70)"));
+        assert!(!dark.contains("fill:rgb(255,255,255)"));
+        assert!(!dark.contains("stroke:rgb(120,120,120)"));
+    }
+
+    #[test]
+    fn y_offset_annotation() {
+        let options = ChartOptions::default();
+        let mut chart = Chart::default();
+        chart.y_axis.set_limits(1.000023, 1.000041);
+        let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+        let mut buffer: Vec<u8> = vec![];
+        draw_chart(
+            &chart,
+            &mut SvgOutput::new(&mut buffer),
+            &mut layout,
+            &options,
+        );
+        let svg = String::from_utf8(buffer).unwrap();
+
+        assert!(svg.contains(">+1e0 ×1e-5</text>"), "{}", svg);
+        assert!(!svg.contains(">1.0000"), "{}", svg);
+
+        // The annotation is drawn above the plot, so there must be room for it:
+        assert_eq!(16.0, layout.y_axis_prefix_height);
+        assert!(layout.plot_top >= options.margins.top + layout.y_axis_prefix_height);
+    }
+
+    #[test]
+    fn gaps_split_runs() {
+        let values = [
+            1.0,
+            2.0,
+            f64::NAN,
+            3.0,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            4.0,
+            f64::NAN,
+        ];
+        let runs = split_at_gaps(&values, |v| *v);
+        assert_eq!(vec![&[1.0, 2.0][..], &[3.0][..], &[4.0][..]], runs);
+        assert!(split_at_gaps(&[f64::NAN], |v| *v).is_empty());
+    }
+
+    #[test]
+    fn nan_splits_line() {
+        let options = ChartOptions::default();
+        let mut chart = Chart::default();
+        let x = vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+        let y = vec![1.0, 2.0, 3.0, f64::NAN, 2.0, 1.0];
+        chart.add_curve(Curve::new(CurveData::points(x, y), "red"));
+        chart.autoscale();
+
+        let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+        let mut buffer: Vec<u8> = vec![];
+        draw_chart(
+            &chart,
+            &mut SvgOutput::new(&mut buffer),
+            &mut layout,
+            &options,
+        );
+        let svg = String::from_utf8(buffer).unwrap();
+
+        let lines: Vec<&str> = svg
+            .lines()
+            .filter(|l| l.contains("<path") && l.contains("stroke:rgb(255,0,0)"))
+            .collect();
+        assert_eq!(2, lines.len(), "{}", svg);
+        assert_eq!(2, lines[0].matches(" L ").count());
+        assert_eq!(1, lines[1].matches(" L ").count());
+        assert!(!svg.contains("NaN"));
+    }
+}
@@ -0,0 +1,160 @@
+//! Cursor lines with a value readout.
+
+use super::canvas::{HorizontalAnchor, VerticalAnchor};
+use super::{Canvas, ChartLayout, Transform};
+use crate::geometry::Point;
+use crate::style::{Color, Stroke, Theme};
+
+/// Styling of a cursor.
+#[derive(Debug, Clone)]
+pub struct CursorOptions {
+    /// Stroke used for the cursor lines.
+    pub stroke: Stroke,
+
+    /// When set, a horizontal cursor line is drawn at this y value as well.
+    pub y: Option<f64>,
+
+    /// Fill color of the box behind the label.
+    pub label_background: Color,
+
+    /// Space between the label text and its box.
+    pub label_padding: f64,
+}
+
+impl CursorOptions {
+    /// A cursor in the axis color of the theme, with its label on the plot background.
+    pub fn from_theme(theme: &Theme) -> Self {
+        CursorOptions {
+            stroke: Stroke::new(theme.axis.clone(), 1.0),
+            label_background: theme.plot_background.clone(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for CursorOptions {
+    fn default() -> Self {
+        CursorOptions {
+            stroke: Stroke::new(Color::black(), 1.0),
+            y: None,
+            label_background: Color::white(),
+            label_padding: 3.0,
+        }
+    }
+}
+
+/// Draw a vertical cursor line at the given x value, with a label near the top.
+///
+/// Nothing is drawn when x is outside of the plot area. The label is placed
+/// right of the cursor line, or left of it when there is no room.
+pub fn draw_cursor<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    x: f64,
+    label: &str,
+    options: &CursorOptions,
+) where
+    C: Canvas,
+{
+    let plot_rect = layout.plot_rect();
+    let cursor = transform.apply_point(Point::new(x, options.y.unwrap_or(0.0)));
+    if !cursor.x().is_finite() || cursor.x() < plot_rect.left() || cursor.x() > plot_rect.right() {
+        return;
+    }
+
+    canvas.save();
+    canvas.set_clip_rect(plot_rect.clone());
+    canvas.set_stroke(&options.stroke, 1.0);
+    canvas.draw_line(&[
+        Point::new(cursor.x(), plot_rect.top()),
+        Point::new(cursor.x(), plot_rect.bottom()),
+    ]);
+
+    if options.y.is_some() && plot_rect.top() <= cursor.y() && cursor.y() <= plot_rect.bottom() {
+        canvas.draw_line(&[
+            Point::new(plot_rect.left(), cursor.y()),
+            Point::new(plot_rect.right(), cursor.y()),
+        ]);
+    }
+
+    // Label box:
+    let padding = options.label_padding;
+    let text_size = canvas.text_size(label);
+    let box_width = text_size.width + 2.0 * padding;
+    let box_height = text_size.height + 2.0 * padding;
+    let box_left = if cursor.x() + padding + box_width <= plot_rect.right() {
+        cursor.x() + padding
+    } else {
+        cursor.x() - padding - box_width
+    };
+    let box_top = plot_rect.top() + padding;
+
+    canvas.set_pen(options.label_background.clone(), 0.8);
+    canvas.fill_rect(box_left, box_top, box_width, box_height);
+    canvas.set_stroke(&options.stroke, 1.0);
+    canvas.draw_rect(box_left, box_top, box_width, box_height);
+    canvas.print_text(
+        &Point::new(box_left + padding, box_top + padding),
+        HorizontalAnchor::Left,
+        VerticalAnchor::Top,
+        label,
+    );
+
+    canvas.restore();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_cursor, CursorOptions};
+    use crate::render::test_util::{layout_and_transform, render_svg};
+    use crate::style::{Color, Stroke};
+
+    fn render(x: f64, options: &CursorOptions) -> String {
+        let (layout, transform) = layout_and_transform((0.0, 10.0), (0.0, 1.0));
+        render_svg(|canvas| draw_cursor(canvas, &layout, &transform, x, "x = 4.2", options))
+    }
+
+    #[test]
+    fn cursor_and_label() {
+        let options = CursorOptions {
+            stroke: Stroke::new(Color::red(), 1.0),
+            ..CursorOptions::default()
+        };
+        let svg = render(4.2, &options);
+
+        let lines = svg
+            .lines()
+            .filter(|l| l.contains("<path") && l.contains("stroke:rgb(255,0,0)"))
+            .count();
+        assert_eq!(1, lines);
+        assert!(svg.contains(">x = 4.2</text>"));
+        assert!(svg.contains("<clipPath"));
+    }
+
+    #[test]
+    fn horizontal_line() {
+        let options = CursorOptions {
+            stroke: Stroke::new(Color::red(), 1.0),
+            y: Some(0.5),
+            ..CursorOptions::default()
+        };
+        let svg = render(4.2, &options);
+
+        let lines = svg
+            .lines()
+            .filter(|l| l.contains("<path") && l.contains("stroke:rgb(255,0,0)"))
+            .count();
+        assert_eq!(2, lines);
+    }
+
+    #[test]
+    fn outside_visible_range() {
+        let svg = render(12.0, &CursorOptions::default());
+        assert!(!svg.contains("<path"));
+        assert!(!svg.contains("<text"));
+
+        let svg = render(-0.1, &CursorOptions::default());
+        assert!(!svg.contains("<text"));
+    }
+}
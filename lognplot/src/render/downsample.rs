@@ -0,0 +1,255 @@
+//! Reduce large traces to a few points per pixel column.
+
+/// Summary of all points falling into a single bin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MinMaxBin {
+    /// Index of this bin, between 0 and the pixel count.
+    pub index: usize,
+
+    /// Smallest and largest y value in this bin.
+    pub min: f64,
+    pub max: f64,
+
+    /// The first and last point of this bin, in input order.
+    pub first: (f64, f64),
+    pub last: (f64, f64),
+
+    /// Amount of points in this bin.
+    pub count: usize,
+}
+
+/// How to reduce the points in a bin.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DownsampleMode {
+    /// Keep the envelope of the signal, see `downsample`.
+    #[default]
+    MinMax,
+
+    /// Average the points, see `downsample_mean`.
+    Mean,
+}
+
+impl DownsampleMode {
+    /// Reduce the points to a line with a few points per bin.
+    ///
+    /// In min/max mode, a bin with multiple points results in its first
+    /// point, its minimum and maximum at the center of the bin, and its last
+    /// point.
+    pub fn apply(self, points: &[(f64, f64)], pixel_count: usize) -> Vec<(f64, f64)> {
+        match self {
+            DownsampleMode::MinMax => {
+                let bins = match Bins::new(points, pixel_count) {
+                    Some(bins) => bins,
+                    None => return vec![],
+                };
+                let mut line = vec![];
+                for bin in downsample(points, pixel_count) {
+                    line.push(bin.first);
+                    if bin.count > 1 {
+                        let center = bins.center(bin.index);
+                        line.push((center, bin.min));
+                        line.push((center, bin.max));
+                        line.push(bin.last);
+                    }
+                }
+                line
+            }
+            DownsampleMode::Mean => downsample_mean(points, pixel_count),
+        }
+    }
+}
+
+/// Division of the x range of some points into bins of equal width.
+struct Bins {
+    x_min: f64,
+    x_span: f64,
+    count: usize,
+}
+
+impl Bins {
+    /// Span the finite points, or return None when there are none.
+    fn new(points: &[(f64, f64)], count: usize) -> Option<Self> {
+        if count == 0 {
+            return None;
+        }
+
+        let x_min = finite(points).map(|p| p.0).fold(f64::INFINITY, f64::min);
+        let x_max = finite(points)
+            .map(|p| p.0)
+            .fold(f64::NEG_INFINITY, f64::max);
+        if x_min > x_max {
+            return None;
+        }
+
+        Some(Bins {
+            x_min,
+            x_span: x_max - x_min,
+            count,
+        })
+    }
+
+    fn index(&self, x: f64) -> usize {
+        if self.x_span > 0.0 {
+            let index = ((x - self.x_min) / self.x_span * self.count as f64) as usize;
+            index.min(self.count - 1)
+        } else {
+            0
+        }
+    }
+
+    fn center(&self, index: usize) -> f64 {
+        self.x_min + (index as f64 + 0.5) * self.x_span / self.count as f64
+    }
+}
+
+fn finite(points: &[(f64, f64)]) -> impl Iterator<Item = &(f64, f64)> {
+    points
+        .iter()
+        .filter(|(x, y)| x.is_finite() && y.is_finite())
+}
+
+/// Bucket points by their x value into `pixel_count` bins of equal width.
+///
+/// For each bin, the minimum and maximum y value is kept, so spikes survive
+/// the downsampling. The first and last point allow drawing a line between
+/// the bins. Only non-empty bins are returned, ordered by x. Points with a
+/// non-finite x or y value are ignored.
+pub fn downsample(points: &[(f64, f64)], pixel_count: usize) -> Vec<MinMaxBin> {
+    let ranges = match Bins::new(points, pixel_count) {
+        Some(ranges) => ranges,
+        None => return vec![],
+    };
+
+    let mut bins: Vec<Option<MinMaxBin>> = vec![None; pixel_count];
+    for &(x, y) in finite(points) {
+        let index = ranges.index(x);
+        match &mut bins[index] {
+            Some(bin) => {
+                bin.min = bin.min.min(y);
+                bin.max = bin.max.max(y);
+                bin.last = (x, y);
+                bin.count += 1;
+            }
+            empty => {
+                *empty = Some(MinMaxBin {
+                    index,
+                    min: y,
+                    max: y,
+                    first: (x, y),
+                    last: (x, y),
+                    count: 1,
+                });
+            }
+        }
+    }
+
+    bins.into_iter().flatten().collect()
+}
+
+/// Bucket points like `downsample`, and average the y values in each bin.
+///
+/// Each non-empty bin results in a point at the center of the bin, with the
+/// mean y value. This smooths noisy signals, at the cost of hiding spikes.
+pub fn downsample_mean(points: &[(f64, f64)], pixel_count: usize) -> Vec<(f64, f64)> {
+    let ranges = match Bins::new(points, pixel_count) {
+        Some(ranges) => ranges,
+        None => return vec![],
+    };
+
+    let mut sums: Vec<(f64, usize)> = vec![(0.0, 0); pixel_count];
+    for &(x, y) in finite(points) {
+        let sum = &mut sums[ranges.index(x)];
+        sum.0 += y;
+        sum.1 += 1;
+    }
+
+    sums.into_iter()
+        .enumerate()
+        .filter(|(_, (_, count))| *count > 0)
+        .map(|(index, (sum, count))| (ranges.center(index), sum / count as f64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{downsample, downsample_mean, DownsampleMode};
+
+    #[test]
+    fn ramp() {
+        let points: Vec<(f64, f64)> = (0..1000).map(|i| (i as f64, i as f64 * 2.0)).collect();
+        let bins = downsample(&points, 10);
+
+        assert_eq!(10, bins.len());
+        for (index, bin) in bins.iter().enumerate() {
+            let first = index as f64 * 100.0;
+            let last = first + 99.0;
+            assert_eq!(index, bin.index);
+            assert_eq!(100, bin.count);
+            assert_eq!((first, first * 2.0), bin.first);
+            assert_eq!((last, last * 2.0), bin.last);
+            assert_eq!(first * 2.0, bin.min);
+            assert_eq!(last * 2.0, bin.max);
+        }
+    }
+
+    #[test]
+    fn spikes_survive() {
+        let mut points: Vec<(f64, f64)> = (0..100_000).map(|i| (i as f64 * 0.01, 1.0)).collect();
+        points[53_712].1 = 100.0;
+        points[80_001].1 = -50.0;
+        let bins = downsample(&points, 300);
+
+        assert_eq!(300, bins.len());
+        assert_eq!(1, bins.iter().filter(|b| b.max == 100.0).count());
+        assert_eq!(1, bins.iter().filter(|b| b.min == -50.0).count());
+        assert_eq!(100_000, bins.iter().map(|b| b.count).sum::<usize>());
+    }
+
+    #[test]
+    fn mean_of_bin() {
+        let points = [(0.0, 1.0), (0.1, 2.0), (0.2, 3.0), (1.0, 10.0)];
+        let means = downsample_mean(&points, 2);
+        assert_eq!(vec![(0.25, 2.0), (0.75, 10.0)], means);
+
+        // Empty bins are skipped:
+        let means = downsample_mean(&points, 3);
+        assert_eq!(2, means.len());
+        assert_eq!(2.0, means[0].1);
+        assert_eq!(10.0, means[1].1);
+
+        assert!(downsample_mean(&[], 10).is_empty());
+        assert_eq!(vec![(4.0, 5.0)], downsample_mean(&[(4.0, 5.0)], 3));
+    }
+
+    #[test]
+    fn modes() {
+        let points = [(0.0, 1.0), (0.1, 5.0), (0.2, -3.0), (1.0, 10.0)];
+        assert_eq!(
+            vec![
+                (0.0, 1.0),
+                (0.25, -3.0),
+                (0.25, 5.0),
+                (0.2, -3.0),
+                (1.0, 10.0)
+            ],
+            DownsampleMode::MinMax.apply(&points, 2)
+        );
+        assert_eq!(
+            vec![(0.25, 1.0), (0.75, 10.0)],
+            DownsampleMode::Mean.apply(&points, 2)
+        );
+        assert_eq!(DownsampleMode::MinMax, DownsampleMode::default());
+    }
+
+    #[test]
+    fn degenerate_input() {
+        assert!(downsample(&[], 10).is_empty());
+        assert!(downsample(&[(1.0, f64::NAN)], 10).is_empty());
+        assert!(downsample(&[(1.0, 2.0)], 0).is_empty());
+
+        let bins = downsample(&[(1.0, 2.0), (1.0, 3.0)], 10);
+        assert_eq!(1, bins.len());
+        assert_eq!(2.0, bins[0].min);
+        assert_eq!(3.0, bins[0].max);
+    }
+}
@@ -0,0 +1,237 @@
+//! Uncertainty of measured data, as error bars or a band around a line.
+
+use super::{Canvas, ChartLayout, Series, Transform};
+use crate::geometry::Point;
+use crate::style::Color;
+
+/// Width of the caps at the ends of an error bar, in pixels.
+const CAP_WIDTH: f64 = 6.0;
+
+/// Opacity of the band drawn by `draw_error_band`.
+const BAND_ALPHA: f64 = 0.25;
+
+/// The values to draw do not match the points of a series.
+#[derive(Debug, PartialEq)]
+pub struct LengthMismatch {
+    /// Amount of points in the series.
+    pub expected: usize,
+    pub actual: usize,
+}
+
+fn check_length(expected: usize, actual: usize) -> Result<(), LengthMismatch> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(LengthMismatch { expected, actual })
+    }
+}
+
+/// Draw a vertical error bar with caps at each point of a series.
+///
+/// Each bar spans the value plus and minus its error, so there must be an
+/// error for every point. Points with a non-finite value or error are
+/// skipped, and nothing is drawn for an invisible series. The bars of a
+/// dimmed series are gray.
+pub fn draw_error_bars<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    series: &Series,
+    errors: &[f64],
+) -> Result<(), LengthMismatch>
+where
+    C: Canvas,
+{
+    check_length(series.points.len(), errors.len())?;
+    if !series.visible {
+        return Ok(());
+    }
+
+    canvas.save();
+    canvas.set_clip_rect(layout.plot_rect());
+    canvas.set_pen(series.draw_color(), series.stroke.opacity);
+    canvas.set_line_width(series.stroke.width);
+
+    let half_cap = CAP_WIDTH / 2.0;
+    for (&(x, y), error) in series.points.iter().zip(errors) {
+        if !x.is_finite() || !y.is_finite() || !error.is_finite() {
+            continue;
+        }
+
+        let low = transform.apply_point(Point::new(x, y - error));
+        let high = transform.apply_point(Point::new(x, y + error));
+        canvas.draw_line(&[low, high]);
+        for end in [low, high].iter() {
+            canvas.draw_line(&[
+                Point::new(end.x() - half_cap, end.y()),
+                Point::new(end.x() + half_cap, end.y()),
+            ]);
+        }
+    }
+
+    canvas.restore();
+    Ok(())
+}
+
+/// Fill the area between a lower and an upper series with a translucent color.
+///
+/// The points of both series are matched by index, using the x values of the
+/// lower series, so the series must have the same length. A non-finite
+/// value in either series breaks the band at that point.
+pub fn draw_error_band<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    lower: &Series,
+    upper: &Series,
+    color: Color,
+) -> Result<(), LengthMismatch>
+where
+    C: Canvas,
+{
+    check_length(lower.points.len(), upper.points.len())?;
+
+    canvas.save();
+    canvas.set_clip_rect(layout.plot_rect());
+    canvas.set_pen(color, BAND_ALPHA);
+
+    let indices: Vec<usize> = (0..lower.points.len()).collect();
+    let runs = indices.split(|&index| {
+        let (x, low) = lower.points[index];
+        let high = upper.points[index].1;
+        !x.is_finite() || !low.is_finite() || !high.is_finite()
+    });
+    for run in runs.filter(|run| run.len() > 1) {
+        let point =
+            |index: usize, y: f64| transform.apply_point(Point::new(lower.points[index].0, y));
+        let mut outline: Vec<Point> = run.iter().map(|&i| point(i, upper.points[i].1)).collect();
+        outline.extend(run.iter().rev().map(|&i| point(i, lower.points[i].1)));
+        canvas.fill_polygon(&outline);
+    }
+
+    canvas.restore();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_error_band, draw_error_bars, LengthMismatch};
+    use crate::geometry::Point;
+    use crate::render::test_util::layout_and_transform;
+    use crate::render::{ChartLayout, DrawOp, RecordingCanvas, Series, Transform};
+    use crate::style::Color;
+
+    fn setup() -> (ChartLayout, Transform) {
+        layout_and_transform((0.0, 10.0), (0.0, 100.0))
+    }
+
+    #[test]
+    fn caps() {
+        let (layout, transform) = setup();
+        let series = Series::new(
+            "a",
+            vec![(1.0, 10.0), (2.0, 20.0), (3.0, 30.0)],
+            Color::red(),
+        );
+
+        let mut canvas = RecordingCanvas::new();
+        draw_error_bars(&mut canvas, &layout, &transform, &series, &[1.0, 2.0, 5.0]).unwrap();
+
+        // A bar and two caps per point, at 65 pixels per x unit, 5 pixels per y unit:
+        let lines = canvas.polylines();
+        assert_eq!(9, lines.len());
+        let caps: Vec<(f64, f64, f64)> = lines
+            .iter()
+            .filter(|l| l[0].y() == l[1].y())
+            .map(|l| (l[0].x(), l[1].x(), l[0].y()))
+            .collect();
+        assert_eq!(
+            vec![
+                (202.0, 208.0, 465.0),
+                (202.0, 208.0, 455.0),
+                (267.0, 273.0, 420.0),
+                (267.0, 273.0, 400.0),
+                (332.0, 338.0, 385.0),
+                (332.0, 338.0, 335.0),
+            ],
+            caps
+        );
+        assert_eq!(
+            &[Point::new(205.0, 465.0), Point::new(205.0, 455.0)],
+            lines[0]
+        );
+    }
+
+    #[test]
+    fn dimmed_bars() {
+        let (layout, transform) = setup();
+        let mut series = Series::new("a", vec![(1.0, 10.0)], Color::red());
+        series.dimmed = true;
+
+        let mut canvas = RecordingCanvas::new();
+        draw_error_bars(&mut canvas, &layout, &transform, &series, &[1.0]).unwrap();
+        assert!(canvas
+            .ops()
+            .contains(&DrawOp::SetPen(Color::red().to_grayscale(), 1.0)));
+    }
+
+    #[test]
+    fn length_mismatch() {
+        let (layout, transform) = setup();
+        let series = Series::new("a", vec![(1.0, 10.0), (2.0, 20.0)], Color::red());
+        let shorter = Series::new("b", vec![(1.0, 10.0)], Color::red());
+
+        let mut canvas = RecordingCanvas::new();
+        assert_eq!(
+            Err(LengthMismatch {
+                expected: 2,
+                actual: 3
+            }),
+            draw_error_bars(&mut canvas, &layout, &transform, &series, &[1.0; 3])
+        );
+        assert!(draw_error_band(
+            &mut canvas,
+            &layout,
+            &transform,
+            &series,
+            &shorter,
+            Color::red()
+        )
+        .is_err());
+        assert!(canvas.ops().is_empty());
+    }
+
+    #[test]
+    fn band() {
+        let (layout, transform) = setup();
+        let lower = Series::new("low", vec![(0.0, 0.0), (10.0, 20.0)], Color::red());
+        let upper = Series::new("high", vec![(0.0, 20.0), (10.0, 40.0)], Color::red());
+
+        let mut canvas = RecordingCanvas::new();
+        draw_error_band(
+            &mut canvas,
+            &layout,
+            &transform,
+            &lower,
+            &upper,
+            Color::green(),
+        )
+        .unwrap();
+
+        assert!(canvas.ops().contains(&DrawOp::SetPen(Color::green(), 0.25)));
+        let fills: Vec<&DrawOp> = canvas
+            .ops()
+            .iter()
+            .filter(|op| matches!(op, DrawOp::FillPolygon(_)))
+            .collect();
+        assert_eq!(
+            vec![&DrawOp::FillPolygon(vec![
+                Point::new(140.0, 410.0),
+                Point::new(790.0, 310.0),
+                Point::new(790.0, 410.0),
+                Point::new(140.0, 510.0),
+            ])],
+            fills
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Gridlines at tick positions.
+
+use super::{snap_to_pixel, Canvas, ChartLayout, Transform};
+use crate::geometry::Point;
+use crate::style::{Color, Stroke, Theme};
+
+/// Styling of the gridlines.
+#[derive(Debug, Clone)]
+pub struct GridOptions {
+    /// Stroke used for the gridlines.
+    pub stroke: Stroke,
+
+    /// Stroke used for the gridlines at minor ticks, see `draw_minor_grid`.
+    pub minor_stroke: Stroke,
+
+    /// When set, the lines where x or y is zero are drawn with this stroke.
+    pub zero_line: Option<Stroke>,
+
+    /// Place the gridlines such that they cover whole pixels.
+    pub snap_to_pixels: bool,
+}
+
+impl GridOptions {
+    /// Gridlines in the grid color of the theme.
+    pub fn from_theme(theme: &Theme) -> Self {
+        GridOptions {
+            stroke: Stroke::new(theme.grid.clone(), 1.0),
+            minor_stroke: Stroke::new(theme.grid.clone(), 1.0).with_opacity(0.4),
+            zero_line: None,
+            snap_to_pixels: false,
+        }
+    }
+
+    fn snap(&self, position: f64) -> f64 {
+        if self.snap_to_pixels {
+            snap_to_pixel(position, self.stroke.width)
+        } else {
+            position
+        }
+    }
+}
+
+impl Default for GridOptions {
+    fn default() -> Self {
+        GridOptions {
+            stroke: Stroke::new(Color::new(220, 220, 220), 1.0),
+            minor_stroke: Stroke::new(Color::new(240, 240, 240), 1.0),
+            zero_line: None,
+            snap_to_pixels: false,
+        }
+    }
+}
+
+/// Draw vertical gridlines at the x ticks and horizontal gridlines at the y ticks.
+///
+/// The ticks are given in data coordinates, and are mapped onto pixels
+/// using the transform. Gridlines outside of the plot area are skipped.
+pub fn draw_grid<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    x_ticks: &[f64],
+    y_ticks: &[f64],
+    options: &GridOptions,
+) where
+    C: Canvas,
+{
+    let plot_rect = layout.plot_rect();
+    canvas.save();
+    canvas.set_clip_rect(plot_rect.clone());
+
+    for x in x_ticks {
+        let x_pixel = options.snap(transform.apply_point(Point::new(*x, 0.0)).x());
+        if x_pixel < plot_rect.left() || x_pixel > plot_rect.right() {
+            continue;
+        }
+
+        set_stroke(canvas, options, *x);
+        canvas.draw_line(&[
+            Point::new(x_pixel, plot_rect.top()),
+            Point::new(x_pixel, plot_rect.bottom()),
+        ]);
+    }
+
+    for y in y_ticks {
+        let y_pixel = options.snap(transform.apply_point(Point::new(0.0, *y)).y());
+        if y_pixel < plot_rect.top() || y_pixel > plot_rect.bottom() {
+            continue;
+        }
+
+        set_stroke(canvas, options, *y);
+        canvas.draw_line(&[
+            Point::new(plot_rect.left(), y_pixel),
+            Point::new(plot_rect.right(), y_pixel),
+        ]);
+    }
+
+    canvas.restore();
+}
+
+/// Draw gridlines at minor ticks, with the minor stroke of the options.
+///
+/// This is meant to be drawn before the major gridlines, which then end
+/// up on top.
+pub fn draw_minor_grid<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    x_ticks: &[f64],
+    y_ticks: &[f64],
+    options: &GridOptions,
+) where
+    C: Canvas,
+{
+    if x_ticks.is_empty() && y_ticks.is_empty() {
+        return;
+    }
+
+    let minor = GridOptions {
+        stroke: options.minor_stroke.clone(),
+        zero_line: None,
+        ..options.clone()
+    };
+    draw_grid(canvas, layout, transform, x_ticks, y_ticks, &minor);
+}
+
+/// Select the zero-line stroke for a zero tick, and the normal stroke otherwise.
+fn set_stroke<C: Canvas>(canvas: &mut C, options: &GridOptions, tick: f64) {
+    let stroke = match &options.zero_line {
+        Some(zero_line) if tick == 0.0 => zero_line,
+        _ => &options.stroke,
+    };
+    canvas.set_stroke(stroke, 1.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_grid, draw_minor_grid, GridOptions};
+    use crate::render::test_util::{layout_and_transform, render_svg};
+    use crate::style::{Color, Stroke};
+
+    fn render(options: &GridOptions) -> String {
+        let (layout, transform) = layout_and_transform((-10.0, 10.0), (0.0, 100.0));
+        render_svg(|canvas| {
+            let x_ticks = [-15.0, -10.0, -5.0, 0.0, 5.0, 10.0, 15.0];
+            let y_ticks = [0.0, 50.0, 100.0, 150.0];
+            draw_grid(canvas, &layout, &transform, &x_ticks, &y_ticks, options);
+        })
+    }
+
+    fn count_lines(svg: &str, color: &str) -> usize {
+        svg.lines()
+            .filter(|l| l.contains("<path") && l.contains(color))
+            .count()
+    }
+
+    #[test]
+    fn gridlines() {
+        let svg = render(&GridOptions::default());
+
+        // 5 vertical and 3 horizontal lines fall within the plot:
+        assert_eq!(8, count_lines(&svg, "stroke:rgb(220,220,220)"));
+        assert!(svg.contains("<clipPath"));
+    }
+
+    #[test]
+    fn zero_line() {
+        let options = GridOptions {
+            zero_line: Some(Stroke::new(Color::black(), 2.0)),
+            ..GridOptions::default()
+        };
+        let svg = render(&options);
+
+        assert_eq!(6, count_lines(&svg, "stroke:rgb(220,220,220)"));
+        assert_eq!(2, count_lines(&svg, "stroke:rgb(0,0,0)"));
+    }
+
+    #[test]
+    fn snapping() {
+        // The x = 0 gridline lies at 465, in the middle of the plot:
+        let svg = render(&GridOptions::default());
+        assert!(svg.contains("M 465 10 L 465 510"), "{}", svg);
+
+        let options = GridOptions {
+            snap_to_pixels: true,
+            ..GridOptions::default()
+        };
+        let svg = render(&options);
+        assert!(svg.contains("M 465.5 10 L 465.5 510"), "{}", svg);
+    }
+
+    #[test]
+    fn minor_gridlines() {
+        let (layout, transform) = layout_and_transform((-10.0, 10.0), (0.0, 100.0));
+        let svg = render_svg(|canvas| {
+            let options = GridOptions {
+                zero_line: Some(Stroke::new(Color::black(), 2.0)),
+                ..GridOptions::default()
+            };
+            let x_ticks = [-7.5, 0.0, 2.5];
+            let y_ticks = [25.0];
+            draw_minor_grid(canvas, &layout, &transform, &x_ticks, &y_ticks, &options);
+        });
+
+        // Minor gridlines never use the zero line stroke:
+        assert_eq!(4, count_lines(&svg, "stroke:rgb(240,240,240)"));
+        assert_eq!(0, count_lines(&svg, "stroke:rgb(0,0,0)"));
+    }
+}
@@ -0,0 +1,107 @@
+//! Heatmaps, such as spectrograms, of a grid of values.
+
+use super::{Canvas, ChartLayout};
+use crate::geometry::{Point, Range};
+use crate::style::Colormap;
+
+/// Draw a grid of values as colored cells, filling the plot area.
+///
+/// The grid holds `rows` rows of `cols` values each. The first row is drawn
+/// at the bottom of the plot, so rows go up like the y axis does. Values
+/// are mapped onto the colormap using the value range, and non-finite
+/// values are left transparent.
+pub fn draw_heatmap<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    grid: &[f32],
+    cols: usize,
+    rows: usize,
+    colormap: Colormap,
+    value_range: Range<f64>,
+) where
+    C: Canvas,
+{
+    if cols == 0 || rows == 0 || grid.len() < cols * rows {
+        warn!("Heatmap grid too small for {}x{} cells", cols, rows);
+        return;
+    }
+
+    let domain = value_range.end() - value_range.begin();
+    let mut rgba = Vec::with_capacity(cols * rows * 4);
+    for row in grid[..cols * rows].chunks(cols).rev() {
+        for value in row {
+            let value = *value as f64;
+            if value.is_finite() {
+                let fraction = if domain == 0.0 {
+                    0.5
+                } else {
+                    (value - value_range.begin()) / domain
+                };
+                let color = colormap.sample(fraction);
+                rgba.extend(&[color.r(), color.g(), color.b(), 255]);
+            } else {
+                rgba.extend(&[0, 0, 0, 0]);
+            }
+        }
+    }
+
+    let plot_rect = layout.plot_rect();
+    canvas.save();
+    canvas.set_clip_rect(plot_rect.clone());
+    canvas.draw_image(
+        Point::new(plot_rect.left(), plot_rect.top()),
+        plot_rect.width(),
+        plot_rect.height(),
+        &rgba,
+        cols as u32,
+        rows as u32,
+    );
+    canvas.restore();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::draw_heatmap;
+    use crate::geometry::{Range, Size};
+    use crate::render::{ChartLayout, ChartOptions, RasterCanvas};
+    use crate::style::Colormap;
+
+    #[test]
+    fn four_cells() {
+        let mut layout = ChartLayout::new(Size::new(400.0, 300.0));
+        layout.layout(&ChartOptions::default());
+        let mut canvas = RasterCanvas::new(400, 300);
+        let grid = [0.0, 1.0, 2.0, 3.0];
+        draw_heatmap(
+            &mut canvas,
+            &layout,
+            &grid,
+            2,
+            2,
+            Colormap::Viridis,
+            Range::new(0.0, 3.0),
+        );
+
+        let x1 = (layout.plot_left + layout.plot_width * 0.25) as usize;
+        let x2 = (layout.plot_left + layout.plot_width * 0.75) as usize;
+        let y1 = (layout.plot_top + layout.plot_height * 0.25) as usize;
+        let y2 = (layout.plot_top + layout.plot_height * 0.75) as usize;
+
+        // The first row is at the bottom:
+        let colors = [
+            canvas.pixel(x1, y2),
+            canvas.pixel(x2, y2),
+            canvas.pixel(x1, y1),
+            canvas.pixel(x2, y1),
+        ];
+        assert_eq!([68, 1, 84, 255], colors[0]);
+        assert_eq!([253, 231, 37, 255], colors[3]);
+        for (index, color) in colors.iter().enumerate() {
+            assert!(colors[index + 1..].iter().all(|other| other != color));
+        }
+
+        // Outside of the plot nothing is drawn:
+        assert_eq!(0, canvas.pixel(x1, (layout.plot_bottom + 2.0) as usize)[3]);
+        assert_eq!(0, canvas.pixel((layout.plot_left - 2.0) as usize, y1)[3]);
+    }
+}
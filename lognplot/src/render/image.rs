@@ -0,0 +1,142 @@
+//! Encoding of images, for embedding them into other formats.
+
+/// Test if RGBA data holds exactly an image of the given size.
+///
+/// Empty images, and sizes which do not fit in memory, are not valid.
+pub fn is_valid_rgba(rgba: &[u8], width: u32, height: u32) -> bool {
+    let size = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(4));
+    matches!(size, Some(size) if size > 0 && size == rgba.len())
+}
+
+/// Encode RGBA pixels as PNG.
+///
+/// The image data is stored without compression, so this works without
+/// any external crate. This is fine for the small images we embed.
+pub fn encode_png(rgba: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+    let mut header = vec![];
+    header.extend(&width.to_be_bytes());
+    header.extend(&height.to_be_bytes());
+    // 8 bit depth, RGBA color, deflate, no filter and no interlacing:
+    header.extend(&[8, 6, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &header);
+
+    // Each row starts with its filter type, which is none:
+    let row_size = width as usize * 4;
+    let mut raw = Vec::with_capacity((row_size + 1) * height as usize);
+    for row in rgba.chunks(row_size).take(height as usize) {
+        raw.push(0);
+        raw.extend(row);
+    }
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(&crc.to_be_bytes());
+}
+
+/// Wrap data into a zlib stream, using uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut stream = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        stream.extend(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = if blocks.peek().is_none() { 1 } else { 0 };
+        let length = block.len() as u16;
+        stream.push(last);
+        stream.extend(&length.to_le_bytes());
+        stream.extend(&(!length).to_le_bytes());
+        stream.extend(block);
+    }
+    stream.extend(&adler32(data).to_be_bytes());
+    stream
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let mut a = 1_u32;
+    let mut b = 0_u32;
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+/// Encode data using the standard base64 alphabet, with padding.
+pub fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::new();
+    for group in data.chunks(3) {
+        let b0 = group[0] as u32;
+        let b1 = group.get(1).copied().unwrap_or(0) as u32;
+        let b2 = group.get(2).copied().unwrap_or(0) as u32;
+        let bits = (b0 << 16) | (b1 << 8) | b2;
+        for (index, shift) in [18, 12, 6, 0].iter().enumerate() {
+            if index <= group.len() {
+                text.push(ALPHABET[((bits >> shift) & 0x3f) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, base64, crc32, encode_png};
+
+    #[test]
+    fn checksums() {
+        assert_eq!(0xae42_6082, crc32(b"IEND"));
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+        assert_eq!(0x11e6_0398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9v", base64(b"foo"));
+        assert_eq!("Zm9vYmFy", base64(b"foobar"));
+    }
+
+    #[test]
+    fn png_structure() {
+        let rgba = [255, 0, 0, 255, 0, 255, 0, 128];
+        let png = encode_png(&rgba, 2, 1);
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &png[..8]);
+        assert_eq!(b"IHDR", &png[12..16]);
+        assert_eq!(&[0, 0, 0, 2, 0, 0, 0, 1, 8, 6], &png[16..26]);
+
+        // Signature, header, data (zlib header, one block, checksum) and end chunks:
+        let data_size = 2 + 5 + (1 + 8) + 4;
+        assert_eq!(8 + 25 + (12 + data_size) + 12, png.len());
+        assert_eq!(b"IEND", &png[png.len() - 8..png.len() - 4]);
+    }
+}
@@ -0,0 +1,149 @@
+//! Status line below the chart.
+
+use super::canvas::{HorizontalAnchor, VerticalAnchor};
+use super::{Canvas, ChartLayout};
+use crate::geometry::Point;
+use crate::style::{Color, Theme};
+
+/// Styling of the info bar.
+#[derive(Debug, Clone)]
+pub struct InfoBarOptions {
+    /// Fill color of the bar.
+    pub background: Color,
+
+    /// Color of the text.
+    pub text_color: Color,
+
+    /// Space between the left of the bar and the text.
+    pub padding: f64,
+}
+
+impl InfoBarOptions {
+    /// An info bar in the background and text colors of the theme.
+    pub fn from_theme(theme: &Theme) -> Self {
+        InfoBarOptions {
+            background: theme.background.clone(),
+            text_color: theme.text.clone(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for InfoBarOptions {
+    fn default() -> Self {
+        InfoBarOptions {
+            background: Color::white(),
+            text_color: Color::black(),
+            padding: 3.0,
+        }
+    }
+}
+
+/// Draw a line of text in the info bar space reserved at the bottom of the layout.
+///
+/// The bar spans from the left of the plot to the right of the canvas, and
+/// the text is left aligned. Text which is too wide is cut off with an ellipsis.
+pub fn draw_info_bar<C>(canvas: &mut C, layout: &ChartLayout, text: &str, options: &InfoBarOptions)
+where
+    C: Canvas,
+{
+    let top = layout.height - layout.info_bar_height;
+    let width = layout.width - layout.plot_left;
+    if layout.info_bar_height <= 0.0 || width <= 0.0 {
+        return;
+    }
+
+    canvas.save();
+    canvas.set_pen(options.background.clone(), 1.0);
+    canvas.fill_rect(layout.plot_left, top, width, layout.info_bar_height);
+
+    let room = width - 2.0 * options.padding;
+    let text = fit_text(canvas, text, room);
+    if !text.is_empty() {
+        canvas.set_pen(options.text_color.clone(), 1.0);
+        canvas.print_text(
+            &Point::new(
+                layout.plot_left + options.padding,
+                top + layout.info_bar_height / 2.0,
+            ),
+            HorizontalAnchor::Left,
+            VerticalAnchor::Middle,
+            &text,
+        );
+    }
+
+    canvas.restore();
+}
+
+/// Shorten the text until it fits the given width, marking the cut with an ellipsis.
+fn fit_text<C>(canvas: &C, text: &str, width: f64) -> String
+where
+    C: Canvas,
+{
+    if canvas.text_size(text).width <= width {
+        return text.to_owned();
+    }
+
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() {
+        chars.pop();
+        let candidate: String = chars.iter().chain(std::iter::once(&'…')).collect();
+        if canvas.text_size(&candidate).width <= width {
+            return candidate;
+        }
+    }
+
+    String::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_info_bar, InfoBarOptions};
+    use crate::geometry::Size;
+    use crate::render::test_util::{render_svg, sized_layout};
+    use crate::render::ChartLayout;
+
+    fn render(size: Size, text: &str) -> (ChartLayout, String) {
+        let layout = sized_layout(size);
+        let svg =
+            render_svg(|canvas| draw_info_bar(canvas, &layout, text, &InfoBarOptions::default()));
+        (layout, svg)
+    }
+
+    /// Get the position and content of the first text element.
+    fn first_text(svg: &str) -> (f64, f64, String) {
+        let line = svg.lines().find(|l| l.contains("<text")).unwrap();
+        let attribute = |name: &str| -> f64 {
+            let start = line.find(&format!("{}=\"", name)).unwrap() + name.len() + 2;
+            let end = start + line[start..].find('"').unwrap();
+            line[start..end].parse().unwrap()
+        };
+        let start = line.find('>').unwrap() + 1;
+        let end = line.find("</text>").unwrap();
+        (attribute("x"), attribute("y"), line[start..end].to_owned())
+    }
+
+    #[test]
+    fn text_in_info_bar() {
+        let (layout, svg) = render(Size::new(800.0, 600.0), "x = 1.5 y = 2.25");
+        let (x, y, text) = first_text(&svg);
+
+        assert_eq!("x = 1.5 y = 2.25", text);
+        assert!(layout.plot_left <= x && x < layout.width);
+        assert!(layout.height - layout.info_bar_height <= y && y <= layout.height);
+    }
+
+    #[test]
+    fn long_text_is_truncated() {
+        let long = "sample ".repeat(20);
+        let (layout, svg) = render(Size::new(300.0, 200.0), &long);
+        let (_, _, text) = first_text(&svg);
+
+        assert!(text.ends_with('…'));
+        assert!(text.len() < long.len());
+        assert!(long.starts_with(text.trim_end_matches('…')));
+
+        // The svg output assumes 8 pixels per byte:
+        assert!((text.len() * 8) as f64 <= layout.width - layout.plot_left);
+    }
+}
@@ -1,5 +1,8 @@
-use super::ChartOptions;
-use crate::geometry::Size;
+use super::{ChartOptions, LegendPosition};
+use crate::geometry::{Rect, Size};
+use cassowary::strength::{MEDIUM, REQUIRED, STRONG, WEAK};
+use cassowary::WeightedRelation::{EQ, GE, LE};
+use cassowary::{Constraint, Solver, Variable};
 
 /// Chart layout in pixels.
 ///
@@ -8,7 +11,13 @@ pub struct ChartLayout {
     pub width: f64,
     pub height: f64,
     pub y_axis_legend_width: f64,
+
+    /// Width of the legend for the right y axis, only used when enabled in the options.
+    pub y_axis_legend_width_right: f64,
     pub title_height: f64,
+
+    /// Height of the offset annotation above the y axis, if the axis has one.
+    pub y_axis_prefix_height: f64,
     pub x_axis_legend_height: f64,
     pub info_bar_height: f64,
     pub plot_top: f64,
@@ -22,11 +31,12 @@ pub struct ChartLayout {
 impl ChartLayout {
     pub fn new(size: Size) -> Self {
         ChartLayout {
-            // TODO: casowary?
             width: size.width,
             y_axis_legend_width: 140.0,
+            y_axis_legend_width_right: 140.0,
             x_axis_legend_height: 60.0,
             title_height: 0.0,
+            y_axis_prefix_height: 0.0,
             info_bar_height: 10.0,
             height: size.height,
             plot_top: 0.0,
@@ -43,13 +53,468 @@ impl ChartLayout {
         self.height = height;
     }
 
+    /// Determine the plot area, using a constraint solver.
+    ///
+    /// When there is enough room, the plot area is placed right next to the
+    /// legends, title and info bar. On small windows, the plot area keeps its
+    /// minimum size as long as possible, at the expense of the space for the
+    /// title and legends.
     pub fn layout(&mut self, options: &ChartOptions) {
-        self.plot_top = options.padding + self.title_height;
-        self.plot_left = self.y_axis_legend_width;
-        self.plot_bottom = self.height
-            - (self.x_axis_legend_height + options.padding * 2.0 + self.info_bar_height);
-        self.plot_right = self.width - options.padding;
-        self.plot_height = self.plot_bottom - self.plot_top;
-        self.plot_width = self.plot_right - self.plot_left;
+        let plot_top = Variable::new();
+        let plot_left = Variable::new();
+        let plot_bottom = Variable::new();
+        let plot_right = Variable::new();
+
+        let margins = &options.margins;
+        let top = margins.top + self.title_height + self.y_axis_prefix_height;
+        let left = margins.left + self.y_axis_legend_width;
+        let bottom =
+            self.height - (self.x_axis_legend_height + margins.bottom + self.info_bar_height);
+        let right = if options.right_y_axis {
+            self.width - margins.right - self.y_axis_legend_width_right
+        } else {
+            self.width - margins.right
+        };
+
+        let mut constraints: Vec<Constraint> = vec![
+            // The plot must fit in the window:
+            plot_left | GE(REQUIRED) | 0.0,
+            plot_top | GE(REQUIRED) | 0.0,
+            plot_right | LE(REQUIRED) | self.width,
+            plot_bottom | LE(REQUIRED) | self.height,
+            (plot_right - plot_left) | GE(REQUIRED) | 0.0,
+            (plot_bottom - plot_top) | GE(REQUIRED) | 0.0,
+            // Keep the plot area usable:
+            (plot_right - plot_left) | GE(STRONG) | options.min_plot_width,
+            (plot_bottom - plot_top) | GE(STRONG) | options.min_plot_height,
+            // Leave room for the axis legends, and then for title and padding:
+            plot_left | GE(MEDIUM * 2.0) | left,
+            plot_bottom | LE(MEDIUM * 2.0) | bottom,
+            plot_top | GE(MEDIUM) | top,
+            // Without pressure, do not waste any space:
+            plot_left | EQ(WEAK) | left,
+            plot_top | EQ(WEAK) | top,
+            plot_bottom | EQ(WEAK) | bottom,
+            plot_right | EQ(WEAK) | right,
+        ];
+
+        if options.right_y_axis {
+            // The right legend gives in before the left one:
+            constraints.push(plot_right | LE(MEDIUM * 1.5) | right);
+        }
+
+        let mut solver = Solver::new();
+        if solver.add_constraints(&constraints).is_ok() {
+            self.plot_top = solver.get_value(plot_top);
+            self.plot_left = solver.get_value(plot_left);
+            self.plot_bottom = solver.get_value(plot_bottom);
+            self.plot_right = solver.get_value(plot_right);
+        } else {
+            // This happens when the window has a negative size.
+            warn!("Unable to solve chart layout constraints");
+            self.plot_top = top;
+            self.plot_left = left;
+            self.plot_bottom = bottom.max(top);
+            self.plot_right = right.max(left);
+        }
+
+        if options.snap_to_pixels {
+            self.snap_plot_rect();
+        }
+
+        // Never hand out negative sizes, not even due to rounding:
+        self.plot_height = (self.plot_bottom - self.plot_top).max(0.0);
+        self.plot_width = (self.plot_right - self.plot_left).max(0.0);
+    }
+
+    /// Move the edges of the plot area onto whole pixels.
+    ///
+    /// The frame around the plot is two pixels wide, so a frame centered
+    /// on an integer coordinate covers whole pixels.
+    pub fn snap_plot_rect(&mut self) {
+        self.plot_left = snap_to_pixel(self.plot_left, FRAME_LINE_WIDTH);
+        self.plot_top = snap_to_pixel(self.plot_top, FRAME_LINE_WIDTH);
+        self.plot_right = snap_to_pixel(self.plot_right, FRAME_LINE_WIDTH);
+        self.plot_bottom = snap_to_pixel(self.plot_bottom, FRAME_LINE_WIDTH);
+        self.plot_height = (self.plot_bottom - self.plot_top).max(0.0);
+        self.plot_width = (self.plot_right - self.plot_left).max(0.0);
+    }
+
+    /// Test if there is room to draw a plot at all.
+    ///
+    /// The plot width and height are never negative, but can be zero,
+    /// or too small to draw anything sensible into.
+    pub fn is_plot_visible(&self) -> bool {
+        self.plot_width >= 1.0 && self.plot_height >= 1.0
+    }
+
+    /// The rectangle in which the data is plotted.
+    pub fn plot_rect(&self) -> Rect {
+        Rect::new(
+            self.plot_left,
+            self.plot_top,
+            self.plot_width,
+            self.plot_height,
+        )
+    }
+
+    /// Layout of a small plot in a corner of this plot, such as an overview.
+    ///
+    /// The inset takes the given fraction of the width and height of the
+    /// plot area, and is placed the given margin away from the edges, as far
+    /// as the plot is large enough. The inset has no room for legends, and
+    /// always lies within the plot area.
+    pub fn inset(&self, corner: LegendPosition, fraction: f64, margin: f64) -> ChartLayout {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let width = self.plot_width * fraction;
+        let height = self.plot_height * fraction;
+        let margin_x = margin.min(self.plot_width - width).max(0.0);
+        let margin_y = margin.min(self.plot_height - height).max(0.0);
+        let left = match corner {
+            LegendPosition::TopLeft | LegendPosition::BottomLeft => self.plot_left + margin_x,
+            LegendPosition::TopRight | LegendPosition::BottomRight => {
+                self.plot_right - margin_x - width
+            }
+        };
+        let top = match corner {
+            LegendPosition::TopLeft | LegendPosition::TopRight => self.plot_top + margin_y,
+            LegendPosition::BottomLeft | LegendPosition::BottomRight => {
+                self.plot_bottom - margin_y - height
+            }
+        };
+
+        let mut inset = ChartLayout::new(Size::new(self.width, self.height));
+        inset.y_axis_legend_width = 0.0;
+        inset.y_axis_legend_width_right = 0.0;
+        inset.x_axis_legend_height = 0.0;
+        inset.info_bar_height = 0.0;
+        inset.set_plot_rect(&Rect::new(left, top, width, height));
+        inset
+    }
+
+    /// Place the plot at the given rectangle.
+    pub fn set_plot_rect(&mut self, rect: &Rect) {
+        self.plot_left = rect.left();
+        self.plot_top = rect.top();
+        self.plot_right = rect.right();
+        self.plot_bottom = rect.bottom();
+        self.plot_width = rect.width();
+        self.plot_height = rect.height();
+    }
+}
+
+/// Width of the frame drawn around the plot area.
+const FRAME_LINE_WIDTH: f64 = 2.0;
+
+/// Snap a coordinate such that a straight line of the given width covers whole pixels.
+///
+/// A line of odd width is centered halfway a pixel, and a line of even width
+/// on the boundary between two pixels. The width is rounded to whole pixels
+/// first, and lines thinner than a pixel are treated as one pixel wide.
+pub fn snap_to_pixel(position: f64, line_width: f64) -> f64 {
+    let line_width = line_width.round().max(1.0) as i64;
+    if line_width % 2 == 1 {
+        position.floor() + 0.5
+    } else {
+        position.round()
+    }
+}
+
+/// Layout of plots stacked in rows, sharing the x axis.
+///
+/// Each row gets an equal part of the height. All rows have the same y axis
+/// legend width, so their plots line up, and only the bottom row leaves
+/// room for the x axis legend.
+pub struct GridLayout {
+    pub width: f64,
+    pub height: f64,
+    pub rows: usize,
+    pub y_axis_legend_width: f64,
+    pub x_axis_legend_height: f64,
+
+    /// Room for a title above the top row.
+    pub title_height: f64,
+}
+
+impl GridLayout {
+    pub fn new(size: Size, rows: usize) -> Self {
+        GridLayout {
+            width: size.width,
+            height: size.height,
+            rows,
+            y_axis_legend_width: 140.0,
+            x_axis_legend_height: 60.0,
+            title_height: 0.0,
+        }
+    }
+
+    /// Determine the layout of each row, from top to bottom.
+    ///
+    /// The plot area of each layout lies within its own row, but the width
+    /// and height of the layouts are those of the whole canvas.
+    pub fn layouts(&self, options: &ChartOptions) -> Vec<ChartLayout> {
+        let row_height = self.height / self.rows.max(1) as f64;
+        (0..self.rows)
+            .map(|row| {
+                let mut layout = ChartLayout::new(Size::new(self.width, row_height));
+                layout.y_axis_legend_width = self.y_axis_legend_width;
+                layout.info_bar_height = 0.0;
+                if row == 0 {
+                    layout.title_height = self.title_height;
+                }
+                layout.x_axis_legend_height = if row + 1 == self.rows {
+                    self.x_axis_legend_height
+                } else {
+                    0.0
+                };
+                layout.layout(options);
+
+                let plot_rect = layout.plot_rect();
+                let top = row as f64 * row_height;
+                layout.resize(self.width, self.height);
+                layout.set_plot_rect(&Rect::new(
+                    plot_rect.left(),
+                    plot_rect.top() + top,
+                    plot_rect.width(),
+                    plot_rect.height(),
+                ));
+                if options.snap_to_pixels {
+                    layout.snap_plot_rect();
+                }
+                layout
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{snap_to_pixel, ChartLayout, GridLayout};
+    use crate::geometry::{Rect, Size};
+    use crate::render::{ChartOptions, LegendPosition, Margins};
+
+    #[test]
+    fn large_window() {
+        let options = ChartOptions::default();
+        let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+        layout.layout(&options);
+
+        assert_almost_eq(140.0, layout.plot_left);
+        assert_almost_eq(790.0, layout.plot_right);
+        assert_almost_eq(10.0, layout.plot_top);
+        assert_almost_eq(510.0, layout.plot_bottom);
+        assert_almost_eq(650.0, layout.plot_width);
+        assert_almost_eq(500.0, layout.plot_height);
+    }
+
+    #[test]
+    fn plot_rect_roundtrip() {
+        let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+        layout.layout(&ChartOptions::default());
+        assert_eq!(Rect::new(140.0, 10.0, 650.0, 500.0), layout.plot_rect());
+
+        layout.set_plot_rect(&Rect::new(20.0, 30.0, 100.0, 50.0));
+        assert_almost_eq(120.0, layout.plot_right);
+        assert_almost_eq(80.0, layout.plot_bottom);
+        assert_eq!(Rect::new(20.0, 30.0, 100.0, 50.0), layout.plot_rect());
+    }
+
+    #[test]
+    fn inset() {
+        let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+        layout.layout(&ChartOptions::default());
+        let main = layout.plot_rect();
+
+        let inset = layout.inset(LegendPosition::TopRight, 0.25, 10.0);
+        assert_eq!(Rect::new(617.5, 20.0, 162.5, 125.0), inset.plot_rect());
+        assert!(main.contains_rect(&inset.plot_rect()));
+
+        let inset = layout.inset(LegendPosition::BottomLeft, 0.5, 10.0);
+        assert_almost_eq(150.0, inset.plot_left);
+        assert_almost_eq(500.0, inset.plot_bottom);
+
+        // Too large margins and fractions are limited to the plot area:
+        for corner in &[LegendPosition::TopLeft, LegendPosition::BottomRight] {
+            let inset = layout.inset(*corner, 0.9, 1000.0);
+            assert!(main.contains_rect(&inset.plot_rect()));
+            let inset = layout.inset(*corner, 3.0, 5.0);
+            assert_eq!(main, inset.plot_rect());
+        }
+    }
+
+    #[test]
+    fn grid_rows() {
+        let options = ChartOptions::default();
+        let layouts = GridLayout::new(Size::new(800.0, 600.0), 2).layouts(&options);
+        assert_eq!(2, layouts.len());
+
+        let (top, bottom) = (&layouts[0], &layouts[1]);
+        assert_almost_eq(10.0, top.plot_top);
+        assert_almost_eq(280.0, top.plot_bottom);
+        assert_almost_eq(310.0, bottom.plot_top);
+        assert_almost_eq(520.0, bottom.plot_bottom);
+        for layout in &layouts {
+            assert_almost_eq(140.0, layout.plot_left);
+            assert_almost_eq(790.0, layout.plot_right);
+            assert_almost_eq(600.0, layout.height);
+        }
+
+        assert!(GridLayout::new(Size::new(800.0, 600.0), 0)
+            .layouts(&options)
+            .is_empty());
+    }
+
+    #[test]
+    fn snapping() {
+        let mut options = ChartOptions::default();
+        let mut layout = ChartLayout::new(Size::new(800.6, 600.3));
+        layout.layout(&options);
+        assert_almost_eq(790.6, layout.plot_right);
+        assert_almost_eq(510.3, layout.plot_bottom);
+
+        options.snap_to_pixels = true;
+        layout.layout(&options);
+        assert_almost_eq(140.0, layout.plot_left);
+        assert_almost_eq(10.0, layout.plot_top);
+        assert_almost_eq(791.0, layout.plot_right);
+        assert_almost_eq(510.0, layout.plot_bottom);
+        assert_almost_eq(651.0, layout.plot_width);
+        assert_almost_eq(500.0, layout.plot_height);
+
+        // Rows of a grid are snapped after they are moved into place:
+        let layouts = GridLayout::new(Size::new(800.0, 601.0), 2).layouts(&options);
+        assert_almost_eq(311.0, layouts[1].plot_top);
+
+        assert_almost_eq(12.5, snap_to_pixel(12.7, 1.0));
+        assert_almost_eq(12.5, snap_to_pixel(12.2, 3.0));
+        assert_almost_eq(13.0, snap_to_pixel(12.7, 2.0));
+        assert_almost_eq(-3.5, snap_to_pixel(-3.2, 0.5));
+    }
+
+    #[test]
+    fn title_shifts_plot_down() {
+        let options = ChartOptions::default();
+        let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+        layout.layout(&options);
+        let plot_top = layout.plot_top;
+        let plot_bottom = layout.plot_bottom;
+
+        layout.title_height = 26.0;
+        layout.layout(&options);
+
+        assert_almost_eq(plot_top + 26.0, layout.plot_top);
+        assert_almost_eq(plot_bottom, layout.plot_bottom);
+    }
+
+    #[test]
+    fn asymmetric_margins() {
+        let options = ChartOptions {
+            margins: Margins::new(5.0, 20.0, 0.0, 30.0),
+            ..ChartOptions::default()
+        };
+        let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+        layout.layout(&options);
+
+        assert_almost_eq(170.0, layout.plot_left);
+        assert_almost_eq(780.0, layout.plot_right);
+        assert_almost_eq(5.0, layout.plot_top);
+        assert_almost_eq(530.0, layout.plot_bottom);
+    }
+
+    #[test]
+    fn huge_margins_are_clamped() {
+        let options = ChartOptions {
+            margins: Margins::uniform(1000.0),
+            min_plot_width: 0.0,
+            min_plot_height: 0.0,
+            ..ChartOptions::default()
+        };
+        let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+        layout.layout(&options);
+
+        assert!(layout.plot_width >= 0.0);
+        assert!(layout.plot_height >= 0.0);
+        assert!(layout.plot_left >= 0.0 && layout.plot_right <= 800.0);
+        assert!(layout.plot_top >= 0.0 && layout.plot_bottom <= 600.0);
+    }
+
+    #[test]
+    fn right_y_axis() {
+        let mut options = ChartOptions {
+            right_y_axis: true,
+            ..ChartOptions::default()
+        };
+        let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+        layout.y_axis_legend_width_right = 60.0;
+        layout.layout(&options);
+
+        assert_almost_eq(140.0, layout.plot_left);
+        assert_almost_eq(730.0, layout.plot_right);
+        assert_almost_eq(590.0, layout.plot_width);
+
+        options.right_y_axis = false;
+        layout.layout(&options);
+        assert_almost_eq(790.0, layout.plot_right);
+    }
+
+    #[test]
+    fn small_window() {
+        let options = ChartOptions::default();
+        let mut layout = ChartLayout::new(Size::new(200.0, 100.0));
+        layout.layout(&options);
+
+        // The legends give in, to keep the minimum plot size:
+        assert_almost_eq(100.0, layout.plot_left);
+        assert_almost_eq(200.0, layout.plot_right);
+        assert_almost_eq(0.0, layout.plot_top);
+        assert_almost_eq(50.0, layout.plot_bottom);
+        assert_almost_eq(options.min_plot_width, layout.plot_width);
+        assert_almost_eq(options.min_plot_height, layout.plot_height);
+    }
+
+    #[test]
+    fn tiny_window() {
+        let options = ChartOptions::default();
+        let mut layout = ChartLayout::new(Size::new(50.0, 20.0));
+        layout.layout(&options);
+
+        // The plot takes up the whole window:
+        assert_almost_eq(0.0, layout.plot_left);
+        assert_almost_eq(50.0, layout.plot_right);
+        assert_almost_eq(0.0, layout.plot_top);
+        assert_almost_eq(20.0, layout.plot_bottom);
+    }
+
+    #[test]
+    fn ten_by_ten_window() {
+        let options = ChartOptions::default();
+        let mut layout = ChartLayout::new(Size::new(10.0, 10.0));
+        layout.layout(&options);
+
+        assert!(layout.plot_width >= 0.0);
+        assert!(layout.plot_height >= 0.0);
+        assert!(layout.plot_right <= 10.0);
+        assert!(layout.plot_bottom <= 10.0);
+        assert!(layout.is_plot_visible());
+    }
+
+    #[test]
+    fn negative_window() {
+        let options = ChartOptions::default();
+        let mut layout = ChartLayout::new(Size::new(-10.0, -10.0));
+        layout.layout(&options);
+
+        assert_almost_eq(0.0, layout.plot_width);
+        assert_almost_eq(0.0, layout.plot_height);
+        assert!(!layout.is_plot_visible());
+    }
+
+    fn assert_almost_eq(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1.0e-6,
+            "{} != {}",
+            expected,
+            actual
+        );
     }
 }
@@ -0,0 +1,152 @@
+//! Legend box listing the traces in a plot.
+
+use super::canvas::{HorizontalAnchor, VerticalAnchor};
+use super::{Canvas, ChartLayout};
+use crate::geometry::Point;
+use crate::style::{Color, Theme};
+
+/// Corner of the plot area where the legend, or an inset plot, is placed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LegendPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Space between the legend box and the plot edges, and around the entries.
+const PADDING: f64 = 5.0;
+
+/// Length of the line drawn in front of each label.
+const SWATCH_WIDTH: f64 = 20.0;
+
+/// Draw a framed box with a colored line and a label for each entry.
+///
+/// The box is sized to the widest label. When there are more entries than
+/// fit in the plot area, the remaining entries are left out. The box and the
+/// labels take their colors from the theme.
+pub fn draw_legend<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    entries: &[(String, Color)],
+    position: LegendPosition,
+    theme: &Theme,
+) where
+    C: Canvas,
+{
+    if entries.is_empty() {
+        return;
+    }
+
+    let plot_rect = layout.plot_rect();
+    let label_width = entries
+        .iter()
+        .map(|(name, _)| canvas.text_size(name).width)
+        .fold(0.0, f64::max);
+    let row_height = entries
+        .iter()
+        .map(|(name, _)| canvas.text_size(name).height)
+        .fold(0.0, f64::max)
+        + PADDING;
+
+    let room = plot_rect.height() - 2.0 * PADDING - PADDING;
+    let rows = ((room / row_height).floor().max(0.0) as usize).min(entries.len());
+    if rows == 0 {
+        return;
+    }
+
+    let box_width = SWATCH_WIDTH + label_width + 3.0 * PADDING;
+    let box_height = rows as f64 * row_height + PADDING;
+    let box_left = match position {
+        LegendPosition::TopLeft | LegendPosition::BottomLeft => plot_rect.left() + PADDING,
+        LegendPosition::TopRight | LegendPosition::BottomRight => {
+            plot_rect.right() - PADDING - box_width
+        }
+    };
+    let box_top = match position {
+        LegendPosition::TopLeft | LegendPosition::TopRight => plot_rect.top() + PADDING,
+        LegendPosition::BottomLeft | LegendPosition::BottomRight => {
+            plot_rect.bottom() - PADDING - box_height
+        }
+    };
+
+    canvas.save();
+    canvas.set_clip_rect(plot_rect);
+
+    canvas.set_pen(theme.plot_background.clone(), 0.8);
+    canvas.fill_rect(box_left, box_top, box_width, box_height);
+    canvas.set_pen(theme.axis.clone(), 1.0);
+    canvas.set_line_width(1.0);
+    canvas.draw_rect(box_left, box_top, box_width, box_height);
+
+    for (index, (name, color)) in entries.iter().take(rows).enumerate() {
+        let row_top = box_top + PADDING + index as f64 * row_height;
+        let row_middle = row_top + (row_height - PADDING) / 2.0;
+
+        canvas.set_pen(color.clone(), 1.0);
+        canvas.set_line_width(2.0);
+        canvas.draw_line(&[
+            Point::new(box_left + PADDING, row_middle),
+            Point::new(box_left + PADDING + SWATCH_WIDTH, row_middle),
+        ]);
+
+        canvas.set_pen(theme.text.clone(), 1.0);
+        canvas.print_text(
+            &Point::new(box_left + 2.0 * PADDING + SWATCH_WIDTH, row_middle),
+            HorizontalAnchor::Left,
+            VerticalAnchor::Middle,
+            name,
+        );
+    }
+
+    canvas.restore();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_legend, LegendPosition};
+    use crate::geometry::Size;
+    use crate::render::test_util::{render_svg, sized_layout};
+    use crate::style::{Color, Theme};
+
+    fn render(size: Size, entries: &[(String, Color)]) -> String {
+        let layout = sized_layout(size);
+        let theme = Theme::default();
+        render_svg(|canvas| draw_legend(canvas, &layout, entries, LegendPosition::TopRight, &theme))
+    }
+
+    #[test]
+    fn three_entries() {
+        let entries = vec![
+            ("voltage".to_owned(), Color::red()),
+            ("current".to_owned(), Color::green()),
+            ("temperature".to_owned(), Color::new(0, 0, 255)),
+        ];
+        let svg = render(Size::new(800.0, 600.0), &entries);
+
+        for color in &["rgb(255,0,0)", "rgb(0,255,0)", "rgb(0,0,255)"] {
+            assert!(
+                svg.lines()
+                    .any(|l| l.contains("<path d=\"M ") && l.contains(&format!("stroke:{}", color))),
+                "{}",
+                svg
+            );
+        }
+        assert!(svg.contains(">voltage</text>"));
+        assert!(svg.contains(">current</text>"));
+        assert!(svg.contains(">temperature</text>"));
+    }
+
+    #[test]
+    fn too_many_entries() {
+        let entries: Vec<(String, Color)> = (0..100)
+            .map(|i| (format!("signal{}", i), Color::red()))
+            .collect();
+        let svg = render(Size::new(800.0, 600.0), &entries);
+
+        let labels = svg.matches("</text>").count();
+        assert!(0 < labels && labels < 100);
+        assert!(svg.contains(">signal0</text>"));
+        assert!(!svg.contains(">signal99</text>"));
+    }
+}
@@ -0,0 +1,168 @@
+//! Scatter plots, with a marker at each data point.
+
+use super::{Canvas, ChartLayout, Series, Transform};
+use crate::geometry::Point;
+
+/// Shape of a marker.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkerShape {
+    Circle,
+    Square,
+    Cross,
+    Plus,
+}
+
+/// Shape and size of the markers drawn by `draw_markers`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkerStyle {
+    pub shape: MarkerShape,
+
+    /// Width and height of a marker, in pixels.
+    pub size: f64,
+
+    /// Skip markers which would be drawn mostly on top of the previous marker.
+    pub decimate: bool,
+}
+
+impl MarkerStyle {
+    /// Markers of the given shape and size, without decimation.
+    pub fn new(shape: MarkerShape, size: f64) -> Self {
+        MarkerStyle {
+            shape,
+            size,
+            decimate: false,
+        }
+    }
+}
+
+/// Draw a marker at each point of a series, in the color of the series.
+///
+/// The markers of a dimmed series are gray, like its line.
+///
+/// Points outside of the plot area, and points with non-finite values, are
+/// skipped. With decimation, a marker closer than half its size to the last
+/// drawn marker is left out. Nothing is drawn for an invisible series.
+pub fn draw_markers<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    series: &Series,
+    marker: &MarkerStyle,
+) where
+    C: Canvas,
+{
+    if !series.visible {
+        return;
+    }
+
+    let plot_rect = layout.plot_rect();
+    canvas.save();
+    canvas.set_clip_rect(plot_rect.clone());
+    canvas.set_pen(series.draw_color(), series.stroke.opacity);
+    canvas.set_line_width(series.stroke.width);
+
+    let half = marker.size / 2.0;
+    let mut last: Option<Point> = None;
+    for &(x, y) in &series.points {
+        let p = transform.apply_point(Point::new(x, y));
+        if !p.x().is_finite() || !p.y().is_finite() || !plot_rect.contains(&p) {
+            continue;
+        }
+
+        if marker.decimate {
+            if let Some(last) = &last {
+                if p.distance_to(last) < half {
+                    continue;
+                }
+            }
+            last = Some(p);
+        }
+
+        match marker.shape {
+            MarkerShape::Circle => canvas.fill_circle(&p, half),
+            MarkerShape::Square => {
+                canvas.fill_rect(p.x() - half, p.y() - half, marker.size, marker.size)
+            }
+            MarkerShape::Cross => {
+                canvas.draw_line(&[
+                    Point::new(p.x() - half, p.y() - half),
+                    Point::new(p.x() + half, p.y() + half),
+                ]);
+                canvas.draw_line(&[
+                    Point::new(p.x() - half, p.y() + half),
+                    Point::new(p.x() + half, p.y() - half),
+                ]);
+            }
+            MarkerShape::Plus => {
+                canvas.draw_line(&[
+                    Point::new(p.x() - half, p.y()),
+                    Point::new(p.x() + half, p.y()),
+                ]);
+                canvas.draw_line(&[
+                    Point::new(p.x(), p.y() - half),
+                    Point::new(p.x(), p.y() + half),
+                ]);
+            }
+        }
+    }
+
+    canvas.restore();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_markers, MarkerShape, MarkerStyle};
+    use crate::render::test_util::{layout_and_transform, render_svg};
+    use crate::render::Series;
+    use crate::style::Color;
+
+    fn render(series: &Series, marker: &MarkerStyle) -> String {
+        let (layout, transform) = layout_and_transform((0.0, 10.0), (0.0, 1.0));
+        render_svg(|canvas| draw_markers(canvas, &layout, &transform, series, marker))
+    }
+
+    #[test]
+    fn marker_per_point() {
+        let series = Series::new(
+            "samples",
+            vec![
+                (1.0, 0.5),
+                (2.0, 0.7),
+                (3.0, f64::NAN),
+                (4.0, 0.2),
+                (20.0, 0.5),
+            ],
+            Color::red(),
+        );
+
+        // The NaN and the point outside of the plot are skipped:
+        let svg = render(&series, &MarkerStyle::new(MarkerShape::Circle, 6.0));
+        assert_eq!(3, svg.matches("<circle").count(), "{}", svg);
+        assert!(svg.contains(r#"r="3""#));
+
+        let svg = render(&series, &MarkerStyle::new(MarkerShape::Square, 6.0));
+        assert_eq!(3, svg.matches("<polygon").count(), "{}", svg);
+    }
+
+    #[test]
+    fn dimmed_markers() {
+        let mut series = Series::new("samples", vec![(1.0, 0.5)], Color::red());
+        series.dimmed = true;
+        let svg = render(&series, &MarkerStyle::new(MarkerShape::Circle, 6.0));
+        assert!(svg.contains("rgb(127,127,127)"), "{}", svg);
+        assert!(!svg.contains("rgb(255,0,0)"));
+    }
+
+    #[test]
+    fn decimate_overlapping() {
+        // 1000 points within 65 pixels:
+        let points = (0..1000).map(|i| (5.0 + i as f64 * 1.0e-3, 0.5)).collect();
+        let series = Series::new("dense", points, Color::red());
+        let mut marker = MarkerStyle::new(MarkerShape::Circle, 6.0);
+        assert_eq!(1000, render(&series, &marker).matches("<circle").count());
+
+        marker.decimate = true;
+        let count = render(&series, &marker).matches("<circle").count();
+        assert!(count > 20 && count < 24, "{}", count);
+    }
+}
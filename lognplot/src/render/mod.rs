@@ -1,24 +1,78 @@
 //! Canvas package for drawing stuff on canvas
 //! This means that we can be artists now!
 
+mod annotation;
+mod autoscale;
+mod axis;
 mod canvas;
 mod chart;
+mod cursor;
+mod downsample;
+mod error_bars;
+mod grid;
+mod heatmap;
+mod image;
+mod info_bar;
 mod layout;
+mod legend;
+mod markers;
+mod navigation;
+mod navigator;
 mod options;
+mod plot;
+mod raster_canvas;
+mod recording_canvas;
+mod ring_series;
+mod series;
+mod softgl;
+mod stacked_area;
 mod svg_output;
+#[cfg(test)]
+mod test_util;
+mod ticks;
 mod transform;
+mod vertices;
 
 #[cfg(feature = "cairo")]
 mod cairo_canvas;
 
 // re-exports
 
-pub use canvas::Canvas;
+pub use annotation::{draw_marker_line, draw_x_selection, Axis};
+pub use autoscale::{
+    autoscale, autoscale_series, autoscale_series_with_padding, autoscale_with_padding, AxisRanges,
+};
+pub use axis::{draw_minor_ticks, draw_x_axis, draw_y_axis, AxisOptions, TickDirection, TickStyle};
+pub use canvas::{Canvas, HorizontalAnchor, TextOrientation, VerticalAnchor};
 pub use chart::draw_chart;
-pub use layout::ChartLayout;
-pub use options::ChartOptions;
-pub use svg_output::SvgOutput;
-pub use transform::{x_pixel_to_domain, x_pixels_to_domain, y_pixel_to_domain};
+pub use cursor::{draw_cursor, CursorOptions};
+pub use downsample::{downsample, downsample_mean, DownsampleMode, MinMaxBin};
+pub use error_bars::{draw_error_band, draw_error_bars, LengthMismatch};
+pub use grid::{draw_grid, draw_minor_grid, GridOptions};
+pub use heatmap::draw_heatmap;
+pub use info_bar::{draw_info_bar, InfoBarOptions};
+pub use layout::{snap_to_pixel, ChartLayout, GridLayout};
+pub use legend::{draw_legend, LegendPosition};
+pub use markers::{draw_markers, MarkerShape, MarkerStyle};
+pub use navigation::{pan, zoom, zoom_about, zoom_x, zoom_y, AxisLock};
+pub use navigator::{draw_navigator, navigator_window};
+pub use options::{ChartOptions, ChartOptionsBuilder, ChartOptionsError, Margins};
+pub use plot::{draw_subplots, render_chart_to_svg, SubplotSpec};
+pub use raster_canvas::RasterCanvas;
+pub use recording_canvas::{DrawOp, RecordingCanvas};
+pub use ring_series::RingSeries;
+pub use series::{
+    draw_series, draw_series_incremental, nearest_point, nearest_point_sorted, AxisSide,
+    InterpolationMode, Series,
+};
+pub use stacked_area::draw_stacked_area;
+pub use svg_output::{SvgOutput, Units};
+pub use ticks::{
+    format_tick, log_ticks_with_minor, minor_ticks, nice_range, ticks, ticks_with_minor,
+    TickFormat, Ticks,
+};
+pub use transform::{x_pixel_to_domain, x_pixels_to_domain, y_pixel_to_domain, Transform};
+pub use vertices::{series_to_vertices, Topology, VertexBuffer, PRIMITIVE_RESTART};
 
 #[cfg(feature = "cairo")]
 pub use cairo_canvas::CairoCanvas;
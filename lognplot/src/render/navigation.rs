@@ -0,0 +1,275 @@
+//! Range calculations for interactive panning and zooming.
+
+use super::ChartLayout;
+use crate::geometry::{Point, Range};
+
+/// Axis which is kept as is by `pan` and `zoom`, for example while a modifier key is held.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AxisLock {
+    /// Both axes move.
+    #[default]
+    None,
+
+    /// Keep the x range, only the y axis moves.
+    LockX,
+
+    /// Keep the y range, only the x axis moves.
+    LockY,
+}
+
+impl AxisLock {
+    fn x_locked(self) -> bool {
+        self == AxisLock::LockX
+    }
+
+    fn y_locked(self) -> bool {
+        self == AxisLock::LockY
+    }
+}
+
+/// Shift the visible ranges to follow the mouse, when dragged by some pixels.
+///
+/// The data under the mouse moves along with it: dragging to the right
+/// shows lower x values, and since screen y grows downward, dragging
+/// downward shows higher y values. When the plot has no size, or the axis
+/// is locked, a range is returned unchanged.
+pub fn pan(
+    range_x: Range<f64>,
+    range_y: Range<f64>,
+    layout: &ChartLayout,
+    dx_px: f64,
+    dy_px: f64,
+    lock: AxisLock,
+) -> (Range<f64>, Range<f64>) {
+    let range_x = if layout.plot_width > 0.0 && !lock.x_locked() {
+        shift(&range_x, -dx_px / layout.plot_width)
+    } else {
+        range_x
+    };
+    let range_y = if layout.plot_height > 0.0 && !lock.y_locked() {
+        shift(&range_y, dy_px / layout.plot_height)
+    } else {
+        range_y
+    };
+    (range_x, range_y)
+}
+
+/// Smallest range length when zooming in, relative to the values in the range.
+const MIN_RELATIVE_LENGTH: f64 = 1.0e-12;
+
+/// Smallest range length when zooming in around zero.
+const MIN_ABSOLUTE_LENGTH: f64 = 1.0e-18;
+
+/// Scale a range by the given factor, keeping the value at a pixel in place.
+///
+/// The range is spread out over the pixels in `extent`, which may be
+/// reversed, as is the case for y axes. A factor below 1 zooms in, above 1
+/// zooms out. Zooming in stops at a minimum length, to stay clear of
+/// floating point noise.
+pub fn zoom_about(range: Range<f64>, extent: Range<f64>, pixel: f64, factor: f64) -> Range<f64> {
+    let pixels = extent.end() - extent.begin();
+    let length = range.end() - range.begin();
+    if pixels == 0.0 || !factor.is_finite() || factor <= 0.0 {
+        return range;
+    }
+
+    let fraction = (pixel - extent.begin()) / pixels;
+    let anchor = range.begin() + fraction * length;
+    let min_length = (anchor.abs() * MIN_RELATIVE_LENGTH).max(MIN_ABSOLUTE_LENGTH);
+    let new_length = if (length * factor).abs() < min_length {
+        min_length.min(length.abs()).copysign(length)
+    } else {
+        length * factor
+    };
+
+    let begin = anchor - fraction * new_length;
+    Range::new(begin, begin + new_length)
+}
+
+/// Zoom the x range around a pixel column, see `zoom_about`.
+pub fn zoom_x(range: Range<f64>, layout: &ChartLayout, pixel: f64, factor: f64) -> Range<f64> {
+    let extent = Range::new(layout.plot_left, layout.plot_right);
+    zoom_about(range, extent, pixel, factor)
+}
+
+/// Zoom the y range around a pixel row, see `zoom_about`.
+pub fn zoom_y(range: Range<f64>, layout: &ChartLayout, pixel: f64, factor: f64) -> Range<f64> {
+    let extent = Range::new(layout.plot_bottom, layout.plot_top);
+    zoom_about(range, extent, pixel, factor)
+}
+
+/// Zoom both ranges around a pixel, except for a locked axis.
+///
+/// See `zoom_x` and `zoom_y` for zooming a single axis.
+pub fn zoom(
+    range_x: Range<f64>,
+    range_y: Range<f64>,
+    layout: &ChartLayout,
+    pixel: Point,
+    factor: f64,
+    lock: AxisLock,
+) -> (Range<f64>, Range<f64>) {
+    let range_x = if lock.x_locked() {
+        range_x
+    } else {
+        zoom_x(range_x, layout, pixel.x(), factor)
+    };
+    let range_y = if lock.y_locked() {
+        range_y
+    } else {
+        zoom_y(range_y, layout, pixel.y(), factor)
+    };
+    (range_x, range_y)
+}
+
+/// Move a range by a fraction of its length.
+fn shift(range: &Range<f64>, fraction: f64) -> Range<f64> {
+    let step = (range.end() - range.begin()) * fraction;
+    Range::new(range.begin() + step, range.end() + step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pan, zoom, zoom_about, zoom_x, zoom_y, AxisLock};
+    use crate::geometry::{Point, Range, Size};
+    use crate::render::test_util::{layout, sized_layout};
+
+    #[test]
+    fn drag_half_plot() {
+        let layout = layout();
+        let (x, y) = pan(
+            Range::new(0.0, 10.0),
+            Range::new(-1.0, 1.0),
+            &layout,
+            layout.plot_width / 2.0,
+            0.0,
+            AxisLock::None,
+        );
+        assert_eq!((-5.0, 5.0), (x.begin(), x.end()));
+        assert_eq!((-1.0, 1.0), (y.begin(), y.end()));
+
+        // Dragging down shows higher values:
+        let (x, y) = pan(
+            Range::new(0.0, 10.0),
+            Range::new(-1.0, 1.0),
+            &layout,
+            0.0,
+            layout.plot_height / 4.0,
+            AxisLock::None,
+        );
+        assert_eq!((0.0, 10.0), (x.begin(), x.end()));
+        assert_eq!((-0.5, 1.5), (y.begin(), y.end()));
+    }
+
+    /// The value at a pixel, when the range is spread over the extent.
+    fn value_at(range: &Range<f64>, extent: &Range<f64>, pixel: f64) -> f64 {
+        let fraction = (pixel - extent.begin()) / (extent.end() - extent.begin());
+        range.begin() + fraction * (range.end() - range.begin())
+    }
+
+    #[test]
+    fn zoom_keeps_value_under_cursor() {
+        let extent = Range::new(100.0, 500.0);
+        let range = Range::new(-3.0, 17.0);
+        for &pixel in &[100.0, 180.0, 333.0, 500.0] {
+            for &factor in &[0.5, 0.9, 2.0] {
+                let zoomed = zoom_about(range.clone(), extent.clone(), pixel, factor);
+                let before = value_at(&range, &extent, pixel);
+                let after = value_at(&zoomed, &extent, pixel);
+                assert!((before - after).abs() < 1.0e-12);
+                assert!(((zoomed.end() - zoomed.begin()) - 20.0 * factor).abs() < 1.0e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn zoom_on_layout() {
+        let layout = layout();
+        let x = zoom_x(Range::new(0.0, 10.0), &layout, layout.plot_left, 0.5);
+        assert_eq!((0.0, 5.0), (x.begin(), x.end()));
+
+        // The bottom of the plot shows the begin of the y range:
+        let y = zoom_y(Range::new(0.0, 10.0), &layout, layout.plot_bottom, 0.5);
+        assert_eq!((0.0, 5.0), (y.begin(), y.end()));
+        let y = zoom_y(Range::new(0.0, 10.0), &layout, layout.plot_top, 0.5);
+        assert_eq!((5.0, 10.0), (y.begin(), y.end()));
+    }
+
+    #[test]
+    fn zoom_limit() {
+        let extent = Range::new(0.0, 100.0);
+        let mut range = Range::new(1.0e6, 1.0e6 + 1.0);
+        for _ in 0..100 {
+            range = zoom_about(range, extent.clone(), 50.0, 0.1);
+        }
+        let length = range.end() - range.begin();
+        assert!(length > 0.0);
+        assert!((length - 1.0e-6).abs() < 1.0e-9);
+        assert!((value_at(&range, &extent, 50.0) - 1.0e6 - 0.5).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn pan_without_plot() {
+        let layout = sized_layout(Size::new(0.0, 0.0));
+        let (x, y) = pan(
+            Range::new(0.0, 1.0),
+            Range::new(2.0, 3.0),
+            &layout,
+            10.0,
+            10.0,
+            AxisLock::None,
+        );
+        assert_eq!((0.0, 1.0), (x.begin(), x.end()));
+        assert_eq!((2.0, 3.0), (y.begin(), y.end()));
+    }
+
+    #[test]
+    fn locked_axis() {
+        let layout = layout();
+        let corner = Point::new(layout.plot_left, layout.plot_bottom);
+        let (x, y) = zoom(
+            Range::new(0.0, 10.0),
+            Range::new(-1.0, 1.0),
+            &layout,
+            corner,
+            0.5,
+            AxisLock::LockY,
+        );
+        assert_eq!((0.0, 5.0), (x.begin(), x.end()));
+        assert_eq!((-1.0, 1.0), (y.begin(), y.end()));
+
+        let (x, y) = zoom(
+            Range::new(0.0, 10.0),
+            Range::new(-1.0, 1.0),
+            &layout,
+            corner,
+            0.5,
+            AxisLock::LockX,
+        );
+        assert_eq!((0.0, 10.0), (x.begin(), x.end()));
+        assert_eq!((-1.0, 0.0), (y.begin(), y.end()));
+
+        let (x, y) = zoom(
+            Range::new(0.0, 10.0),
+            Range::new(-1.0, 1.0),
+            &layout,
+            corner,
+            2.0,
+            AxisLock::None,
+        );
+        assert_eq!((0.0, 20.0), (x.begin(), x.end()));
+        assert_eq!((-1.0, 3.0), (y.begin(), y.end()));
+
+        // Dragging diagonally only pans the unlocked axis:
+        let (x, y) = pan(
+            Range::new(0.0, 10.0),
+            Range::new(-1.0, 1.0),
+            &layout,
+            layout.plot_width / 2.0,
+            layout.plot_height / 4.0,
+            AxisLock::LockY,
+        );
+        assert_eq!((-5.0, 5.0), (x.begin(), x.end()));
+        assert_eq!((-1.0, 1.0), (y.begin(), y.end()));
+    }
+}
@@ -0,0 +1,119 @@
+//! Overview of the full data, with the visible part highlighted.
+
+use super::{autoscale_with_padding, Canvas, DownsampleMode, Series, Transform};
+use crate::geometry::{Point, Range, Rect};
+use crate::style::Color;
+
+/// Opacity of the highlighted window.
+const WINDOW_ALPHA: f64 = 0.3;
+
+/// Draw a strip with an overview of the series, and a box at the visible range.
+///
+/// The series are drawn against the full x range, downsampled to the width
+/// of the strip, and scaled to fit vertically. The visible range is
+/// highlighted with a shaded box, which can be dragged around when combined
+/// with `navigator_window` for hit testing.
+pub fn draw_navigator<C>(
+    canvas: &mut C,
+    rect: &Rect,
+    full_range: &Range<f64>,
+    visible_range: &Range<f64>,
+    series: &[Series],
+) where
+    C: Canvas,
+{
+    if rect.width() <= 0.0 || rect.height() <= 0.0 {
+        return;
+    }
+
+    let visible: Vec<&Series> = series.iter().filter(|s| s.visible).collect();
+    let traces: Vec<&[(f64, f64)]> = visible.iter().map(|s| s.points.as_slice()).collect();
+    let (_, y_range) = autoscale_with_padding(&traces, 0.05);
+    let transform = Transform::from_ranges(full_range, &y_range, rect);
+
+    canvas.save();
+    canvas.set_clip_rect(rect.clone());
+    canvas.set_line_width(1.0);
+    for series in visible {
+        let line: Vec<Point> = DownsampleMode::MinMax
+            .apply(&series.points, rect.width() as usize)
+            .into_iter()
+            .map(|(x, y)| transform.apply_point(Point::new(x, y)))
+            .collect();
+        canvas.set_pen(series.color.clone(), 1.0);
+        canvas.draw_line(&line);
+    }
+
+    let window = navigator_window(rect, full_range, visible_range);
+    canvas.set_pen(Color::gray(), WINDOW_ALPHA);
+    canvas.fill_rect(window.left(), window.top(), window.width(), window.height());
+    canvas.set_pen(Color::black(), 1.0);
+    canvas.draw_rect(window.left(), window.top(), window.width(), window.height());
+    canvas.restore();
+
+    canvas.set_pen(Color::gray(), 1.0);
+    canvas.draw_rect(rect.left(), rect.top(), rect.width(), rect.height());
+}
+
+/// The box of the visible range, as drawn by `draw_navigator`.
+///
+/// The box spans the height of the strip, and is limited to its width.
+pub fn navigator_window(rect: &Rect, full_range: &Range<f64>, visible_range: &Range<f64>) -> Rect {
+    let transform = Transform::from_ranges(full_range, &Range::new(0.0, 1.0), rect);
+    let x0 = transform
+        .apply_point(Point::new(visible_range.begin(), 0.0))
+        .x();
+    let x1 = transform
+        .apply_point(Point::new(visible_range.end(), 0.0))
+        .x();
+    let left = x0.min(x1).clamp(rect.left(), rect.right());
+    let right = x0.max(x1).clamp(rect.left(), rect.right());
+    Rect::new(left, rect.top(), right - left, rect.height())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_navigator, navigator_window};
+    use crate::geometry::{Point, Range, Rect};
+    use crate::render::{DrawOp, RecordingCanvas, Series};
+    use crate::style::Color;
+
+    #[test]
+    fn window_span() {
+        let rect = Rect::new(10.0, 500.0, 200.0, 40.0);
+        let full = Range::new(0.0, 100.0);
+
+        let window = navigator_window(&rect, &full, &Range::new(25.0, 50.0));
+        assert_eq!(Rect::new(60.0, 500.0, 50.0, 40.0), window);
+
+        // Limited to the strip:
+        let window = navigator_window(&rect, &full, &Range::new(-50.0, 10.0));
+        assert_eq!(Rect::new(10.0, 500.0, 20.0, 40.0), window);
+    }
+
+    #[test]
+    fn overview() {
+        let rect = Rect::new(10.0, 500.0, 200.0, 40.0);
+        let points = (0..10_000)
+            .map(|i| (i as f64 * 0.01, (i % 7) as f64))
+            .collect();
+        let series = vec![Series::new("a", points, Color::red())];
+
+        let mut canvas = RecordingCanvas::new();
+        let full = Range::new(0.0, 100.0);
+        draw_navigator(&mut canvas, &rect, &full, &Range::new(25.0, 50.0), &series);
+
+        // The series is downsampled to a few points per pixel column:
+        let lines = canvas.polylines();
+        assert_eq!(1, lines.len());
+        assert!(lines[0].len() <= 4 * 200);
+
+        let window = canvas.ops().iter().find_map(|op| match op {
+            DrawOp::FillPolygon(points) => Some(points.clone()),
+            _ => None,
+        });
+        let window = window.unwrap();
+        assert!(window.contains(&Point::new(60.0, 500.0)));
+        assert!(window.contains(&Point::new(110.0, 540.0)));
+    }
+}
@@ -1,6 +1,82 @@
+use super::{TickFormat, TickStyle};
+use crate::style::Theme;
+
+/// Empty space around the chart, in pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Margins {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+impl Margins {
+    pub fn new(top: f64, right: f64, bottom: f64, left: f64) -> Self {
+        Margins {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// The same margin on all sides.
+    pub fn uniform(margin: f64) -> Self {
+        Self::new(margin, margin, margin, margin)
+    }
+
+    /// The margins as they were derived from a single padding value.
+    ///
+    /// There is no margin on the left, since the y axis legend already
+    /// includes padding, and the bottom has padding around the x axis legend.
+    pub fn from_padding(padding: f64) -> Self {
+        Self::new(padding, padding, padding * 2.0, 0.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct ChartOptions {
+    /// Spacing of the tick labels, which are placed at twice this distance from the plot.
     pub tick_size: f64,
     pub padding: f64,
+
+    /// Space between the chart and the edges of the canvas.
+    pub margins: Margins,
+
+    /// Minimum size of the plot area, which is kept as long as the window is large enough.
+    pub min_plot_width: f64,
+    pub min_plot_height: f64,
+
+    /// Reserve room for a second y axis on the right of the plot.
+    pub right_y_axis: bool,
+
+    /// Colors and font sizes of the chart.
+    pub theme: Theme,
+
+    /// Format of the tick labels, as drawn by `render_chart_to_svg`.
+    pub tick_format: TickFormat,
+
+    /// Place the plot edges and gridlines on whole pixels, so thin lines are crisp.
+    pub snap_to_pixels: bool,
+
+    /// Amount of minor ticks between two major ticks, as drawn by `render_chart_to_svg`.
+    pub minor_tick_count: usize,
+
+    /// Direction and length of the tick marks along the axes.
+    pub tick_style: TickStyle,
+
+    /// Title drawn above the plot, with room reserved for it.
+    ///
+    /// A title set on the `Chart` itself takes precedence.
+    pub title: Option<String>,
+    pub title_font_size: f64,
+}
+
+impl ChartOptions {
+    /// Start building options, from the defaults.
+    pub fn builder() -> ChartOptionsBuilder {
+        ChartOptionsBuilder::new()
+    }
 }
 
 impl Default for ChartOptions {
@@ -8,6 +84,196 @@ impl Default for ChartOptions {
         ChartOptions {
             tick_size: 7.0,
             padding: 10.0,
+            margins: Margins::from_padding(10.0),
+            min_plot_width: 100.0,
+            min_plot_height: 50.0,
+            right_y_axis: false,
+            theme: Theme::default(),
+            tick_format: TickFormat::default(),
+            snap_to_pixels: false,
+            minor_tick_count: 0,
+            tick_style: TickStyle::default(),
+            title: None,
+            title_font_size: 12.0,
         }
     }
 }
+
+/// Options which are rejected by `ChartOptionsBuilder::build`.
+#[derive(Debug, PartialEq)]
+pub enum ChartOptionsError {
+    /// A size or distance which is negative or not a number.
+    Negative(&'static str),
+}
+
+/// Construct `ChartOptions` with chainable setters.
+///
+/// Fields which are not set keep their default value.
+pub struct ChartOptionsBuilder {
+    options: ChartOptions,
+}
+
+impl ChartOptionsBuilder {
+    fn new() -> Self {
+        ChartOptionsBuilder {
+            options: ChartOptions::default(),
+        }
+    }
+
+    /// Length of the tick markers.
+    pub fn tick_size(mut self, tick_size: f64) -> Self {
+        self.options.tick_size = tick_size;
+        self
+    }
+
+    /// Space around the axis legends and labels.
+    ///
+    /// This does not change the margins, use `margins` for that.
+    pub fn padding(mut self, padding: f64) -> Self {
+        self.options.padding = padding;
+        self
+    }
+
+    /// Space between the chart and the edges of the canvas.
+    pub fn margins(mut self, margins: Margins) -> Self {
+        self.options.margins = margins;
+        self
+    }
+
+    /// Minimum size of the plot area.
+    pub fn min_plot_size(mut self, width: f64, height: f64) -> Self {
+        self.options.min_plot_width = width;
+        self.options.min_plot_height = height;
+        self
+    }
+
+    /// Reserve room for a second y axis on the right of the plot.
+    pub fn right_y_axis(mut self, right_y_axis: bool) -> Self {
+        self.options.right_y_axis = right_y_axis;
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.options.theme = theme;
+        self
+    }
+
+    pub fn tick_format(mut self, tick_format: TickFormat) -> Self {
+        self.options.tick_format = tick_format;
+        self
+    }
+
+    /// Place the plot edges and gridlines on whole pixels.
+    pub fn snap_to_pixels(mut self, snap_to_pixels: bool) -> Self {
+        self.options.snap_to_pixels = snap_to_pixels;
+        self
+    }
+
+    /// Amount of minor ticks between two major ticks, zero to leave them out.
+    pub fn minor_tick_count(mut self, minor_tick_count: usize) -> Self {
+        self.options.minor_tick_count = minor_tick_count;
+        self
+    }
+
+    pub fn tick_style(mut self, tick_style: TickStyle) -> Self {
+        self.options.tick_style = tick_style;
+        self
+    }
+
+    /// Title drawn above the plot.
+    pub fn title(mut self, title: &str) -> Self {
+        self.options.title = Some(title.to_owned());
+        self
+    }
+
+    pub fn title_font_size(mut self, title_font_size: f64) -> Self {
+        self.options.title_font_size = title_font_size;
+        self
+    }
+
+    /// Check the options, and return them when they are usable.
+    pub fn build(self) -> Result<ChartOptions, ChartOptionsError> {
+        let options = self.options;
+        let distances = [
+            ("tick_size", options.tick_size),
+            ("padding", options.padding),
+            ("margins.top", options.margins.top),
+            ("margins.right", options.margins.right),
+            ("margins.bottom", options.margins.bottom),
+            ("margins.left", options.margins.left),
+            ("min_plot_width", options.min_plot_width),
+            ("min_plot_height", options.min_plot_height),
+            ("tick_style.length", options.tick_style.length),
+            ("title_font_size", options.title_font_size),
+        ];
+        for &(name, value) in distances.iter() {
+            if value.is_nan() || value < 0.0 {
+                return Err(ChartOptionsError::Negative(name));
+            }
+        }
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChartOptions, ChartOptionsError, Margins};
+    use crate::render::{TickDirection, TickFormat, TickStyle};
+    use crate::style::Theme;
+
+    #[test]
+    fn builder_defaults() {
+        let options = ChartOptions::builder().build().unwrap();
+        assert_eq!(ChartOptions::default(), options);
+    }
+
+    #[test]
+    fn builder_setters() {
+        let options = ChartOptions::builder()
+            .tick_size(4.0)
+            .padding(3.0)
+            .margins(Margins::uniform(5.0))
+            .min_plot_size(20.0, 10.0)
+            .right_y_axis(true)
+            .theme(Theme::dark())
+            .tick_format(TickFormat::SiPrefix(3))
+            .snap_to_pixels(true)
+            .minor_tick_count(4)
+            .tick_style(TickStyle::new(TickDirection::Cross, 5.0))
+            .title("Sensors")
+            .title_font_size(20.0)
+            .build()
+            .unwrap();
+
+        assert_eq!(4.0, options.tick_size);
+        assert_eq!(3.0, options.padding);
+        assert_eq!(Margins::uniform(5.0), options.margins);
+        assert_eq!(
+            (20.0, 10.0),
+            (options.min_plot_width, options.min_plot_height)
+        );
+        assert!(options.right_y_axis);
+        assert_eq!(Theme::dark(), options.theme);
+        assert_eq!(TickFormat::SiPrefix(3), options.tick_format);
+        assert!(options.snap_to_pixels);
+        assert_eq!(4, options.minor_tick_count);
+        assert_eq!(TickDirection::Cross, options.tick_style.direction);
+        assert_eq!(5.0, options.tick_style.length);
+        assert_eq!(Some("Sensors".to_owned()), options.title);
+        assert_eq!(20.0, options.title_font_size);
+    }
+
+    #[test]
+    fn builder_validates() {
+        assert_eq!(
+            Err(ChartOptionsError::Negative("padding")),
+            ChartOptions::builder().padding(-1.0).build()
+        );
+        assert_eq!(
+            Err(ChartOptionsError::Negative("margins.left")),
+            ChartOptions::builder()
+                .margins(Margins::new(0.0, 0.0, 0.0, f64::NAN))
+                .build()
+        );
+    }
+}
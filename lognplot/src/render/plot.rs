@@ -0,0 +1,446 @@
+//! Complete plots of a few series, in a single call.
+
+use super::canvas::{HorizontalAnchor, VerticalAnchor};
+use super::chart::{PIXELS_PER_X_TICK, PIXELS_PER_Y_TICK};
+use super::{
+    draw_grid, draw_legend, draw_minor_grid, draw_minor_ticks, draw_series, draw_x_axis,
+    draw_y_axis, format_tick, minor_ticks, ticks, Axis, AxisOptions, Canvas, ChartLayout,
+    ChartOptions, GridLayout, GridOptions, LegendPosition, Series, SvgOutput, Transform,
+};
+use crate::geometry::{Point, Range, Size};
+
+/// Render series into a finished SVG document, with axis, gridlines, legend and title.
+///
+/// The series are plotted against the given x and y range, with the title
+/// of the options, if any, above the plot. Ticks are placed at round numbers, in the tick
+/// format of the options. The output only depends on the input, so it is
+/// suitable for reports and tests.
+pub fn render_chart_to_svg(
+    size: Size,
+    series: &[Series],
+    x_range: &Range<f64>,
+    y_range: &Range<f64>,
+    options: &ChartOptions,
+) -> String {
+    let mut buffer: Vec<u8> = vec![];
+    {
+        let mut output = SvgOutput::new(&mut buffer).with_size(size.clone());
+        draw_plot(&mut output, size, series, x_range, y_range, options);
+    }
+    String::from_utf8(buffer).expect("SVG output is valid utf-8")
+}
+
+/// The series drawn in a single row of `draw_subplots`, against their own y range.
+#[derive(Debug, Clone)]
+pub struct SubplotSpec {
+    pub series: Vec<Series>,
+    pub y_range: Range<f64>,
+}
+
+impl SubplotSpec {
+    pub fn new(series: Vec<Series>, y_range: Range<f64>) -> Self {
+        SubplotSpec { series, y_range }
+    }
+}
+
+/// Draw a vertical stack of plots, which share the x range.
+///
+/// Subplot `i` is drawn in row `i` of the grid, and subplots beyond the
+/// rows of the grid are left out. The x ticks are at the same positions in
+/// each row, but are only labeled below the bottom row. The width of the y
+/// axis legend is the same for all rows, so the plots line up. The title
+/// of the options, if any, is drawn above the top row.
+pub fn draw_subplots<C>(
+    canvas: &mut C,
+    grid: &mut GridLayout,
+    x_range: &Range<f64>,
+    subplots: &[SubplotSpec],
+    options: &ChartOptions,
+) where
+    C: Canvas,
+{
+    let theme = &options.theme;
+    canvas.set_font_size(theme.font_size);
+    grid.title_height = title_height(canvas, options);
+
+    let layouts = grid.layouts(options);
+    let first = match layouts.first() {
+        Some(first) => first,
+        None => return,
+    };
+    let x_ticks = tick_labels(x_range, x_tick_count(first), options);
+    let y_ticks: Vec<Vec<(f64, String)>> = layouts
+        .iter()
+        .zip(subplots.iter())
+        .map(|(layout, subplot)| tick_labels(&subplot.y_range, y_tick_count(layout), options))
+        .collect();
+
+    let all_y_ticks: Vec<(f64, String)> = y_ticks.iter().flatten().cloned().collect();
+    grid.y_axis_legend_width = y_axis_legend_width(canvas, &all_y_ticks, options);
+    grid.x_axis_legend_height = x_axis_legend_height(canvas, &x_ticks, options);
+    let layouts = grid.layouts(options);
+
+    canvas.set_pen(theme.background.clone(), 1.0);
+    canvas.fill_rect(0.0, 0.0, grid.width, grid.height);
+
+    let rows = layouts.len();
+    for (index, ((layout, subplot), y_ticks)) in layouts
+        .iter()
+        .zip(subplots.iter())
+        .zip(y_ticks.iter())
+        .enumerate()
+    {
+        let transform = Transform::from_ranges(x_range, &subplot.y_range, &layout.plot_rect());
+        let x_labels = index + 1 == rows;
+        draw_plot_area(
+            canvas, layout, &transform, &x_ticks, x_labels, y_ticks, options,
+        );
+        draw_plot_content(canvas, layout, &transform, &subplot.series, options);
+    }
+
+    draw_title(canvas, first.width, options);
+}
+
+fn draw_plot<C>(
+    canvas: &mut C,
+    size: Size,
+    series: &[Series],
+    x_range: &Range<f64>,
+    y_range: &Range<f64>,
+    options: &ChartOptions,
+) where
+    C: Canvas,
+{
+    let theme = &options.theme;
+    canvas.set_font_size(theme.font_size);
+
+    let mut layout = ChartLayout::new(size);
+    layout.info_bar_height = 0.0;
+    layout.title_height = title_height(canvas, options);
+    layout.layout(options);
+
+    // Label the ticks, and make room for the labels:
+    let x_ticks = tick_labels(x_range, x_tick_count(&layout), options);
+    let y_ticks = tick_labels(y_range, y_tick_count(&layout), options);
+    layout.y_axis_legend_width = y_axis_legend_width(canvas, &y_ticks, options);
+    layout.x_axis_legend_height = x_axis_legend_height(canvas, &x_ticks, options);
+    layout.layout(options);
+
+    let transform = Transform::from_ranges(x_range, y_range, &layout.plot_rect());
+
+    canvas.set_pen(theme.background.clone(), 1.0);
+    canvas.fill_rect(0.0, 0.0, layout.width, layout.height);
+    draw_plot_area(
+        canvas, &layout, &transform, &x_ticks, true, &y_ticks, options,
+    );
+    draw_plot_content(canvas, &layout, &transform, series, options);
+    draw_title(canvas, layout.width, options);
+}
+
+fn x_tick_count(layout: &ChartLayout) -> usize {
+    (layout.plot_width as usize / PIXELS_PER_X_TICK).max(2)
+}
+
+fn y_tick_count(layout: &ChartLayout) -> usize {
+    (layout.plot_height as usize / PIXELS_PER_Y_TICK).max(2)
+}
+
+/// Ticks within the range, labeled in the tick format of the options.
+fn tick_labels(range: &Range<f64>, count: usize, options: &ChartOptions) -> Vec<(f64, String)> {
+    ticks(range, count)
+        .into_iter()
+        .filter(|t| range.contains(*t))
+        .map(|t| (t, format_tick(t, options.tick_format)))
+        .collect()
+}
+
+fn title_height<C: Canvas>(canvas: &mut C, options: &ChartOptions) -> f64 {
+    match &options.title {
+        Some(title) => {
+            canvas.set_font_size(options.title_font_size);
+            let height = canvas.text_size(title).height + options.padding;
+            canvas.set_font_size(options.theme.font_size);
+            height
+        }
+        None => 0.0,
+    }
+}
+
+fn y_axis_legend_width<C: Canvas>(
+    canvas: &C,
+    y_ticks: &[(f64, String)],
+    options: &ChartOptions,
+) -> f64 {
+    let y_labels_max_width = y_ticks
+        .iter()
+        .map(|t| canvas.text_size(&t.1).width)
+        .fold(1.0, f64::max);
+    y_labels_max_width + options.tick_size * 2.0 + options.padding
+}
+
+fn x_axis_legend_height<C: Canvas>(
+    canvas: &C,
+    x_ticks: &[(f64, String)],
+    options: &ChartOptions,
+) -> f64 {
+    let x_labels_max_height = x_ticks
+        .iter()
+        .map(|t| canvas.text_size(&t.1).height)
+        .fold(1.0, f64::max);
+    x_labels_max_height + options.tick_size * 2.0 + options.padding
+}
+
+/// Fill the plot area, and draw the gridlines and ticks.
+///
+/// The x ticks are only labeled when `x_labels` is set.
+fn draw_plot_area<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    x_ticks: &[(f64, String)],
+    x_labels: bool,
+    y_ticks: &[(f64, String)],
+    options: &ChartOptions,
+) where
+    C: Canvas,
+{
+    let theme = &options.theme;
+    let plot_rect = layout.plot_rect();
+    canvas.set_pen(theme.plot_background.clone(), 1.0);
+    canvas.fill_rect(
+        plot_rect.left(),
+        plot_rect.top(),
+        plot_rect.width(),
+        plot_rect.height(),
+    );
+
+    let x_values: Vec<f64> = x_ticks.iter().map(|t| t.0).collect();
+    let y_values: Vec<f64> = y_ticks.iter().map(|t| t.0).collect();
+    let x_minor = padded_minor_ticks(&x_values, options.minor_tick_count);
+    let y_minor = padded_minor_ticks(&y_values, options.minor_tick_count);
+    let grid_options = GridOptions {
+        snap_to_pixels: options.snap_to_pixels,
+        ..GridOptions::from_theme(theme)
+    };
+    draw_minor_grid(canvas, layout, transform, &x_minor, &y_minor, &grid_options);
+    draw_grid(
+        canvas,
+        layout,
+        transform,
+        &x_values,
+        &y_values,
+        &grid_options,
+    );
+
+    // Tick markers and labels, the frame drawn later on doubles as axis line:
+    let axis_options = AxisOptions {
+        spine: false,
+        ..AxisOptions::from_options(options)
+    };
+    let x_axis_options = AxisOptions {
+        labels: x_labels,
+        ..axis_options.clone()
+    };
+    let format = options.tick_format;
+    draw_x_axis(
+        canvas,
+        layout,
+        transform,
+        &x_values,
+        format,
+        &x_axis_options,
+    );
+    draw_y_axis(canvas, layout, transform, &y_values, format, &axis_options);
+    draw_minor_ticks(canvas, layout, transform, Axis::X, &x_minor, &axis_options);
+    draw_minor_ticks(canvas, layout, transform, Axis::Y, &y_minor, &axis_options);
+}
+
+/// Minor ticks between the major ticks, and beyond the first and last major
+/// tick, up to the edges of the plot.
+fn padded_minor_ticks(major: &[f64], minor_count: usize) -> Vec<f64> {
+    if major.len() < 2 || minor_count == 0 {
+        return vec![];
+    }
+
+    let step = major[1] - major[0];
+    let mut padded = vec![major[0] - step];
+    padded.extend_from_slice(major);
+    padded.push(major[major.len() - 1] + step);
+    minor_ticks(&padded, minor_count)
+}
+
+/// Draw the series, the frame around the plot area and the legend.
+fn draw_plot_content<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    series: &[Series],
+    options: &ChartOptions,
+) where
+    C: Canvas,
+{
+    let theme = &options.theme;
+    let plot_rect = layout.plot_rect();
+    for series in series {
+        draw_series(canvas, layout, transform, series);
+    }
+
+    canvas.set_pen(theme.axis.clone(), 1.0);
+    canvas.set_line_width(2.0);
+    canvas.draw_rect(
+        plot_rect.left(),
+        plot_rect.top(),
+        plot_rect.width(),
+        plot_rect.height(),
+    );
+
+    let entries: Vec<(String, _)> = series
+        .iter()
+        .filter(|s| s.visible)
+        .map(|s| s.legend_entry())
+        .collect();
+    draw_legend(canvas, layout, &entries, LegendPosition::TopRight, theme);
+}
+
+fn draw_title<C: Canvas>(canvas: &mut C, width: f64, options: &ChartOptions) {
+    if let Some(title) = &options.title {
+        let theme = &options.theme;
+        canvas.set_font_size(options.title_font_size);
+        canvas.set_pen(theme.text.clone(), 1.0);
+        canvas.print_text(
+            &Point::new(width / 2.0, options.margins.top),
+            HorizontalAnchor::Middle,
+            VerticalAnchor::Top,
+            title,
+        );
+        canvas.set_font_size(theme.font_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_subplots, padded_minor_ticks, render_chart_to_svg, SubplotSpec};
+    use crate::geometry::{Range, Size};
+    use crate::render::{ChartLayout, ChartOptions, GridLayout, Series, SvgOutput};
+    use crate::style::Color;
+
+    fn render() -> String {
+        let series = vec![
+            Series::new(
+                "temperature",
+                vec![(0.0, 20.0), (2.5, 22.5), (5.0, 21.0), (10.0, 24.0)],
+                Color::red(),
+            ),
+            Series::new(
+                "humidity",
+                vec![(0.0, 40.0), (2.5, 35.0), (5.0, 37.5), (10.0, 30.0)],
+                Color::new(0, 0, 255),
+            ),
+        ];
+        let options = ChartOptions {
+            title: Some("Sensors".to_owned()),
+            ..ChartOptions::default()
+        };
+        render_chart_to_svg(
+            Size::new(640.0, 400.0),
+            &series,
+            &Range::new(0.0, 10.0),
+            &Range::new(0.0, 50.0),
+            &options,
+        )
+    }
+
+    #[test]
+    fn golden_file() {
+        // Update the reference when the output changes on purpose:
+        let expected = include_str!("testdata/small_chart.svg");
+        assert_eq!(expected, render());
+    }
+
+    #[test]
+    fn shared_x_axis() {
+        let subplots = vec![
+            SubplotSpec::new(
+                vec![Series::new(
+                    "a",
+                    vec![(0.0, 0.0), (10.0, 1.0)],
+                    Color::red(),
+                )],
+                Range::new(0.0, 1.0),
+            ),
+            SubplotSpec::new(
+                vec![Series::new(
+                    "b",
+                    vec![(0.0, 50.0), (10.0, 20.0)],
+                    Color::green(),
+                )],
+                Range::new(0.0, 100.0),
+            ),
+        ];
+        let mut grid = GridLayout::new(Size::new(800.0, 600.0), 2);
+        let mut buffer: Vec<u8> = vec![];
+        draw_subplots(
+            &mut SvgOutput::new(&mut buffer),
+            &mut grid,
+            &Range::new(0.0, 10.0),
+            &subplots,
+            &ChartOptions::default(),
+        );
+        let svg = String::from_utf8(buffer).unwrap();
+
+        // Each row gets half of the height:
+        let layouts = grid.layouts(&ChartOptions::default());
+        assert!(layouts[0].plot_top < layouts[0].plot_bottom);
+        assert!(layouts[0].plot_bottom < 300.0 && 300.0 < layouts[1].plot_top);
+        assert!(layouts[1].plot_bottom < 600.0);
+        assert!((layouts[0].plot_left - layouts[1].plot_left).abs() < 1.0e-9);
+
+        // A vertical gridline at each x tick, in both rows:
+        let vertical = |layout: &ChartLayout| {
+            let mut xs: Vec<String> = svg
+                .lines()
+                .filter(|l| l.contains("stroke:rgb(120,120,120)"))
+                .flat_map(|l| {
+                    let top = format!(" {} L ", layout.plot_top);
+                    l.split("M ")
+                        .filter(|segment| segment.contains(&top))
+                        .map(|segment| segment.split(' ').next().unwrap().to_owned())
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            xs.sort();
+            xs
+        };
+        let top_xs = vertical(&layouts[0]);
+        assert!(top_xs.len() >= 3, "{}", svg);
+        assert_eq!(top_xs, vertical(&layouts[1]));
+
+        // Only the bottom row has x labels, so each label appears once:
+        assert_eq!(1, svg.matches(">10</text>").count(), "{}", svg);
+    }
+
+    #[test]
+    fn minor_ticks_up_to_edges() {
+        assert_eq!(
+            vec![-1.0, 1.0, 3.0, 5.0],
+            padded_minor_ticks(&[0.0, 2.0, 4.0], 1)
+        );
+        assert!(padded_minor_ticks(&[0.0, 2.0, 4.0], 0).is_empty());
+        assert!(padded_minor_ticks(&[1.0], 4).is_empty());
+
+        // Minor gridlines are drawn in the grid color, at 40% opacity:
+        let options = ChartOptions {
+            minor_tick_count: 4,
+            ..ChartOptions::default()
+        };
+        let range = Range::new(0.0, 10.0);
+        let svg = render_chart_to_svg(Size::new(640.0, 400.0), &[], &range, &range, &options);
+        assert!(svg.contains("stroke-opacity:0.4"), "{}", svg);
+        assert!(!render().contains("stroke-opacity:0.4"));
+    }
+
+    #[test]
+    fn deterministic() {
+        assert_eq!(render(), render());
+    }
+}
@@ -0,0 +1,633 @@
+use super::canvas::{HorizontalAnchor, VerticalAnchor};
+use super::image;
+use super::softgl::{self, Paint, Pixmap};
+use super::Canvas;
+use crate::geometry::{Path, Point, Rect, Size};
+use crate::style::{Color, Gradient};
+
+/// Render into an RGBA pixel buffer, which can be saved as PNG.
+///
+/// Text is not drawn, since there is no font rasterizer. The size of text
+/// is estimated like the SVG output does, so charts get the same layout.
+pub struct RasterCanvas {
+    pixmap: Pixmap,
+    pen: Color,
+    alpha: f64,
+    width: f64,
+
+    /// Dash pattern of lines, empty for solid lines.
+    dash: Vec<f64>,
+    gradient: Option<Gradient>,
+    clip: Option<Rect>,
+
+    /// Smooth the edges of shapes.
+    antialias: bool,
+
+    /// Saved states, see `save` and `restore`.
+    state_stack: Vec<RasterState>,
+
+    /// Regions drawn into since the last `take_dirty_rects`.
+    dirty: Vec<Rect>,
+
+    /// When set, drawing is limited to this region, see `set_redraw_region`.
+    redraw_region: Option<Rect>,
+}
+
+/// Drawing state which can be saved and restored.
+struct RasterState {
+    pen: Color,
+    alpha: f64,
+    width: f64,
+    dash: Vec<f64>,
+    gradient: Option<Gradient>,
+    clip: Option<Rect>,
+}
+
+impl RasterCanvas {
+    /// Create a new, fully transparent, canvas of the given size in pixels.
+    pub fn new(width: usize, height: usize) -> Self {
+        RasterCanvas {
+            pixmap: Pixmap::new(width, height),
+            pen: Color::black(),
+            alpha: 1.0,
+            width: 1.0,
+            dash: vec![],
+            gradient: None,
+            clip: None,
+            antialias: true,
+            state_stack: vec![],
+            dirty: vec![],
+            redraw_region: None,
+        }
+    }
+
+    /// Enable or disable anti-aliasing, which is enabled by default.
+    ///
+    /// Without anti-aliasing, drawing is faster and edges are crisp, but jagged.
+    pub fn with_antialiasing(mut self, enabled: bool) -> Self {
+        self.antialias = enabled;
+        self
+    }
+
+    /// Get the RGBA value of a single pixel.
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        self.pixmap.pixel(x, y)
+    }
+
+    /// Take the rendered image, as width, height and RGBA bytes.
+    pub fn into_rgba(self) -> (usize, usize, Vec<u8>) {
+        let width = self.pixmap.width();
+        let height = self.pixmap.height();
+        (width, height, self.pixmap.into_data())
+    }
+
+    /// Take the regions which were drawn into since the last call.
+    ///
+    /// The rectangles cover whole pixels, and overlapping regions are merged
+    /// into one. A user interface only has to copy these parts of the image
+    /// to the screen.
+    pub fn take_dirty_rects(&mut self) -> Vec<Rect> {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Clear a region, and limit all drawing to it, or draw everywhere again with None.
+    ///
+    /// The region applies on top of the clip rectangle. Passing the dirty
+    /// rectangles of the previous frame allows redrawing just the changed
+    /// part of a plot, instead of the whole image.
+    pub fn set_redraw_region(&mut self, region: Option<Rect>) {
+        if let Some(region) = &region {
+            self.pixmap.clear(region);
+            let corners = [
+                Point::new(region.left(), region.top()),
+                Point::new(region.right(), region.bottom()),
+            ];
+            self.mark_dirty(&corners, &self.canvas_rect());
+        }
+        self.redraw_region = region;
+    }
+
+    /// Write the rendered image to a PNG file.
+    #[cfg(feature = "png")]
+    pub fn save_png<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        let writer = std::io::BufWriter::new(file);
+        let mut encoder = png::Encoder::new(
+            writer,
+            self.pixmap.width() as u32,
+            self.pixmap.height() as u32,
+        );
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(self.pixmap.data())?;
+        Ok(())
+    }
+
+    /// Stroke a polyline with the current pen and line width.
+    ///
+    /// Lines thinner than a pixel are drawn one pixel wide, with a lower
+    /// alpha, so that they do not disappear.
+    fn stroke(&mut self, points: &[Point], closed: bool) {
+        let (width, alpha) = if self.width < 1.0 {
+            (1.0, self.alpha * self.width.max(0.0))
+        } else {
+            (self.width, self.alpha)
+        };
+        let area = match self.drawable_area() {
+            Some(area) => area,
+            None => return,
+        };
+        let contours = if self.dash.is_empty() {
+            softgl::stroke_polyline(points, width, closed)
+        } else {
+            softgl::dash_polyline(points, &self.dash, closed)
+                .iter()
+                .flat_map(|dash| softgl::stroke_polyline(dash, width, false))
+                .collect()
+        };
+        let paint = Paint::Solid(&self.pen, alpha);
+        softgl::fill_contours(
+            &mut self.pixmap,
+            &contours,
+            Some(&area),
+            &paint,
+            self.antialias,
+        );
+        let outline: Vec<Point> = contours.into_iter().flatten().collect();
+        self.mark_dirty(&outline, &area);
+    }
+
+    fn fill(&mut self, points: Vec<Point>) {
+        let paint = match &self.gradient {
+            Some(gradient) => Paint::Gradient(gradient),
+            None => Paint::Solid(&self.pen, self.alpha),
+        };
+        let area = match self.drawable_area() {
+            Some(area) => area,
+            None => return,
+        };
+        let contours = [points];
+        softgl::fill_contours(
+            &mut self.pixmap,
+            &contours,
+            Some(&area),
+            &paint,
+            self.antialias,
+        );
+        self.mark_dirty(&contours[0], &area);
+    }
+
+    /// The clip rectangle, limited to the canvas and the redraw region.
+    ///
+    /// Returns None when there is nothing left to draw into.
+    fn drawable_area(&self) -> Option<Rect> {
+        let mut area = self.canvas_rect();
+        for limit in self.clip.iter().chain(self.redraw_region.iter()) {
+            area = area.intersection(limit)?;
+        }
+        Some(area)
+    }
+
+    fn canvas_rect(&self) -> Rect {
+        Rect::new(
+            0.0,
+            0.0,
+            self.pixmap.width() as f64,
+            self.pixmap.height() as f64,
+        )
+    }
+
+    /// Add the whole pixels around the given points, within the area that
+    /// was drawn into, to the dirty regions.
+    ///
+    /// A region which overlaps with existing regions is merged with them.
+    fn mark_dirty(&mut self, points: &[Point], area: &Rect) {
+        if points.is_empty() {
+            return;
+        }
+
+        let left = points.iter().map(|p| p.x()).fold(f64::INFINITY, f64::min);
+        let right = points
+            .iter()
+            .map(|p| p.x())
+            .fold(f64::NEG_INFINITY, f64::max);
+        let top = points.iter().map(|p| p.y()).fold(f64::INFINITY, f64::min);
+        let bottom = points
+            .iter()
+            .map(|p| p.y())
+            .fold(f64::NEG_INFINITY, f64::max);
+        let bounds = Rect::new(left, top, right - left, bottom - top);
+        let mut region = match area.intersection(&bounds) {
+            Some(region) => {
+                let left = region.left().floor();
+                let top = region.top().floor();
+                Rect::new(
+                    left,
+                    top,
+                    region.right().ceil() - left,
+                    region.bottom().ceil() - top,
+                )
+            }
+            None => return,
+        };
+        if region.width() <= 0.0 || region.height() <= 0.0 {
+            return;
+        }
+
+        // Merging can make the region overlap with others, so repeat until it does not:
+        while let Some(index) = self
+            .dirty
+            .iter()
+            .position(|other| other.intersection(&region).is_some())
+        {
+            region = region.union(&self.dirty.swap_remove(index));
+        }
+        self.dirty.push(region);
+    }
+}
+
+impl Canvas for RasterCanvas {
+    fn set_pen(&mut self, color: Color, alpha: f64) {
+        self.pen = color;
+        self.alpha = alpha;
+        self.gradient = None;
+        self.dash.clear();
+    }
+
+    fn set_line_width(&mut self, width: f64) {
+        self.width = width;
+    }
+
+    fn set_dash(&mut self, dash: &[f64]) {
+        self.dash = dash.to_vec();
+    }
+
+    /// Text is not drawn, see `RasterCanvas`.
+    fn print_text(
+        &mut self,
+        _p: &Point,
+        _horizontal_anchor: HorizontalAnchor,
+        _vertical_anchor: VerticalAnchor,
+        text: &str,
+    ) {
+        trace!("Skipping text {}", text);
+    }
+
+    fn text_size(&self, text: &str) -> Size {
+        // Assume 16x8 font size here, like the SVG output.
+        let width = (text.len() * 8) as f64;
+        Size::new(width, 16.0)
+    }
+
+    fn draw_line(&mut self, points: &[Point]) {
+        self.stroke(points, false);
+    }
+
+    fn draw_polygon(&mut self, points: &[Point]) {
+        if points.len() > 2 {
+            self.stroke(points, true);
+        }
+    }
+
+    fn draw_circle(&mut self, center: &Point, radius: f64) {
+        let points = softgl::flatten_circle(center, radius);
+        self.stroke(&points, true);
+    }
+
+    fn draw_path(&mut self, path: &Path) {
+        for (points, closed) in softgl::flatten_path(path) {
+            self.stroke(&points, closed);
+        }
+    }
+
+    fn fill_polygon(&mut self, points: &[Point]) {
+        self.fill(points.to_vec());
+    }
+
+    fn fill_circle(&mut self, center: &Point, radius: f64) {
+        self.fill(softgl::flatten_circle(center, radius));
+    }
+
+    fn draw_image(
+        &mut self,
+        top_left: Point,
+        width: f64,
+        height: f64,
+        rgba: &[u8],
+        img_w: u32,
+        img_h: u32,
+    ) {
+        if !image::is_valid_rgba(rgba, img_w, img_h) {
+            warn!("Invalid image data for a {}x{} image", img_w, img_h);
+            return;
+        }
+
+        let area = match self.drawable_area() {
+            Some(area) => area,
+            None => return,
+        };
+        let rect = Rect::new(top_left.x(), top_left.y(), width, height);
+        softgl::draw_image(
+            &mut self.pixmap,
+            &rect,
+            Some(&area),
+            rgba,
+            img_w as usize,
+            img_h as usize,
+        );
+        let corners = [
+            top_left,
+            Point::new(top_left.x() + width, top_left.y() + height),
+        ];
+        self.mark_dirty(&corners, &area);
+    }
+
+    fn set_gradient(&mut self, gradient: &Gradient) {
+        self.gradient = Some(gradient.clone());
+    }
+
+    fn set_clip_rect(&mut self, rect: Rect) {
+        self.clip = Some(rect);
+    }
+
+    fn clear_clip(&mut self) {
+        self.clip = None;
+    }
+
+    fn save(&mut self) {
+        self.state_stack.push(RasterState {
+            pen: self.pen.clone(),
+            alpha: self.alpha,
+            width: self.width,
+            dash: self.dash.clone(),
+            gradient: self.gradient.clone(),
+            clip: self.clip.clone(),
+        });
+    }
+
+    fn restore(&mut self) {
+        if let Some(state) = self.state_stack.pop() {
+            self.pen = state.pen;
+            self.alpha = state.alpha;
+            self.width = state.width;
+            self.dash = state.dash;
+            self.gradient = state.gradient;
+            self.clip = state.clip;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RasterCanvas;
+    use crate::geometry::{Point, Rect};
+    use crate::render::Canvas;
+    use crate::style::{Color, Stroke};
+
+    #[test]
+    fn filled_rectangle() {
+        let mut canvas = RasterCanvas::new(20, 20);
+        canvas.set_pen(Color::red(), 1.0);
+        canvas.fill_rect(5.0, 5.0, 10.0, 10.0);
+
+        assert_eq!([255, 0, 0, 255], canvas.pixel(5, 5));
+        assert_eq!([255, 0, 0, 255], canvas.pixel(14, 14));
+        assert_eq!([0, 0, 0, 0], canvas.pixel(4, 10));
+        assert_eq!([0, 0, 0, 0], canvas.pixel(15, 10));
+    }
+
+    #[test]
+    fn infinite_vertex() {
+        for &antialias in &[true, false] {
+            let mut canvas = RasterCanvas::new(20, 20).with_antialiasing(antialias);
+            canvas.set_pen(Color::red(), 1.0);
+            canvas.fill_polygon(&[
+                Point::new(2.0, 2.0),
+                Point::new(10.0, f64::NEG_INFINITY),
+                Point::new(18.0, 2.0),
+                Point::new(18.0, 18.0),
+                Point::new(2.0, 18.0),
+            ]);
+            canvas.fill_polygon(&[
+                Point::new(f64::NAN, 2.0),
+                Point::new(18.0, 2.0),
+                Point::new(18.0, 18.0),
+            ]);
+
+            // The finite edges are still filled:
+            assert_eq!([255, 0, 0, 255], canvas.pixel(10, 10));
+        }
+    }
+
+    #[test]
+    fn rounded_rectangle() {
+        let mut canvas = RasterCanvas::new(20, 20);
+        canvas.draw_rounded_rect(
+            &Rect::new(2.0, 2.0, 16.0, 16.0),
+            6.0,
+            None,
+            Some(Color::red()),
+        );
+
+        assert_eq!([255, 0, 0, 255], canvas.pixel(10, 10));
+        assert_eq!([255, 0, 0, 255], canvas.pixel(10, 2));
+        assert_eq!([255, 0, 0, 255], canvas.pixel(2, 10));
+        assert_eq!([0, 0, 0, 0], canvas.pixel(2, 2));
+        assert_eq!([0, 0, 0, 0], canvas.pixel(17, 17));
+    }
+
+    #[test]
+    fn line_width() {
+        let mut canvas = RasterCanvas::new(20, 20);
+        canvas.set_pen(Color::white(), 1.0);
+        canvas.set_line_width(4.0);
+        canvas.draw_line(&[Point::new(0.0, 10.0), Point::new(20.0, 10.0)]);
+
+        for row in 8..12 {
+            assert_eq!([255, 255, 255, 255], canvas.pixel(10, row));
+        }
+        assert_eq!([0, 0, 0, 0], canvas.pixel(10, 7));
+        assert_eq!([0, 0, 0, 0], canvas.pixel(10, 12));
+    }
+
+    #[test]
+    fn dashed_line() {
+        let mut canvas = RasterCanvas::new(20, 20).with_antialiasing(false);
+        canvas.set_stroke(
+            &Stroke::new(Color::white(), 2.0).with_dash(&[4.0, 4.0]),
+            1.0,
+        );
+        canvas.draw_line(&[Point::new(0.0, 10.0), Point::new(20.0, 10.0)]);
+
+        for &x in &[1, 2, 9, 10, 17, 18] {
+            assert_eq!([255, 255, 255, 255], canvas.pixel(x, 10), "{}", x);
+        }
+        for &x in &[5, 6, 13, 14] {
+            assert_eq!([0, 0, 0, 0], canvas.pixel(x, 10), "{}", x);
+        }
+
+        // The next pen draws solid lines again:
+        canvas.set_pen(Color::white(), 1.0);
+        canvas.draw_line(&[Point::new(0.0, 15.5), Point::new(20.0, 15.5)]);
+        assert_eq!([255, 255, 255, 255], canvas.pixel(6, 15));
+    }
+
+    #[test]
+    fn thick_line_span() {
+        let mut canvas = RasterCanvas::new(20, 20);
+        canvas.set_pen(Color::black(), 1.0);
+        canvas.set_line_width(5.0);
+        canvas.draw_line(&[Point::new(2.0, 10.5), Point::new(18.0, 10.5)]);
+
+        let covered: Vec<usize> = (0..20).filter(|y| canvas.pixel(10, *y)[3] > 0).collect();
+        assert_eq!(vec![8, 9, 10, 11, 12], covered);
+        assert_eq!(0, canvas.pixel(1, 10)[3]);
+        assert_eq!(0, canvas.pixel(18, 10)[3]);
+    }
+
+    #[test]
+    fn thin_line_is_visible() {
+        let mut canvas = RasterCanvas::new(20, 20);
+        canvas.set_pen(Color::black(), 1.0);
+        canvas.set_line_width(0.2);
+        canvas.draw_line(&[Point::new(0.0, 10.3), Point::new(20.0, 10.3)]);
+
+        let alpha = canvas.pixel(10, 10)[3];
+        assert!(0 < alpha && alpha < 255);
+    }
+
+    #[test]
+    fn antialiasing() {
+        let diagonal = [Point::new(0.0, 0.0), Point::new(20.0, 20.0)];
+        let alphas = |canvas: &RasterCanvas| -> Vec<u8> {
+            (0..20)
+                .flat_map(|y| (0..20).map(move |x| (x, y)))
+                .map(|(x, y)| canvas.pixel(x, y)[3])
+                .collect()
+        };
+
+        let mut canvas = RasterCanvas::new(20, 20);
+        canvas.set_pen(Color::black(), 1.0);
+        canvas.draw_line(&diagonal);
+        let smooth = alphas(&canvas);
+        assert!(smooth.iter().any(|a| 0 < *a && *a < 255));
+
+        let mut canvas = RasterCanvas::new(20, 20).with_antialiasing(false);
+        canvas.set_pen(Color::black(), 1.0);
+        canvas.draw_line(&diagonal);
+        let crisp = alphas(&canvas);
+        assert!(crisp.iter().all(|a| *a == 0 || *a == 255));
+        assert!(crisp.contains(&255));
+    }
+
+    #[test]
+    fn alpha_blending() {
+        let mut canvas = RasterCanvas::new(20, 20);
+        canvas.set_pen(Color::new(0, 0, 200), 1.0);
+        canvas.fill_rect(0.0, 0.0, 20.0, 20.0);
+        canvas.set_pen(Color::red(), 0.5);
+        canvas.fill_rect(5.0, 5.0, 10.0, 10.0);
+
+        let expected = Color::red().blend_over(0.5, &Color::new(0, 0, 200));
+        assert_eq!([128, 0, 100, 255], canvas.pixel(10, 10));
+        assert_eq!(
+            [expected.r(), expected.g(), expected.b(), 255],
+            canvas.pixel(10, 10)
+        );
+        assert_eq!([0, 0, 200, 255], canvas.pixel(2, 2));
+    }
+
+    #[test]
+    fn scaled_image() {
+        // A 2x1 image, with an opaque red and a half transparent green pixel:
+        let rgba = [255, 0, 0, 255, 0, 255, 0, 128];
+        let mut canvas = RasterCanvas::new(20, 20);
+        canvas.set_clip_rect(Rect::new(0.0, 0.0, 20.0, 8.0));
+        canvas.draw_image(Point::new(2.0, 2.0), 8.0, 10.0, &rgba, 2, 1);
+
+        assert_eq!([255, 0, 0, 255], canvas.pixel(2, 2));
+        assert_eq!([255, 0, 0, 255], canvas.pixel(5, 7));
+        assert_eq!([0, 255, 0, 128], canvas.pixel(6, 2));
+        assert_eq!([0, 255, 0, 128], canvas.pixel(9, 7));
+        assert_eq!([0, 0, 0, 0], canvas.pixel(10, 5));
+        assert_eq!([0, 0, 0, 0], canvas.pixel(1, 5));
+
+        // Clipped:
+        assert_eq!([0, 0, 0, 0], canvas.pixel(5, 8));
+
+        // Data of the wrong size, also when the size overflows, is not drawn:
+        let mut canvas = RasterCanvas::new(20, 20);
+        canvas.draw_image(Point::new(0.0, 0.0), 8.0, 8.0, &rgba, 1, 1);
+        canvas.draw_image(Point::new(0.0, 0.0), 8.0, 8.0, &rgba, u32::MAX, 2);
+        assert_eq!([0, 0, 0, 0], canvas.pixel(2, 2));
+    }
+
+    #[test]
+    fn clipping_and_alpha() {
+        let mut canvas = RasterCanvas::new(20, 20);
+        canvas.set_clip_rect(Rect::new(0.0, 0.0, 10.0, 20.0));
+        canvas.set_pen(Color::green(), 0.5);
+        canvas.fill_rect(0.0, 0.0, 20.0, 20.0);
+        canvas.clear_clip();
+
+        assert_eq!([0, 255, 0, 128], canvas.pixel(9, 5));
+        assert_eq!([0, 0, 0, 0], canvas.pixel(10, 5));
+
+        let (width, height, data) = canvas.into_rgba();
+        assert_eq!((20, 20), (width, height));
+        assert_eq!(20 * 20 * 4, data.len());
+    }
+
+    #[test]
+    fn dirty_rects() {
+        let mut canvas = RasterCanvas::new(100, 100);
+        assert!(canvas.take_dirty_rects().is_empty());
+
+        canvas.set_pen(Color::black(), 1.0);
+        canvas.set_line_width(2.0);
+        canvas.draw_line(&[Point::new(80.0, 10.0), Point::new(90.0, 20.0)]);
+        let dirty = canvas.take_dirty_rects();
+        assert_eq!(1, dirty.len());
+        let rect = &dirty[0];
+        assert!(rect.left() >= 78.0 && rect.right() <= 92.0, "{:?}", rect);
+        assert!(rect.top() >= 8.0 && rect.bottom() <= 22.0, "{:?}", rect);
+        assert_eq!(rect.left(), rect.left().floor());
+
+        // Taking the rects resets them:
+        assert!(canvas.take_dirty_rects().is_empty());
+
+        // Overlapping regions are merged, and clipping limits the region:
+        canvas.fill_rect(0.0, 0.0, 10.0, 10.0);
+        canvas.fill_rect(5.0, 5.0, 10.0, 10.0);
+        canvas.set_clip_rect(Rect::new(50.0, 50.0, 10.0, 10.0));
+        canvas.fill_rect(40.0, 40.0, 100.0, 100.0);
+        let dirty = canvas.take_dirty_rects();
+        assert_eq!(2, dirty.len());
+        assert!(dirty.contains(&Rect::new(0.0, 0.0, 15.0, 15.0)));
+        assert!(dirty.contains(&Rect::new(50.0, 50.0, 10.0, 10.0)));
+    }
+
+    #[test]
+    fn redraw_region() {
+        let mut canvas = RasterCanvas::new(20, 20);
+        canvas.set_pen(Color::red(), 1.0);
+        canvas.fill_rect(0.0, 0.0, 20.0, 20.0);
+        canvas.take_dirty_rects();
+
+        // The region is cleared, and only pixels inside it are drawn:
+        canvas.set_redraw_region(Some(Rect::new(10.0, 0.0, 10.0, 20.0)));
+        assert_eq!([0, 0, 0, 0], canvas.pixel(15, 5));
+        canvas.set_pen(Color::green(), 1.0);
+        canvas.fill_rect(5.0, 5.0, 10.0, 10.0);
+        canvas.set_redraw_region(None);
+
+        assert_eq!([255, 0, 0, 255], canvas.pixel(7, 7));
+        assert_eq!([0, 255, 0, 255], canvas.pixel(12, 7));
+        assert_eq!([0, 0, 0, 0], canvas.pixel(17, 7));
+        assert_eq!(
+            vec![Rect::new(10.0, 0.0, 10.0, 20.0)],
+            canvas.take_dirty_rects()
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! A canvas which records the drawing operations, for use in tests.
+
+use super::canvas::{HorizontalAnchor, TextOrientation, VerticalAnchor};
+use super::Canvas;
+use crate::geometry::{Path, Point, Rect, Size};
+use crate::style::{Color, Gradient};
+
+/// A single call made on a `RecordingCanvas`.
+///
+/// The provided methods of `Canvas` are recorded as the calls they are
+/// built on, so `fill_rect` shows up as `FillPolygon`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawOp {
+    SetPen(Color, f64),
+    SetLineWidth(f64),
+    SetDash(Vec<f64>),
+    SetFontSize(f64),
+    SetTitle(Option<String>),
+    SetGradient(Gradient),
+    Text {
+        position: Point,
+        horizontal_anchor: HorizontalAnchor,
+        vertical_anchor: VerticalAnchor,
+        orientation: TextOrientation,
+        text: String,
+    },
+    Polyline(Vec<Point>),
+    Polygon(Vec<Point>),
+    Circle(Point, f64),
+    Path(Path),
+    FillPolygon(Vec<Point>),
+    FillCircle(Point, f64),
+
+    /// An image, drawn at the given rectangle, with its size in pixels.
+    Image(Rect, u32, u32),
+    SetClip(Rect),
+    ClearClip,
+    Save,
+    Restore,
+}
+
+/// Record drawing operations instead of drawing them.
+///
+/// This allows checking what render functions draw, without depending on
+/// the formatting of an output format. Text is measured like the SVG output
+/// does, as 8 by 16 pixels per character.
+#[derive(Debug, Default)]
+pub struct RecordingCanvas {
+    ops: Vec<DrawOp>,
+}
+
+impl RecordingCanvas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The operations recorded so far, in order.
+    pub fn ops(&self) -> &[DrawOp] {
+        &self.ops
+    }
+
+    /// Take the recorded operations, leaving the canvas empty.
+    pub fn take_ops(&mut self) -> Vec<DrawOp> {
+        std::mem::take(&mut self.ops)
+    }
+
+    /// The points of all polylines drawn with `draw_line`.
+    pub fn polylines(&self) -> Vec<&[Point]> {
+        self.ops
+            .iter()
+            .filter_map(|op| match op {
+                DrawOp::Polyline(points) => Some(points.as_slice()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl Canvas for RecordingCanvas {
+    fn set_pen(&mut self, color: Color, alpha: f64) {
+        self.ops.push(DrawOp::SetPen(color, alpha));
+    }
+
+    fn set_line_width(&mut self, width: f64) {
+        self.ops.push(DrawOp::SetLineWidth(width));
+    }
+
+    fn set_dash(&mut self, dash: &[f64]) {
+        self.ops.push(DrawOp::SetDash(dash.to_vec()));
+    }
+
+    fn set_font_size(&mut self, size: f64) {
+        self.ops.push(DrawOp::SetFontSize(size));
+    }
+
+    fn set_title(&mut self, title: Option<&str>) {
+        self.ops
+            .push(DrawOp::SetTitle(title.map(|title| title.to_owned())));
+    }
+
+    fn print_text(
+        &mut self,
+        p: &Point,
+        horizontal_anchor: HorizontalAnchor,
+        vertical_anchor: VerticalAnchor,
+        text: &str,
+    ) {
+        self.print_text_oriented(
+            p,
+            horizontal_anchor,
+            vertical_anchor,
+            TextOrientation::Horizontal,
+            text,
+        );
+    }
+
+    fn print_text_oriented(
+        &mut self,
+        p: &Point,
+        horizontal_anchor: HorizontalAnchor,
+        vertical_anchor: VerticalAnchor,
+        orientation: TextOrientation,
+        text: &str,
+    ) {
+        self.ops.push(DrawOp::Text {
+            position: *p,
+            horizontal_anchor,
+            vertical_anchor,
+            orientation,
+            text: text.to_owned(),
+        });
+    }
+
+    fn text_size(&self, text: &str) -> Size {
+        Size::new((text.len() * 8) as f64, 16.0)
+    }
+
+    fn draw_line(&mut self, points: &[Point]) {
+        self.ops.push(DrawOp::Polyline(points.to_vec()));
+    }
+
+    fn draw_polygon(&mut self, points: &[Point]) {
+        self.ops.push(DrawOp::Polygon(points.to_vec()));
+    }
+
+    fn draw_circle(&mut self, center: &Point, radius: f64) {
+        self.ops.push(DrawOp::Circle(*center, radius));
+    }
+
+    fn draw_path(&mut self, path: &Path) {
+        self.ops.push(DrawOp::Path(path.clone()));
+    }
+
+    fn fill_polygon(&mut self, points: &[Point]) {
+        self.ops.push(DrawOp::FillPolygon(points.to_vec()));
+    }
+
+    fn fill_circle(&mut self, center: &Point, radius: f64) {
+        self.ops.push(DrawOp::FillCircle(*center, radius));
+    }
+
+    fn draw_image(
+        &mut self,
+        top_left: Point,
+        width: f64,
+        height: f64,
+        _rgba: &[u8],
+        img_w: u32,
+        img_h: u32,
+    ) {
+        let rect = Rect::new(top_left.x(), top_left.y(), width, height);
+        self.ops.push(DrawOp::Image(rect, img_w, img_h));
+    }
+
+    fn set_gradient(&mut self, gradient: &Gradient) {
+        self.ops.push(DrawOp::SetGradient(gradient.clone()));
+    }
+
+    fn set_clip_rect(&mut self, rect: Rect) {
+        self.ops.push(DrawOp::SetClip(rect));
+    }
+
+    fn clear_clip(&mut self) {
+        self.ops.push(DrawOp::ClearClip);
+    }
+
+    fn save(&mut self) {
+        self.ops.push(DrawOp::Save);
+    }
+
+    fn restore(&mut self) {
+        self.ops.push(DrawOp::Restore);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DrawOp, RecordingCanvas};
+    use crate::geometry::{Range, Size};
+    use crate::render::{draw_series, Canvas, ChartLayout, ChartOptions, Series, Transform};
+    use crate::style::Color;
+
+    #[test]
+    fn series_is_one_polyline() {
+        let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+        layout.layout(&ChartOptions::default());
+        let transform = Transform::from_ranges(
+            &Range::new(0.0, 10.0),
+            &Range::new(0.0, 10.0),
+            &layout.plot_rect(),
+        );
+        let points = (0..=10)
+            .map(|i| (i as f64, (i * i) as f64 / 10.0))
+            .collect();
+        let series = Series::new("squares", points, Color::red());
+
+        let mut canvas = RecordingCanvas::new();
+        draw_series(&mut canvas, &layout, &transform, &series);
+
+        let lines = canvas.polylines();
+        assert_eq!(1, lines.len());
+        assert_eq!(11, lines[0].len());
+        assert_eq!(Some(&DrawOp::Save), canvas.ops().first());
+        assert_eq!(Some(&DrawOp::Restore), canvas.ops().last());
+        assert!(canvas
+            .ops()
+            .contains(&DrawOp::SetPen(Color::red(), series.stroke.opacity)));
+    }
+
+    #[test]
+    fn provided_methods() {
+        let mut canvas = RecordingCanvas::new();
+        canvas.fill_rect(1.0, 2.0, 3.0, 4.0);
+        canvas.draw_rect(1.0, 2.0, 3.0, 4.0);
+
+        let ops = canvas.take_ops();
+        assert!(matches!(ops[0], DrawOp::FillPolygon(ref p) if p.len() == 4));
+        assert!(matches!(ops[1], DrawOp::Polygon(ref p) if p.len() == 4));
+        assert!(canvas.ops().is_empty());
+    }
+}
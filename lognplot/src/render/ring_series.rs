@@ -0,0 +1,127 @@
+//! Bounded storage of the most recent points of a live signal.
+
+use std::collections::VecDeque;
+
+/// Which points a ring series keeps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Limit {
+    /// Keep at most this many points.
+    Count(usize),
+
+    /// Keep points no older than this, relative to the newest point.
+    Age(f64),
+}
+
+/// A series which drops old points as new points arrive.
+///
+/// This keeps memory bounded during long captures.
+#[derive(Debug, Clone)]
+pub struct RingSeries {
+    points: VecDeque<(f64, f64)>,
+    limit: Limit,
+}
+
+impl RingSeries {
+    /// Keep the most recent `capacity` points.
+    pub fn with_capacity(capacity: usize) -> Self {
+        RingSeries {
+            points: VecDeque::with_capacity(capacity),
+            limit: Limit::Count(capacity),
+        }
+    }
+
+    /// Keep the points with an x value within `window` of the newest point.
+    pub fn with_time_window(window: f64) -> Self {
+        RingSeries {
+            points: VecDeque::new(),
+            limit: Limit::Age(window),
+        }
+    }
+
+    /// Append a point, and drop the points which fall outside the limit.
+    ///
+    /// Points are expected to arrive in order of increasing x.
+    pub fn push(&mut self, x: f64, y: f64) {
+        match self.limit {
+            Limit::Count(capacity) => {
+                if capacity == 0 {
+                    return;
+                }
+                if self.points.len() == capacity {
+                    self.points.pop_front();
+                }
+                self.points.push_back((x, y));
+            }
+            Limit::Age(window) => {
+                self.points.push_back((x, y));
+                while let Some(&(oldest, _)) = self.points.front() {
+                    if oldest < x - window {
+                        self.points.pop_front();
+                    } else {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// Iterate over the current points, from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = &(f64, f64)> {
+        self.points.iter()
+    }
+
+    /// The current points as a single slice, for example to draw them.
+    pub fn as_slice(&mut self) -> &[(f64, f64)] {
+        self.points.make_contiguous()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RingSeries;
+
+    #[test]
+    fn evict_oldest() {
+        let mut series = RingSeries::with_capacity(3);
+        for i in 0..5 {
+            series.push(i as f64, i as f64 * 10.0);
+        }
+
+        assert_eq!(3, series.len());
+        assert_eq!(&[(2.0, 20.0), (3.0, 30.0), (4.0, 40.0)], series.as_slice());
+
+        series.push(5.0, 50.0);
+        let points: Vec<(f64, f64)> = series.iter().cloned().collect();
+        assert_eq!(vec![(3.0, 30.0), (4.0, 40.0), (5.0, 50.0)], points);
+
+        let mut empty = RingSeries::with_capacity(0);
+        empty.push(1.0, 1.0);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn evict_by_age() {
+        let mut series = RingSeries::with_time_window(1.0);
+        for &x in &[0.0, 0.3, 0.6, 0.9, 1.2, 1.5] {
+            series.push(x, 1.0);
+        }
+        let xs: Vec<f64> = series.iter().map(|p| p.0).collect();
+        assert_eq!(vec![0.6, 0.9, 1.2, 1.5], xs);
+
+        // A gap drops everything but the new point:
+        series.push(10.0, 2.0);
+        assert_eq!(&[(10.0, 2.0)], series.as_slice());
+    }
+}
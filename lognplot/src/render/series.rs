@@ -0,0 +1,429 @@
+//! A named trace of points, together with its styling.
+
+use super::{Canvas, ChartLayout, Transform};
+use crate::geometry::Point;
+use crate::style::{Color, Stroke};
+
+/// The y axis against which a series is plotted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AxisSide {
+    Left,
+    Right,
+}
+
+/// How the line between two points of a series is drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum InterpolationMode {
+    /// A straight line from point to point.
+    #[default]
+    Linear,
+
+    /// Jump to the value of the next point right away, and hold it until that point.
+    StepBefore,
+
+    /// Hold the value of a point until the next point, and jump there.
+    StepAfter,
+}
+
+impl InterpolationMode {
+    /// Insert the corner points of the steps between the given points.
+    ///
+    /// Linear interpolation returns the points unchanged.
+    pub fn apply(self, points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        let mut line = Vec::with_capacity(points.len() * 2);
+        for (index, &point) in points.iter().enumerate() {
+            if index > 0 {
+                let previous = points[index - 1];
+                match self {
+                    InterpolationMode::Linear => {}
+                    InterpolationMode::StepBefore => line.push((previous.0, point.1)),
+                    InterpolationMode::StepAfter => line.push((point.0, previous.1)),
+                }
+            }
+            line.push(point);
+        }
+        line
+    }
+}
+
+/// A trace of points with its name and styling.
+#[derive(Debug, Clone)]
+pub struct Series {
+    /// Name of the series, as shown in the legend.
+    pub name: String,
+
+    /// The data points, as x and y values.
+    pub points: Vec<(f64, f64)>,
+
+    /// Color of the series, used for its line and legend entry.
+    pub color: Color,
+
+    /// Line style. Only the width, opacity and dashes are used, the color is taken from `color`.
+    pub stroke: Stroke,
+
+    /// Invisible series are not drawn.
+    pub visible: bool,
+
+    /// Dimmed series are drawn in gray, for example when toggled off.
+    pub dimmed: bool,
+
+    /// The y axis this series belongs to.
+    pub axis: AxisSide,
+
+    /// How the points are connected.
+    pub interpolation: InterpolationMode,
+}
+
+impl Series {
+    /// Create a visible series on the left axis, drawn with a 1 pixel wide straight line.
+    pub fn new(name: &str, points: Vec<(f64, f64)>, color: Color) -> Self {
+        Series {
+            name: name.to_owned(),
+            points,
+            stroke: Stroke::new(color.clone(), 1.0),
+            color,
+            visible: true,
+            dimmed: false,
+            axis: AxisSide::Left,
+            interpolation: InterpolationMode::default(),
+        }
+    }
+
+    /// The name and color of this series, as used by `draw_legend`.
+    pub fn legend_entry(&self) -> (String, Color) {
+        (self.name.clone(), self.color.clone())
+    }
+
+    /// The color to draw the series with, which is gray when it is dimmed.
+    pub fn draw_color(&self) -> Color {
+        if self.dimmed {
+            self.color.to_grayscale()
+        } else {
+            self.color.clone()
+        }
+    }
+}
+
+/// Draw a series as a line through its points, clipped to the plot area.
+///
+/// Nothing is drawn for an invisible series, and a dimmed series is drawn
+/// in gray. The line gets the name of the series as title. Non-finite
+/// values split the line into separate parts, and the points are connected
+/// according to the interpolation mode of the series.
+pub fn draw_series<C>(canvas: &mut C, layout: &ChartLayout, transform: &Transform, series: &Series)
+where
+    C: Canvas,
+{
+    if !series.visible {
+        return;
+    }
+
+    draw_points(canvas, layout, transform, series, &series.points);
+}
+
+/// Draw only the points of a series from the given index onward, for live traces.
+///
+/// The line starts at the point before `from_index`, so it connects to the
+/// line drawn before. Returns the index to continue from next time, which
+/// is the amount of points when the series is visible. Nothing is drawn
+/// when there are no new points.
+pub fn draw_series_incremental<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    series: &Series,
+    from_index: usize,
+) -> usize
+where
+    C: Canvas,
+{
+    let length = series.points.len();
+    if !series.visible {
+        return from_index.min(length);
+    }
+    if from_index >= length {
+        return length;
+    }
+
+    let start = from_index.saturating_sub(1);
+    draw_points(canvas, layout, transform, series, &series.points[start..]);
+    length
+}
+
+/// Draw a line through points in the style of a series, see `draw_series`.
+fn draw_points<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    series: &Series,
+    points: &[(f64, f64)],
+) where
+    C: Canvas,
+{
+    canvas.save();
+    canvas.set_clip_rect(layout.plot_rect());
+    canvas.set_title(Some(&series.name));
+    canvas.set_pen(series.draw_color(), series.stroke.opacity);
+    canvas.set_line_width(series.stroke.width);
+    canvas.set_dash(series.stroke.dash_pattern());
+
+    let runs = points.split(|(x, y)| !x.is_finite() || !y.is_finite());
+    for run in runs {
+        let points: Vec<Point> = series
+            .interpolation
+            .apply(run)
+            .into_iter()
+            .map(|(x, y)| transform.apply_point(Point::new(x, y)))
+            .collect();
+        canvas.draw_line(&points);
+    }
+
+    canvas.restore();
+}
+
+/// Find the point of a series which is closest to a pixel on screen.
+///
+/// Returns the index of the point, and the point in data coordinates.
+/// Distances are measured on screen, and points with non-finite values
+/// are skipped. All points are transformed, use `nearest_point_sorted`
+/// for long series which are sorted by x.
+pub fn nearest_point(
+    series: &Series,
+    transform: &Transform,
+    pixel: Point,
+) -> Option<(usize, Point)> {
+    find_nearest_point(series, transform, pixel, false)
+}
+
+/// Find the point closest to a pixel, like `nearest_point`, in a series sorted by x.
+///
+/// A binary search is used to start near the pixel, so only points close
+/// by are transformed. The order is not checked, an unsorted series can give
+/// a point which is not the nearest.
+pub fn nearest_point_sorted(
+    series: &Series,
+    transform: &Transform,
+    pixel: Point,
+) -> Option<(usize, Point)> {
+    find_nearest_point(series, transform, pixel, true)
+}
+
+fn find_nearest_point(
+    series: &Series,
+    transform: &Transform,
+    pixel: Point,
+    sorted: bool,
+) -> Option<(usize, Point)> {
+    let points = &series.points;
+    let screen = |index: usize| {
+        let (x, y) = points[index];
+        transform.apply_point(Point::new(x, y))
+    };
+
+    let mut best: Option<(usize, f64)> = None;
+    let consider = |index: usize, best: &mut Option<(usize, f64)>| {
+        let distance = screen(index).distance_to(&pixel);
+        let is_closer = match best {
+            Some((_, best_distance)) => distance < *best_distance,
+            None => true,
+        };
+        if distance.is_finite() && is_closer {
+            *best = Some((index, distance));
+        }
+    };
+
+    let sorted = sorted && transform.is_axis_aligned();
+    match transform.invert() {
+        Some(inverse) if sorted => {
+            // Walk outwards from the pixel, until points are further away
+            // in x direction alone than the nearest point so far.
+            let x = inverse.apply_point(pixel).x();
+            let start = points.partition_point(|p| p.0 < x);
+            let is_far = |index: usize, best: &Option<(usize, f64)>| match best {
+                Some((_, distance)) => (screen(index).x() - pixel.x()).abs() > *distance,
+                None => false,
+            };
+
+            for index in start..points.len() {
+                if is_far(index, &best) {
+                    break;
+                }
+                consider(index, &mut best);
+            }
+            for index in (0..start).rev() {
+                if is_far(index, &best) {
+                    break;
+                }
+                consider(index, &mut best);
+            }
+        }
+        _ => {
+            for index in 0..points.len() {
+                consider(index, &mut best);
+            }
+        }
+    }
+
+    best.map(|(index, _)| {
+        let (x, y) = points[index];
+        (index, Point::new(x, y))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        draw_series, draw_series_incremental, nearest_point, nearest_point_sorted,
+        InterpolationMode, Series,
+    };
+    use crate::geometry::{Point, Range, Rect};
+    use crate::render::test_util::{layout_and_transform, render_svg};
+    use crate::render::{RecordingCanvas, Transform};
+    use crate::style::Color;
+
+    fn render(series: &Series) -> String {
+        let (layout, transform) = layout_and_transform((0.0, 10.0), (0.0, 1.0));
+        render_svg(|canvas| draw_series(canvas, &layout, &transform, series))
+    }
+
+    #[test]
+    fn visible_series() {
+        let series = Series::new(
+            "voltage",
+            vec![(0.0, 0.0), (5.0, 1.0), (10.0, 0.5)],
+            Color::red(),
+        );
+        let svg = render(&series);
+
+        let line = svg
+            .lines()
+            .find(|l| l.contains("<path d=\"M "))
+            .expect("a line");
+        assert!(line.contains("stroke:rgb(255,0,0)"), "{}", line);
+        assert_eq!(2, line.matches(" L ").count());
+        assert!(
+            line.starts_with("   <g><title>voltage</title><path d=\"M "),
+            "{}",
+            line
+        );
+    }
+
+    #[test]
+    fn incremental() {
+        let (layout, transform) = layout_and_transform((0.0, 10.0), (0.0, 1.0));
+        let mut series = Series::new(
+            "live",
+            vec![(0.0, 0.0), (1.0, 0.5), (2.0, 0.2)],
+            Color::red(),
+        );
+
+        let mut canvas = RecordingCanvas::new();
+        let drawn = draw_series_incremental(&mut canvas, &layout, &transform, &series, 0);
+        assert_eq!(3, drawn);
+        assert_eq!(3, canvas.polylines()[0].len());
+
+        // One new point results in one segment, from the last drawn point:
+        series.points.push((3.0, 1.0));
+        let mut canvas = RecordingCanvas::new();
+        let drawn = draw_series_incremental(&mut canvas, &layout, &transform, &series, drawn);
+        assert_eq!(4, drawn);
+        let lines = canvas.polylines();
+        assert_eq!(1, lines.len());
+        let expected = [
+            transform.apply_point(Point::new(2.0, 0.2)),
+            transform.apply_point(Point::new(3.0, 1.0)),
+        ];
+        assert_eq!(&expected, lines[0]);
+
+        // Nothing new:
+        let mut canvas = RecordingCanvas::new();
+        assert_eq!(
+            4,
+            draw_series_incremental(&mut canvas, &layout, &transform, &series, drawn)
+        );
+        assert!(canvas.ops().is_empty());
+    }
+
+    #[test]
+    fn dimmed_series() {
+        let mut series = Series::new("voltage", vec![(0.0, 0.0), (5.0, 1.0)], Color::red());
+        series.dimmed = true;
+        let svg = render(&series);
+
+        assert!(svg.contains("stroke:rgb(127,127,127)"), "{}", svg);
+        assert_eq!(Color::red(), series.legend_entry().1);
+    }
+
+    #[test]
+    fn steps() {
+        let points = [(0.0, 0.0), (1.0, 1.0)];
+        assert_eq!(
+            vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)],
+            InterpolationMode::StepAfter.apply(&points)
+        );
+        assert_eq!(
+            vec![(0.0, 0.0), (0.0, 1.0), (1.0, 1.0)],
+            InterpolationMode::StepBefore.apply(&points)
+        );
+        assert_eq!(points.to_vec(), InterpolationMode::Linear.apply(&points));
+        assert!(InterpolationMode::StepAfter.apply(&[]).is_empty());
+
+        // Horizontal from x 0 to x 10, then up to y 1:
+        let mut series = Series::new("gpio", vec![(0.0, 0.0), (10.0, 1.0)], Color::red());
+        series.interpolation = InterpolationMode::StepAfter;
+        let svg = render(&series);
+        assert!(svg.contains("M 140 510 L 790 510 L 790 10"), "{}", svg);
+    }
+
+    #[test]
+    fn click_between_points() {
+        let points: Vec<(f64, f64)> = (0..1000).map(|i| (i as f64, (i % 7) as f64)).collect();
+        let series = Series::new("sawtooth", points, Color::red());
+        let transform = Transform::from_ranges(
+            &Range::new(0.0, 1000.0),
+            &Range::new(0.0, 10.0),
+            &Rect::new(0.0, 0.0, 1000.0, 100.0),
+        );
+
+        // Halfway point 3 (at y 3) and point 4 (at y 4), just above point 4:
+        let pixel = Point::new(3.6, 59.0);
+        let (index, point) = nearest_point_sorted(&series, &transform, pixel).unwrap();
+        assert_eq!(4, index);
+        assert_eq!(Point::new(4.0, 4.0), point);
+        assert_eq!(
+            Some((index, point)),
+            nearest_point(&series, &transform, pixel)
+        );
+
+        // Left of all points:
+        let pixel = Point::new(-50.0, 100.0);
+        assert_eq!(
+            0,
+            nearest_point_sorted(&series, &transform, pixel).unwrap().0
+        );
+
+        // Searching an unsorted series gives the same point:
+        let mut shuffled = series.clone();
+        shuffled.points.reverse();
+        let (index, point) = nearest_point(&shuffled, &transform, Point::new(3.6, 59.0)).unwrap();
+        assert_eq!(995, index);
+        assert_eq!(Point::new(4.0, 4.0), point);
+    }
+
+    #[test]
+    fn nearest_in_empty_series() {
+        let series = Series::new("empty", vec![(1.0, f64::NAN)], Color::red());
+        let transform = Transform::identity();
+        assert!(nearest_point(&series, &transform, Point::new(1.0, 1.0)).is_none());
+    }
+
+    #[test]
+    fn invisible_series() {
+        let mut series = Series::new("voltage", vec![(0.0, 0.0), (5.0, 1.0)], Color::red());
+        series.visible = false;
+        let svg = render(&series);
+
+        assert!(!svg.contains("<path"));
+        assert!(!svg.contains("stroke:rgb(255,0,0)"));
+    }
+}
@@ -0,0 +1,614 @@
+//! A minimal software rasterizer.
+//!
+//! All shapes are flattened into polygons, which are filled
+//! with a scanline algorithm using the non-zero winding rule.
+//! Anti-aliasing is done by measuring how much of each pixel is covered
+//! on a few sub scanlines.
+
+use crate::geometry::{Path, PathElement, Point, Rect};
+use crate::style::{Color, Gradient, GradientKind};
+
+/// Amount of line segments used to flatten a bezier curve.
+const CURVE_STEPS: usize = 16;
+
+/// Shortest dash or gap, in pixels, so tiny dash patterns stay cheap to draw.
+const MIN_DASH_LENGTH: f64 = 0.5;
+
+/// Most dashes drawn on a single polyline, the rest of the polyline is drawn solid.
+const MAX_DASHES: usize = 10_000;
+
+/// A buffer of RGBA pixels, 8 bits per channel, not premultiplied.
+pub struct Pixmap {
+    width: usize,
+    height: usize,
+    data: Vec<u8>,
+}
+
+impl Pixmap {
+    /// Create a new, fully transparent, pixmap.
+    pub fn new(width: usize, height: usize) -> Self {
+        Pixmap {
+            width,
+            height,
+            data: vec![0; width * height * 4],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[cfg(feature = "png")]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Get the RGBA value of a single pixel.
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let index = (y * self.width + x) * 4;
+        [
+            self.data[index],
+            self.data[index + 1],
+            self.data[index + 2],
+            self.data[index + 3],
+        ]
+    }
+
+    /// Make the pixels with their center inside the rectangle fully transparent.
+    pub fn clear(&mut self, rect: &Rect) {
+        if let Some((columns, rows)) = self.pixel_span(rect) {
+            for y in rows {
+                let start = (y * self.width + columns.start) * 4;
+                let end = (y * self.width + columns.end) * 4;
+                for value in &mut self.data[start..end] {
+                    *value = 0;
+                }
+            }
+        }
+    }
+
+    /// The columns and rows of the pixels with their center inside the rectangle.
+    fn pixel_span(&self, rect: &Rect) -> Option<(std::ops::Range<usize>, std::ops::Range<usize>)> {
+        let first_x = (rect.left() - 0.5).ceil().max(0.0) as usize;
+        let last_x = ((rect.right() - 0.5).floor() as isize).min(self.width as isize - 1);
+        let first_y = (rect.top() - 0.5).ceil().max(0.0) as usize;
+        let last_y = ((rect.bottom() - 0.5).floor() as isize).min(self.height as isize - 1);
+        if last_x < first_x as isize || last_y < first_y as isize {
+            return None;
+        }
+        Some((first_x..last_x as usize + 1, first_y..last_y as usize + 1))
+    }
+
+    /// Blend a color with rgb values between 0 and 1 over a pixel.
+    ///
+    /// This is source-over compositing, so it matches `Color::blend_over`
+    /// for opaque pixels.
+    fn blend(&mut self, x: usize, y: usize, color: [f64; 4]) {
+        let index = (y * self.width + x) * 4;
+        let source_alpha = color[3].clamp(0.0, 1.0);
+        let dest_alpha = self.data[index + 3] as f64 / 255.0;
+        let alpha = source_alpha + dest_alpha * (1.0 - source_alpha);
+        if alpha <= 0.0 {
+            return;
+        }
+
+        for (channel, source) in color.iter().take(3).enumerate() {
+            let source = source.clamp(0.0, 1.0);
+            let dest = self.data[index + channel] as f64 / 255.0;
+            let value = (source * source_alpha + dest * dest_alpha * (1.0 - source_alpha)) / alpha;
+            self.data[index + channel] = (value * 255.0).round() as u8;
+        }
+        self.data[index + 3] = (alpha * 255.0).round() as u8;
+    }
+}
+
+/// Scale an RGBA image into a rectangle of the pixmap.
+///
+/// Each pixel with its center inside the rectangle takes the color of the
+/// nearest image pixel, which is blended using the image alpha.
+pub fn draw_image(
+    pixmap: &mut Pixmap,
+    rect: &Rect,
+    clip: Option<&Rect>,
+    rgba: &[u8],
+    img_w: usize,
+    img_h: usize,
+) {
+    if rect.width() <= 0.0 || rect.height() <= 0.0 || img_w == 0 || img_h == 0 {
+        return;
+    }
+
+    let mut area = rect.clone();
+    if let Some(clip) = clip {
+        match area.intersection(clip) {
+            Some(clipped) => area = clipped,
+            None => return,
+        }
+    }
+
+    let (columns, rows) = match pixmap.pixel_span(&area) {
+        Some(span) => span,
+        None => return,
+    };
+
+    for y in rows {
+        let v = (y as f64 + 0.5 - rect.top()) / rect.height();
+        let source_y = ((v * img_h as f64) as usize).min(img_h - 1);
+        for x in columns.clone() {
+            let u = (x as f64 + 0.5 - rect.left()) / rect.width();
+            let source_x = ((u * img_w as f64) as usize).min(img_w - 1);
+            let index = (source_y * img_w + source_x) * 4;
+            let color = [
+                rgba[index] as f64 / 255.0,
+                rgba[index + 1] as f64 / 255.0,
+                rgba[index + 2] as f64 / 255.0,
+                rgba[index + 3] as f64 / 255.0,
+            ];
+            pixmap.blend(x, y, color);
+        }
+    }
+}
+
+/// What to fill a shape with.
+pub enum Paint<'a> {
+    Solid(&'a Color, f64),
+    Gradient(&'a Gradient),
+}
+
+impl<'a> Paint<'a> {
+    fn color_at(&self, x: f64, y: f64) -> [f64; 4] {
+        match self {
+            Paint::Solid(color, alpha) => to_rgba(color, *alpha),
+            Paint::Gradient(gradient) => gradient_color_at(gradient, x, y),
+        }
+    }
+}
+
+fn to_rgba(color: &Color, alpha: f64) -> [f64; 4] {
+    [
+        color.r() as f64 / 255.0,
+        color.g() as f64 / 255.0,
+        color.b() as f64 / 255.0,
+        alpha,
+    ]
+}
+
+/// Evaluate a gradient at the given position.
+///
+/// Stops are expected in increasing offset order.
+fn gradient_color_at(gradient: &Gradient, x: f64, y: f64) -> [f64; 4] {
+    let t = match &gradient.kind {
+        GradientKind::Linear { start, end } => {
+            let dx = end.x() - start.x();
+            let dy = end.y() - start.y();
+            let length2 = dx * dx + dy * dy;
+            if length2 > 0.0 {
+                ((x - start.x()) * dx + (y - start.y()) * dy) / length2
+            } else {
+                0.0
+            }
+        }
+        GradientKind::Radial { center, radius } => {
+            if *radius > 0.0 {
+                (x - center.x()).hypot(y - center.y()) / radius
+            } else {
+                0.0
+            }
+        }
+    };
+    let t = t.clamp(0.0, 1.0);
+
+    let stops = &gradient.stops;
+    if stops.is_empty() {
+        return [0.0; 4];
+    }
+
+    let first = &stops[0];
+    if t <= first.offset {
+        return to_rgba(&first.color, first.alpha);
+    }
+
+    for pair in stops.windows(2) {
+        let (from, to) = (&pair[0], &pair[1]);
+        if t <= to.offset {
+            let span = to.offset - from.offset;
+            let f = if span > 0.0 {
+                (t - from.offset) / span
+            } else {
+                1.0
+            };
+            let a = to_rgba(&from.color, from.alpha);
+            let b = to_rgba(&to.color, to.alpha);
+            return [
+                a[0] + (b[0] - a[0]) * f,
+                a[1] + (b[1] - a[1]) * f,
+                a[2] + (b[2] - a[2]) * f,
+                a[3] + (b[3] - a[3]) * f,
+            ];
+        }
+    }
+
+    let last = &stops[stops.len() - 1];
+    to_rgba(&last.color, last.alpha)
+}
+
+/// Amount of sub scanlines per pixel row when anti-aliasing.
+const SUBSAMPLES: usize = 4;
+
+/// Fill a set of closed contours as a single shape, using the non-zero winding rule.
+///
+/// Only pixels with their center inside the clip rectangle are touched. With
+/// anti-aliasing, the alpha of each pixel is scaled with the part of the pixel
+/// covered by the shape, otherwise pixels are either painted or not, depending
+/// on whether their center is inside the shape.
+pub fn fill_contours(
+    pixmap: &mut Pixmap,
+    contours: &[Vec<Point>],
+    clip: Option<&Rect>,
+    paint: &Paint,
+    antialias: bool,
+) {
+    // Gather edges, remembering their direction for the winding number.
+    // Edges with a non-finite end cannot be scanned, so leave them out:
+    let mut edges: Vec<(Point, Point, i32)> = vec![];
+    let is_finite = |p: &Point| p.x().is_finite() && p.y().is_finite();
+    for contour in contours {
+        if contour.len() < 3 {
+            continue;
+        }
+        for (index, p1) in contour.iter().enumerate() {
+            let p2 = &contour[(index + 1) % contour.len()];
+            if !is_finite(p1) || !is_finite(p2) {
+                continue;
+            }
+            if p1.y() < p2.y() {
+                edges.push((*p1, *p2, 1));
+            } else if p1.y() > p2.y() {
+                edges.push((*p2, *p1, -1));
+            }
+        }
+    }
+
+    if edges.is_empty() || pixmap.width == 0 || pixmap.height == 0 {
+        return;
+    }
+
+    let (mut left, mut top, mut right, mut bottom) =
+        (0.0, 0.0, pixmap.width as f64, pixmap.height as f64);
+    if let Some(clip) = clip {
+        left = clip.left().max(left);
+        top = clip.top().max(top);
+        right = clip.right().min(right);
+        bottom = clip.bottom().min(bottom);
+    }
+
+    let min_y = edges.iter().map(|e| e.0.y()).fold(f64::INFINITY, f64::min);
+    let max_y = edges
+        .iter()
+        .map(|e| e.1.y())
+        .fold(f64::NEG_INFINITY, f64::max);
+    let first_row = min_y.max(top).floor().max(0.0) as usize;
+    let last_row = max_y.min(bottom).ceil() - 1.0;
+    if last_row < 0.0 {
+        return;
+    }
+    let last_row = (last_row as usize).min(pixmap.height - 1);
+    let first_column = (left - 0.5).ceil().max(0.0) as usize;
+    let last_column = (right - 0.5).floor();
+    if last_column < 0.0 || first_column as f64 > last_column {
+        return;
+    }
+    let last_column = (last_column as usize).min(pixmap.width - 1);
+
+    let mut coverage: Vec<f64> = vec![0.0; pixmap.width];
+    for row in first_row..=last_row {
+        let center_y = row as f64 + 0.5;
+        if center_y < top || center_y > bottom {
+            continue;
+        }
+
+        if antialias {
+            for sub_row in 0..SUBSAMPLES {
+                let sample_y = row as f64 + (sub_row as f64 + 0.5) / SUBSAMPLES as f64;
+                for (start, end) in spans(&edges, sample_y) {
+                    let start = start.max(first_column as f64);
+                    let end = end.min(last_column as f64 + 1.0);
+                    if start >= end {
+                        continue;
+                    }
+
+                    let columns = coverage
+                        .iter_mut()
+                        .enumerate()
+                        .take(end.ceil() as usize)
+                        .skip(start.floor() as usize);
+                    for (column, value) in columns {
+                        let overlap = end.min(column as f64 + 1.0) - start.max(column as f64);
+                        *value += overlap / SUBSAMPLES as f64;
+                    }
+                }
+            }
+
+            let columns = coverage
+                .iter_mut()
+                .enumerate()
+                .take(last_column + 1)
+                .skip(first_column);
+            for (column, value) in columns {
+                if *value > 0.0 {
+                    let mut color = paint.color_at(column as f64 + 0.5, center_y);
+                    color[3] *= value.min(1.0);
+                    pixmap.blend(column, row, color);
+                    *value = 0.0;
+                }
+            }
+        } else {
+            for (start, end) in spans(&edges, center_y) {
+                let start = (start - 0.5).ceil().max(first_column as f64);
+                let end = (end - 0.5).ceil().min(last_column as f64 + 1.0);
+                if start >= end {
+                    continue;
+                }
+
+                for column in start as usize..end as usize {
+                    let color = paint.color_at(column as f64 + 0.5, center_y);
+                    pixmap.blend(column, row, color);
+                }
+            }
+        }
+    }
+}
+
+/// Determine the horizontal spans inside the shape at the given height.
+fn spans(edges: &[(Point, Point, i32)], y: f64) -> Vec<(f64, f64)> {
+    let mut crossings: Vec<(f64, i32)> = vec![];
+    for (p1, p2, direction) in edges {
+        if p1.y() <= y && y < p2.y() {
+            let f = (y - p1.y()) / (p2.y() - p1.y());
+            crossings.push((p1.x() + f * (p2.x() - p1.x()), *direction));
+        }
+    }
+    crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut result = vec![];
+    let mut winding = 0;
+    for pair in crossings.windows(2) {
+        winding += pair[0].1;
+        if winding != 0 {
+            result.push((pair[0].0, pair[1].0));
+        }
+    }
+    result
+}
+
+/// Approximate a circle by a polygon.
+pub fn flatten_circle(center: &Point, radius: f64) -> Vec<Point> {
+    let radius = radius.abs();
+    let segments = (radius * std::f64::consts::PI).ceil().clamp(16.0, 1024.0) as usize;
+    (0..segments)
+        .map(|index| {
+            let angle = 2.0 * std::f64::consts::PI * index as f64 / segments as f64;
+            Point::new(
+                center.x() + radius * angle.cos(),
+                center.y() + radius * angle.sin(),
+            )
+        })
+        .collect()
+}
+
+/// Flatten a path into polylines, together with a flag telling if the polyline is closed.
+pub fn flatten_path(path: &Path) -> Vec<(Vec<Point>, bool)> {
+    let mut polylines = vec![];
+    let mut current: Vec<Point> = vec![];
+
+    for element in path.elements() {
+        match element {
+            PathElement::MoveTo(p) => {
+                if current.len() > 1 {
+                    polylines.push((current, false));
+                }
+                current = vec![*p];
+            }
+            PathElement::LineTo(p) => current.push(*p),
+            PathElement::QuadTo(c, p) => {
+                let start = *current.last().unwrap_or(c);
+                for step in 1..=CURVE_STEPS {
+                    let t = step as f64 / CURVE_STEPS as f64;
+                    let u = 1.0 - t;
+                    current.push(Point::new(
+                        u * u * start.x() + 2.0 * u * t * c.x() + t * t * p.x(),
+                        u * u * start.y() + 2.0 * u * t * c.y() + t * t * p.y(),
+                    ));
+                }
+            }
+            PathElement::CubicTo(c1, c2, p) => {
+                let start = *current.last().unwrap_or(c1);
+                for step in 1..=CURVE_STEPS {
+                    let t = step as f64 / CURVE_STEPS as f64;
+                    let u = 1.0 - t;
+                    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+                    current.push(Point::new(
+                        a * start.x() + b * c1.x() + c * c2.x() + d * p.x(),
+                        a * start.y() + b * c1.y() + c * c2.y() + d * p.y(),
+                    ));
+                }
+            }
+            PathElement::Close => {
+                if let Some(first) = current.first().cloned() {
+                    if current.len() > 1 {
+                        polylines.push((current, true));
+                    }
+                    current = vec![first];
+                }
+            }
+        }
+    }
+
+    if current.len() > 1 {
+        polylines.push((current, false));
+    }
+
+    polylines
+}
+
+/// Turn a polyline into contours which cover the stroke of the given width.
+///
+/// Each segment becomes a rectangle, and vertices get round joins.
+/// All contours have the same orientation, so filling them with the
+/// non-zero winding rule paints overlapping parts only once.
+pub fn stroke_polyline(points: &[Point], width: f64, closed: bool) -> Vec<Vec<Point>> {
+    let half_width = width / 2.0;
+    let mut contours = vec![];
+    if half_width <= 0.0 || points.len() < 2 {
+        return contours;
+    }
+
+    let segment_count = if closed {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+    for index in 0..segment_count {
+        let p1 = points[index];
+        let p2 = points[(index + 1) % points.len()];
+        let length = p1.distance_to(&p2);
+        if length <= 0.0 {
+            continue;
+        }
+
+        let direction = p2 - p1;
+        let normal = Point::new(-direction.y(), direction.x()) * (half_width / length);
+        contours.push(vec![p1 + normal, p2 + normal, p2 - normal, p1 - normal]);
+    }
+
+    let joins: Vec<&Point> = if closed {
+        points.iter().collect()
+    } else {
+        points[1..points.len() - 1].iter().collect()
+    };
+    for p in joins {
+        contours.push(flatten_circle(p, half_width));
+    }
+
+    for contour in &mut contours {
+        if signed_area(contour) < 0.0 {
+            contour.reverse();
+        }
+    }
+
+    contours
+}
+
+/// Split a polyline into dashes, following an alternating dash and gap pattern.
+///
+/// The pattern continues around the corners of the polyline, and must be
+/// valid, see `Stroke::is_dashed`. Dashes and gaps are at least half a
+/// pixel long.
+pub fn dash_polyline(points: &[Point], pattern: &[f64], closed: bool) -> Vec<Vec<Point>> {
+    let mut points = points.to_vec();
+    if closed && points.len() > 2 {
+        points.push(points[0]);
+    }
+
+    let mut dashes = vec![];
+    let mut current = points.first().map(|p| vec![*p]);
+    let mut index = 0;
+    let mut remaining = pattern[0].max(MIN_DASH_LENGTH);
+    let mut solid = false;
+    for segment in points.windows(2) {
+        let (p1, p2) = (segment[0], segment[1]);
+        let length = p1.distance_to(&p2);
+        let mut position = 0.0;
+        while !solid && length - position > remaining {
+            position += remaining;
+            let p = p1 + (p2 - p1) * (position / length);
+            match current.take() {
+                Some(mut dash) => {
+                    dash.push(p);
+                    dashes.push(dash);
+                    if dashes.len() == MAX_DASHES {
+                        solid = true;
+                        current = Some(vec![p]);
+                    }
+                }
+                None => {
+                    current = Some(vec![p]);
+                }
+            }
+            index += 1;
+            remaining = pattern[index % pattern.len()].max(MIN_DASH_LENGTH);
+        }
+        remaining -= length - position;
+        if let Some(dash) = &mut current {
+            dash.push(p2);
+        }
+    }
+
+    if let Some(dash) = current {
+        if dash.len() > 1 {
+            dashes.push(dash);
+        }
+    }
+    dashes
+}
+
+fn signed_area(points: &[Point]) -> f64 {
+    let mut area = 0.0;
+    for (index, p1) in points.iter().enumerate() {
+        let p2 = &points[(index + 1) % points.len()];
+        area += p1.x() * p2.y() - p2.x() * p1.y();
+    }
+    area / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dash_polyline, MAX_DASHES};
+    use crate::geometry::Point;
+
+    #[test]
+    fn dash_around_corner() {
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(6.0, 0.0),
+            Point::new(6.0, 6.0),
+        ];
+        let dashes = dash_polyline(&points, &[4.0, 4.0], false);
+        assert_eq!(
+            vec![
+                vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0)],
+                vec![Point::new(6.0, 2.0), Point::new(6.0, 6.0)],
+            ],
+            dashes
+        );
+    }
+
+    #[test]
+    fn tiny_dashes() {
+        let start = Point::new(0.0, 0.0);
+        let end = Point::new(100.0, 0.0);
+
+        // Tiny dashes and gaps are drawn half a pixel long:
+        let tiny = dash_polyline(&[start, end], &[1.0e-9, 1.0e-9], false);
+        assert_eq!(100, tiny.len());
+        assert_eq!(vec![Point::new(1.0, 0.0), Point::new(1.5, 0.0)], tiny[1]);
+    }
+
+    #[test]
+    fn long_line() {
+        // Very long lines end in a solid part:
+        let start = Point::new(0.0, 0.0);
+        let end = Point::new(1.0e6, 0.0);
+        let long = dash_polyline(&[start, end], &[0.5, 0.5], false);
+        assert_eq!(MAX_DASHES + 1, long.len());
+        assert_eq!(vec![Point::new(9_999.5, 0.0), end], long[MAX_DASHES]);
+    }
+}
@@ -0,0 +1,146 @@
+//! Stacked area charts, where each series is drawn on top of the previous ones.
+
+use super::{Canvas, ChartLayout, Series, Transform};
+use crate::geometry::Point;
+
+/// Draw series as filled areas stacked on top of each other.
+///
+/// The first series fills the area between zero and its values, and each
+/// next series fills the area between the sum of the previous series and
+/// the sum including itself. The series must share an x grid: points are
+/// matched by index, so resample series with different x values first.
+/// When the series differ in length, only the common points are drawn.
+///
+/// Each area is filled with the color and opacity of its series, and
+/// outlined at the top with its stroke width, in gray for a dimmed series.
+/// A non-finite value breaks
+/// the stack at that point, for the series and all series above it.
+/// Invisible series are skipped, and do not add to the stack.
+pub fn draw_stacked_area<C>(
+    canvas: &mut C,
+    layout: &ChartLayout,
+    transform: &Transform,
+    series: &[Series],
+) where
+    C: Canvas,
+{
+    let visible: Vec<&Series> = series.iter().filter(|s| s.visible).collect();
+    let length = match visible.iter().map(|s| s.points.len()).min() {
+        Some(length) => length,
+        None => return,
+    };
+
+    canvas.save();
+    canvas.set_clip_rect(layout.plot_rect());
+
+    let mut baseline = vec![0.0; length];
+    for series in visible {
+        let top: Vec<f64> = baseline
+            .iter()
+            .zip(series.points.iter())
+            .map(|(base, (x, y))| {
+                if x.is_finite() && y.is_finite() {
+                    base + y
+                } else {
+                    f64::NAN
+                }
+            })
+            .collect();
+
+        let indices: Vec<usize> = (0..length).collect();
+        let runs = indices.split(|index| !top[*index].is_finite());
+        for run in runs.filter(|run| run.len() > 1) {
+            let point =
+                |index: usize, y: f64| transform.apply_point(Point::new(series.points[index].0, y));
+            let upper: Vec<Point> = run.iter().map(|&i| point(i, top[i])).collect();
+            let mut outline = upper.clone();
+            outline.extend(run.iter().rev().map(|&i| point(i, baseline[i])));
+
+            canvas.set_pen(series.draw_color(), series.stroke.opacity);
+            canvas.fill_polygon(&outline);
+            canvas.set_line_width(series.stroke.width);
+            canvas.draw_line(&upper);
+        }
+
+        baseline = top;
+    }
+
+    canvas.restore();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::draw_stacked_area;
+    use crate::render::test_util::{layout_and_transform, render_svg};
+    use crate::render::Series;
+    use crate::style::Color;
+
+    fn render(series: &[Series]) -> String {
+        let (layout, transform) = layout_and_transform((0.0, 10.0), (0.0, 5.0));
+        render_svg(|canvas| draw_stacked_area(canvas, &layout, &transform, series))
+    }
+
+    fn areas<'s>(svg: &'s str, fill: &str) -> Vec<&'s str> {
+        svg.lines()
+            .filter(|l| l.contains("<polygon") && l.contains(fill))
+            .collect()
+    }
+
+    #[test]
+    fn second_series_on_top_of_first() {
+        let series = vec![
+            Series::new("a", vec![(0.0, 1.0), (10.0, 1.0)], Color::red()),
+            Series::new("b", vec![(0.0, 2.0), (10.0, 2.0)], Color::green()),
+        ];
+        let svg = render(&series);
+
+        // Plot area spans x 140 to 790, and y 510 (at 0) to 10 (at 5):
+        let first = areas(&svg, "fill:rgb(255,0,0)");
+        assert_eq!(1, first.len(), "{}", svg);
+        assert!(
+            first[0].contains("140,410 790,410 790,510 140,510"),
+            "{}",
+            svg
+        );
+
+        let second = areas(&svg, "fill:rgb(0,255,0)");
+        assert_eq!(1, second.len(), "{}", svg);
+        assert!(
+            second[0].contains("140,210 790,210 790,410 140,410"),
+            "{}",
+            svg
+        );
+    }
+
+    #[test]
+    fn nan_breaks_stack() {
+        let series = vec![
+            Series::new(
+                "a",
+                vec![(0.0, 1.0), (1.0, f64::NAN), (2.0, 1.0), (3.0, 1.0)],
+                Color::red(),
+            ),
+            Series::new(
+                "b",
+                vec![(0.0, 1.0), (1.0, 1.0), (2.0, 1.0), (3.0, 1.0)],
+                Color::green(),
+            ),
+        ];
+        let svg = render(&series);
+
+        // The single point before the gap has no area, the part after the
+        // gap is drawn for both series:
+        assert_eq!(1, areas(&svg, "fill:rgb(255,0,0)").len());
+        assert_eq!(1, areas(&svg, "fill:rgb(0,255,0)").len());
+        assert!(!svg.contains("NaN"));
+    }
+
+    #[test]
+    fn dimmed_area() {
+        let mut series = Series::new("a", vec![(0.0, 1.0), (10.0, 1.0)], Color::red());
+        series.dimmed = true;
+        let svg = render(&[series]);
+        assert_eq!(1, areas(&svg, "fill:rgb(127,127,127)").len(), "{}", svg);
+        assert!(!svg.contains("rgb(255,0,0)"));
+    }
+}
@@ -1,49 +1,347 @@
-use super::canvas::{HorizontalAnchor, VerticalAnchor};
+use super::canvas::{clamp_radius, HorizontalAnchor, TextOrientation, VerticalAnchor};
+use super::image;
 use super::Canvas;
-use crate::geometry::{Point, Size};
-use crate::style::Color;
+use crate::geometry::{Path, PathElement, Point, Rect, Size};
+use crate::style::{Color, Gradient, GradientKind, Stroke};
+use std::collections::HashMap;
 
 use std::io::Write;
 
+/// Units for the width and height of the SVG document.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Units {
+    Px,
+    Mm,
+
+    /// Fill the element containing the SVG, the size only sets the view box.
+    Percent,
+}
+
 /// Output to SVG file format!
+///
+/// The document is written to the file when the output is dropped.
 pub struct SvgOutput<'w> {
     file: &'w mut dyn Write,
+
+    /// All elements emitted so far.
+    body: String,
+
+    /// Size of the drawing, used for the view box.
+    size: Size,
+    units: Units,
+
+    /// Color to fill the whole drawing with, transparent when `None`.
+    background: Option<Color>,
+
+    pen: Color,
+    alpha: f64,
+    width: f64,
+
+    /// Dash pattern of lines, empty for solid lines.
+    dash: Vec<f64>,
+
+    /// Amount of decimals to use for coordinates.
+    precision: usize,
+
+    /// Amount of clip paths emitted, used to create unique id's.
+    clip_count: usize,
+
+    /// The current clip rectangle, if any.
+    clip: Option<Rect>,
+
+    /// Id of the gradient used for filling, if any.
+    fill_gradient: Option<String>,
+
+    /// Gradients emitted so far, to re-use identical gradients.
+    gradients: HashMap<String, String>,
+
+    /// Saved states, see `save` and `restore`.
+    state_stack: Vec<SvgState>,
+
+    /// Use css classes instead of inline styles.
+    use_classes: bool,
+
+    /// Compress the document, creating an svgz file.
+    #[cfg(feature = "gzip")]
+    gzip: bool,
+
+    /// Distinct styles, the position in this list determines the class name.
+    classes: Vec<String>,
+
+    /// Line being built up from connected `draw_line` calls, see `draw_line`.
+    pending_line: Option<PendingLine>,
+
+    /// Tooltip of the elements drawn next, see `set_title`.
+    title: Option<String>,
+}
+
+/// A path of line segments sharing a single style and title.
+struct PendingLine {
+    style: String,
+    title: Option<String>,
+    data: String,
+    last: Point,
+}
+
+/// Drawing state which can be saved and restored.
+struct SvgState {
     pen: Color,
+    alpha: f64,
     width: f64,
+    dash: Vec<f64>,
+    clip: Option<Rect>,
+    fill_gradient: Option<String>,
+    title: Option<String>,
 }
 
 impl<'w> SvgOutput<'w> {
     pub fn new(file: &'w mut dyn Write) -> Self {
-        let width = 1000;
-        let height = 1000;
-        writeln!(file, r#"<?xml version="1.0" encoding="UTF-8" ?>"#).unwrap();
-        writeln!(
-            file,
-            r#"<svg width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
-            width, height
-        )
-        .unwrap();
         SvgOutput {
             file,
+            body: String::new(),
+            size: Size::new(1000.0, 1000.0),
+            units: Units::Px,
+            background: None,
             pen: Color::black(),
+            alpha: 1.0,
             width: 1.0,
+            dash: vec![],
+            precision: 2,
+            clip_count: 0,
+            clip: None,
+            fill_gradient: None,
+            gradients: HashMap::new(),
+            state_stack: vec![],
+            use_classes: false,
+            #[cfg(feature = "gzip")]
+            gzip: false,
+            classes: vec![],
+            pending_line: None,
+            title: None,
+        }
+    }
+
+    /// Set the size of the drawing, which becomes the view box. The default is 1000x1000.
+    pub fn with_size(mut self, size: Size) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Set the units of the document width and height.
+    ///
+    /// With pixels or millimeters, one unit in the drawing maps onto one
+    /// unit of the document.
+    pub fn with_units(mut self, units: Units) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Fill the whole drawing with the given color, or keep it transparent with `None`.
+    ///
+    /// By default the background is transparent.
+    pub fn with_background(mut self, background: Option<Color>) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Put each distinct style in a css class, and refer to that from the elements.
+    ///
+    /// This makes the output a lot smaller when many elements share a style.
+    pub fn with_style_classes(mut self, enabled: bool) -> Self {
+        self.use_classes = enabled;
+        self
+    }
+
+    /// Gzip compress the output, as is expected for `.svgz` files.
+    #[cfg(feature = "gzip")]
+    pub fn with_gzip(mut self, enabled: bool) -> Self {
+        self.gzip = enabled;
+        self
+    }
+
+    /// Create the attribute to style an element with.
+    fn style_attribute(&mut self, style: String) -> String {
+        if self.use_classes {
+            let index = if let Some(index) = self.classes.iter().position(|s| *s == style) {
+                index
+            } else {
+                self.classes.push(style);
+                self.classes.len() - 1
+            };
+            format!(r#"class="s{}""#, index + 1)
+        } else {
+            format!(r#"style="{}""#, style)
+        }
+    }
+
+    fn header(&self) -> String {
+        let (width, height) = match self.units {
+            Units::Px => (
+                format!("{}px", self.num(self.size.width)),
+                format!("{}px", self.num(self.size.height)),
+            ),
+            Units::Mm => (
+                format!("{}mm", self.num(self.size.width)),
+                format!("{}mm", self.num(self.size.height)),
+            ),
+            Units::Percent => ("100%".to_owned(), "100%".to_owned()),
+        };
+        let mut header = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">
+"#,
+            width,
+            height,
+            self.num(self.size.width),
+            self.num(self.size.height)
+        );
+
+        if !self.classes.is_empty() {
+            header.push_str("   <defs><style type=\"text/css\"><![CDATA[\n");
+            for (index, style) in self.classes.iter().enumerate() {
+                header.push_str(&format!("      .s{} {{ {} }}\n", index + 1, style));
+            }
+            header.push_str("   ]]></style></defs>\n");
+        }
+
+        if let Some(color) = &self.background {
+            header.push_str(&format!(
+                "   <rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"rgb({},{},{})\" />\n",
+                self.num(self.size.width),
+                self.num(self.size.height),
+                color.r(),
+                color.g(),
+                color.b()
+            ));
+        }
+
+        header
+    }
+
+    /// Add an element, after the line under construction.
+    fn emit(&mut self, element: &str) {
+        self.flush_line();
+        self.body.push_str(element);
+        self.body.push('\n');
+    }
+
+    /// Add a drawn element, in a group with its title when there is one.
+    fn emit_shape(&mut self, element: &str) {
+        match &self.title {
+            Some(title) => {
+                let element = with_title(title, element);
+                self.emit(&element);
+            }
+            None => self.emit(element),
+        }
+    }
+
+    fn flush_line(&mut self) {
+        if let Some(line) = self.pending_line.take() {
+            let style = self.style_attribute(format!("{};fill:none", line.style));
+            let element = format!("   <path d=\"{}\" {} />", line.data, style);
+            let element = match &line.title {
+                Some(title) => with_title(title, &element),
+                None => element,
+            };
+            self.body.push_str(&element);
+            self.body.push('\n');
+        }
+    }
+
+    /// Round all emitted coordinates to the given amount of decimals.
+    ///
+    /// The default is 2 decimals. Every coordinate is rounded the same way,
+    /// so shapes which share a coordinate, such as a clip rectangle and the
+    /// lines ending on it, still line up exactly.
+    pub fn with_precision(mut self, decimals: usize) -> Self {
+        self.precision = decimals;
+        self
+    }
+
+    /// Format a coordinate with the configured precision, without trailing zeros.
+    fn num(&self, value: f64) -> String {
+        let factor = 10.0_f64.powi(self.precision as i32);
+        let rounded = (value * factor).round() / factor;
+        if rounded == 0.0 {
+            // Prevent "-0"
+            "0".to_owned()
+        } else {
+            format!("{}", rounded)
         }
     }
 
     fn get_stroke_style(&self) -> String {
-        format!(
-            r#"stroke:rgb({},{},{});stroke-width:2"#,
+        let mut style = format!(
+            r#"stroke:rgb({},{},{});stroke-width:{}"#,
+            self.pen.r(),
+            self.pen.g(),
+            self.pen.b(),
+            self.num(self.width)
+        );
+        if self.alpha < 1.0 {
+            style.push_str(&format!(";stroke-opacity:{}", self.num(self.alpha)));
+        }
+        if !self.dash.is_empty() {
+            let dash: Vec<String> = self.dash.iter().map(|d| self.num(*d)).collect();
+            style.push_str(&format!(";stroke-dasharray:{}", dash.join(",")));
+        }
+        style
+    }
+
+    /// Fill style for polygons, using the non-zero winding rule.
+    fn get_fill_style(&self) -> String {
+        if let Some(gradient_id) = &self.fill_gradient {
+            return format!("fill:url(#{});fill-rule:nonzero", gradient_id);
+        }
+
+        let mut style = format!(
+            r#"fill:rgb({},{},{});fill-rule:nonzero"#,
             self.pen.r(),
             self.pen.g(),
             self.pen.b()
-        )
+        );
+        if self.alpha < 1.0 {
+            style.push_str(&format!(";fill-opacity:{}", self.num(self.alpha)));
+        }
+        style
+    }
+
+    /// Convert a path into SVG path data.
+    fn path_to_string(&self, path: &Path) -> String {
+        let commands: Vec<String> = path
+            .elements()
+            .iter()
+            .map(|element| match element {
+                PathElement::MoveTo(p) => format!("M {} {}", self.num(p.x()), self.num(p.y())),
+                PathElement::LineTo(p) => format!("L {} {}", self.num(p.x()), self.num(p.y())),
+                PathElement::QuadTo(c, p) => format!(
+                    "Q {} {} {} {}",
+                    self.num(c.x()),
+                    self.num(c.y()),
+                    self.num(p.x()),
+                    self.num(p.y())
+                ),
+                PathElement::CubicTo(c1, c2, p) => format!(
+                    "C {} {} {} {} {} {}",
+                    self.num(c1.x()),
+                    self.num(c1.y()),
+                    self.num(c2.x()),
+                    self.num(c2.y()),
+                    self.num(p.x()),
+                    self.num(p.y())
+                ),
+                PathElement::Close => "Z".to_owned(),
+            })
+            .collect();
+        commands.join(" ")
     }
 
     /// Convert array of points into SVG points string.
-    fn points_to_string(points: &[Point]) -> String {
+    fn points_to_string(&self, points: &[Point]) -> String {
         let point_texts: Vec<String> = points
             .iter()
-            .map(|p| format!("{},{}", p.x(), p.y()))
+            .map(|p| format!("{},{}", self.num(p.x()), self.num(p.y())))
             .collect();
         point_texts.join(" ")
     }
@@ -51,30 +349,65 @@ impl<'w> SvgOutput<'w> {
 
 /// Implement the canvas API for svg output!
 impl<'w> Canvas for SvgOutput<'w> {
-    fn set_pen(&mut self, color: Color, _alpha: f64) {
+    fn set_pen(&mut self, color: Color, alpha: f64) {
         self.pen = color;
+        self.alpha = alpha;
+        self.fill_gradient = None;
+        self.dash.clear();
     }
 
     fn set_line_width(&mut self, width: f64) {
         self.width = width;
     }
 
+    fn set_dash(&mut self, dash: &[f64]) {
+        self.dash = dash.to_vec();
+    }
+
+    /// Put the elements drawn next in a group with a `<title>`, which browsers show as tooltip.
+    fn set_title(&mut self, title: Option<&str>) {
+        self.title = title.map(|title| title.to_owned());
+    }
+
     fn print_text(
         &mut self,
         p: &Point,
-        _horizontal_anchor: HorizontalAnchor,
-        _vertical_anchor: VerticalAnchor,
+        horizontal_anchor: HorizontalAnchor,
+        vertical_anchor: VerticalAnchor,
         text: &str,
     ) {
         info!("Printing text! {}", text);
-        writeln!(
-            self.file,
-            r#"   <text x="{}" y="{}">{}</text>"#,
-            p.x(),
-            p.y(),
-            text
-        )
-        .unwrap();
+        self.emit_shape(&format!(
+            r#"   <text x="{}" y="{}"{}>{}</text>"#,
+            self.num(p.x()),
+            self.num(p.y()),
+            anchor_attributes(horizontal_anchor, vertical_anchor),
+            escape_xml(text)
+        ));
+    }
+
+    fn print_text_oriented(
+        &mut self,
+        p: &Point,
+        horizontal_anchor: HorizontalAnchor,
+        vertical_anchor: VerticalAnchor,
+        orientation: TextOrientation,
+        text: &str,
+    ) {
+        if orientation == TextOrientation::Horizontal {
+            self.print_text(p, horizontal_anchor, vertical_anchor, text);
+            return;
+        }
+
+        let degrees = orientation.radians().to_degrees();
+        self.emit_shape(&format!(
+            r#"   <text x="{x}" y="{y}"{} transform="rotate({} {x} {y})">{}</text>"#,
+            anchor_attributes(horizontal_anchor, vertical_anchor),
+            self.num(degrees),
+            escape_xml(text),
+            x = self.num(p.x()),
+            y = self.num(p.y()),
+        ));
     }
 
     fn text_size(&self, text: &str) -> Size {
@@ -85,75 +418,865 @@ impl<'w> Canvas for SvgOutput<'w> {
     }
 
     /// Draw a line between points.
+    ///
+    /// Lines which start where the previous line ended, and have the same
+    /// style and title, are merged into a single path element.
     fn draw_line(&mut self, points: &[Point]) {
+        if points.len() < 2 {
+            return;
+        }
+
         let style = self.get_stroke_style();
+        let first = points[0];
+        let is_connected = match &self.pending_line {
+            Some(line) => line.style == style && line.title == self.title && line.last == first,
+            None => false,
+        };
 
-        if points.len() == 2 {
-            let p1 = points[0];
-            let p2 = points[1];
-            trace!("Line between {:?} and {:?}", p1, p2);
-            writeln!(
-                self.file,
-                r#"   <line x1="{}" y1="{}" x2="{}" y2="{}" style="{}" />"#,
-                p1.x(),
-                p1.y(),
-                p2.x(),
-                p2.y(),
-                style
-            )
-            .unwrap();
-        } else if points.len() > 2 {
-            let point_text = Self::points_to_string(points);
-            writeln!(
-                self.file,
-                r#"   <polyline points="{}" style="{}" />"#,
-                point_text, style
-            )
-            .unwrap();
+        if !is_connected {
+            self.flush_line();
+            trace!("Line starting at {:?}", first);
+            self.pending_line = Some(PendingLine {
+                data: format!("M {} {}", self.num(first.x()), self.num(first.y())),
+                style,
+                title: self.title.clone(),
+                last: first,
+            });
+        }
+
+        let mut segments = String::new();
+        for p in &points[1..] {
+            segments.push_str(&format!(" L {} {}", self.num(p.x()), self.num(p.y())));
         }
+        let line = self.pending_line.as_mut().unwrap();
+        line.data.push_str(&segments);
+        line.last = points[points.len() - 1];
     }
 
     fn draw_polygon(&mut self, points: &[Point]) {
         if points.len() > 2 {
-            let style = self.get_stroke_style();
+            let style = self.style_attribute(self.get_stroke_style());
 
-            let point_text = Self::points_to_string(points);
-            writeln!(
-                self.file,
-                r#"   <polygon points="{}" style="{}" />"#,
+            let point_text = self.points_to_string(points);
+            self.emit_shape(&format!(
+                r#"   <polygon points="{}" {} />"#,
                 point_text, style
-            )
-            .unwrap();
+            ));
         }
     }
 
     fn fill_polygon(&mut self, points: &[Point]) {
         if points.len() > 2 {
-            let style = format!(
-                r#"fill:rgb({},{},{});stroke-width:1"#,
-                self.pen.r(),
-                self.pen.g(),
-                self.pen.b()
-            );
+            let style = self.style_attribute(self.get_fill_style());
 
-            let point_text = Self::points_to_string(points);
-            writeln!(
-                self.file,
-                r#"   <polygon points="{}" style="{}" />"#,
+            let point_text = self.points_to_string(points);
+            self.emit_shape(&format!(
+                r#"   <polygon points="{}" {} />"#,
                 point_text, style
-            )
-            .unwrap();
+            ));
+        }
+    }
+
+    fn draw_image(
+        &mut self,
+        top_left: Point,
+        width: f64,
+        height: f64,
+        rgba: &[u8],
+        img_w: u32,
+        img_h: u32,
+    ) {
+        if !image::is_valid_rgba(rgba, img_w, img_h) {
+            warn!("Invalid image data for a {}x{} image", img_w, img_h);
+            return;
+        }
+
+        let png = image::encode_png(rgba, img_w, img_h);
+        self.emit_shape(&format!(
+            r#"   <image x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="none" href="data:image/png;base64,{}" />"#,
+            self.num(top_left.x()),
+            self.num(top_left.y()),
+            self.num(width),
+            self.num(height),
+            image::base64(&png)
+        ));
+    }
+
+    fn draw_circle(&mut self, center: &Point, radius: f64) {
+        let style = self.style_attribute(format!("{};fill:none", self.get_stroke_style()));
+        self.emit_shape(&format!(
+            r#"   <circle cx="{}" cy="{}" r="{}" {} />"#,
+            self.num(center.x()),
+            self.num(center.y()),
+            self.num(radius),
+            style
+        ));
+    }
+
+    fn draw_rounded_rect(
+        &mut self,
+        rect: &Rect,
+        radius: f64,
+        stroke: Option<Stroke>,
+        fill: Option<Color>,
+    ) {
+        let mut style = match fill {
+            Some(color) => {
+                self.set_pen(color, 1.0);
+                self.get_fill_style()
+            }
+            None => "fill:none".to_owned(),
+        };
+        if let Some(stroke) = stroke {
+            self.set_stroke(&stroke, 1.0);
+            style.push(';');
+            style.push_str(&self.get_stroke_style());
         }
+        let style = self.style_attribute(style);
+
+        let radius = clamp_radius(rect, radius);
+        let corners = if radius > 0.0 {
+            format!(r#" rx="{r}" ry="{r}""#, r = self.num(radius))
+        } else {
+            String::new()
+        };
+        self.emit_shape(&format!(
+            r#"   <rect x="{}" y="{}" width="{}" height="{}"{} {} />"#,
+            self.num(rect.x()),
+            self.num(rect.y()),
+            self.num(rect.width()),
+            self.num(rect.height()),
+            corners,
+            style
+        ));
     }
 
-    fn draw_circle(&mut self, _center: &Point, _radius: f64) {
-        // TODO!
+    fn fill_circle(&mut self, center: &Point, radius: f64) {
+        let style = self.style_attribute(self.get_fill_style());
+        self.emit_shape(&format!(
+            r#"   <circle cx="{}" cy="{}" r="{}" {} />"#,
+            self.num(center.x()),
+            self.num(center.y()),
+            self.num(radius),
+            style
+        ));
     }
+
+    /// Emit a gradient definition, unless an identical gradient was emitted before.
+    fn set_gradient(&mut self, gradient: &Gradient) {
+        let (tag, attributes) = match &gradient.kind {
+            GradientKind::Linear { start, end } => (
+                "linearGradient",
+                format!(
+                    r#"x1="{}" y1="{}" x2="{}" y2="{}""#,
+                    self.num(start.x()),
+                    self.num(start.y()),
+                    self.num(end.x()),
+                    self.num(end.y())
+                ),
+            ),
+            GradientKind::Radial { center, radius } => (
+                "radialGradient",
+                format!(
+                    r#"cx="{}" cy="{}" r="{}""#,
+                    self.num(center.x()),
+                    self.num(center.y()),
+                    self.num(*radius)
+                ),
+            ),
+        };
+        let stops: Vec<String> = gradient
+            .stops
+            .iter()
+            .map(|stop| {
+                format!(
+                    r#"<stop offset="{}" stop-color="rgb({},{},{})" stop-opacity="{}" />"#,
+                    stop.offset,
+                    stop.color.r(),
+                    stop.color.g(),
+                    stop.color.b(),
+                    stop.alpha
+                )
+            })
+            .collect();
+        let key = format!("{} {} {}", tag, attributes, stops.join(""));
+
+        let gradient_id = if let Some(gradient_id) = self.gradients.get(&key) {
+            gradient_id.clone()
+        } else {
+            let gradient_id = format!("gradient{}", self.gradients.len() + 1);
+            self.emit(&format!(
+                r#"   <defs><{0} id="{1}" gradientUnits="userSpaceOnUse" {2}>{3}</{0}></defs>"#,
+                tag,
+                gradient_id,
+                attributes,
+                stops.join("")
+            ));
+            self.gradients.insert(key, gradient_id.clone());
+            gradient_id
+        };
+
+        self.fill_gradient = Some(gradient_id);
+    }
+
+    fn draw_path(&mut self, path: &Path) {
+        let style = self.style_attribute(format!("{};fill:none", self.get_stroke_style()));
+        let path_text = self.path_to_string(path);
+        self.emit_shape(&format!(r#"   <path d="{}" {} />"#, path_text, style));
+    }
+
+    /// Define a clip path, and put all following elements in a group using this clip path.
+    fn set_clip_rect(&mut self, rect: Rect) {
+        self.clear_clip();
+        self.clip_count += 1;
+        let clip_id = format!("clip{}", self.clip_count);
+        self.emit(&format!(r#"   <defs><clipPath id="{}"><rect x="{}" y="{}" width="{}" height="{}" /></clipPath></defs>"#,
+            clip_id,
+            self.num(rect.x()),
+            self.num(rect.y()),
+            self.num(rect.width()),
+            self.num(rect.height())));
+        self.emit(&format!(r#"   <g clip-path="url(#{})">"#, clip_id));
+        self.clip = Some(rect);
+    }
+
+    fn clear_clip(&mut self) {
+        if self.clip.take().is_some() {
+            self.emit("   </g>");
+        }
+    }
+
+    fn save(&mut self) {
+        self.state_stack.push(SvgState {
+            pen: self.pen.clone(),
+            alpha: self.alpha,
+            width: self.width,
+            dash: self.dash.clone(),
+            clip: self.clip.clone(),
+            fill_gradient: self.fill_gradient.clone(),
+            title: self.title.clone(),
+        });
+    }
+
+    fn restore(&mut self) {
+        if let Some(state) = self.state_stack.pop() {
+            self.pen = state.pen;
+            self.alpha = state.alpha;
+            self.width = state.width;
+            self.dash = state.dash;
+            self.fill_gradient = state.fill_gradient;
+            self.title = state.title;
+            if state.clip != self.clip {
+                self.clear_clip();
+                if let Some(rect) = state.clip {
+                    self.set_clip_rect(rect);
+                }
+            }
+        }
+    }
+}
+
+/// Align text in SVG like the anchors do on the other canvases.
+fn anchor_attributes(
+    horizontal_anchor: HorizontalAnchor,
+    vertical_anchor: VerticalAnchor,
+) -> String {
+    let text_anchor = match horizontal_anchor {
+        HorizontalAnchor::Left => "start",
+        HorizontalAnchor::Middle => "middle",
+        HorizontalAnchor::Right => "end",
+    };
+    let baseline = match vertical_anchor {
+        VerticalAnchor::Top => "hanging",
+        VerticalAnchor::Middle => "central",
+        VerticalAnchor::Bottom => "alphabetic",
+    };
+    format!(
+        r#" text-anchor="{}" dominant-baseline="{}""#,
+        text_anchor, baseline
+    )
 }
 
-/// Implement drop destructor so we can write the closing svg tag.
+/// Escape the characters which have a meaning in XML text.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Wrap an element in a group with a title.
+fn with_title(title: &str, element: &str) -> String {
+    format!(
+        "   <g><title>{}</title>{}</g>",
+        escape_xml(title),
+        element.trim_start()
+    )
+}
+
+/// Implement drop destructor so we can write the document.
 impl<'w> Drop for SvgOutput<'w> {
     fn drop(&mut self) {
-        writeln!(self.file, "</svg>").unwrap();
+        self.clear_clip();
+        self.emit("</svg>");
+        let header = self.header();
+
+        #[cfg(feature = "gzip")]
+        {
+            if self.gzip {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(&mut *self.file, flate2::Compression::default());
+                encoder.write_all(header.as_bytes()).unwrap();
+                encoder.write_all(self.body.as_bytes()).unwrap();
+                encoder.finish().unwrap();
+                return;
+            }
+        }
+
+        self.file.write_all(header.as_bytes()).unwrap();
+        self.file.write_all(self.body.as_bytes()).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SvgOutput, Units};
+    use crate::geometry::{Path, Point, Rect, Size};
+    use crate::render::canvas::{HorizontalAnchor, TextOrientation, VerticalAnchor};
+    use crate::render::Canvas;
+    use crate::style::{Color, Gradient, Stroke};
+
+    #[test]
+    fn rounded_rect() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            let rect = Rect::new(10.0, 20.0, 100.0, 40.0);
+            let stroke = Stroke::new(Color::black(), 1.0);
+            canvas.draw_rounded_rect(&rect, 4.5, Some(stroke.clone()), Some(Color::white()));
+            canvas.draw_rounded_rect(&rect, 0.0, Some(stroke), None);
+            canvas.draw_rounded_rect(&rect, 50.0, None, Some(Color::red()));
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+        let rects: Vec<&str> = svg.lines().filter(|l| l.contains("<rect")).collect();
+
+        assert_eq!(3, rects.len());
+        assert!(
+            rects[0].contains(r#"<rect x="10" y="20" width="100" height="40" rx="4.5" ry="4.5""#)
+        );
+        assert!(rects[0].contains("fill:rgb(255,255,255)"));
+        assert!(rects[0].contains("stroke:rgb(0,0,0)"));
+        assert!(!rects[1].contains("rx="));
+        assert!(rects[1].contains("fill:none"));
+        assert!(rects[2].contains(r#"rx="20" ry="20""#));
+        assert!(!rects[2].contains("stroke:"));
+    }
+
+    #[test]
+    fn titles() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            canvas.save();
+            canvas.set_title(Some("a <b>"));
+            canvas.draw_line(&[Point::new(0.0, 0.0), Point::new(10.0, 0.0)]);
+            canvas.set_title(Some("c"));
+            canvas.draw_line(&[Point::new(10.0, 0.0), Point::new(20.0, 0.0)]);
+            canvas.fill_circle(&Point::new(5.0, 5.0), 2.0);
+            canvas.restore();
+            canvas.draw_line(&[Point::new(20.0, 0.0), Point::new(30.0, 0.0)]);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = svg.lines().filter(|l| l.contains("<path")).collect();
+
+        // Lines with another title are not merged:
+        assert_eq!(3, lines.len(), "{}", svg);
+        assert!(lines[0].starts_with("   <g><title>a &lt;b&gt;</title><path d=\"M 0 0 L 10 0\""));
+        assert!(lines[0].ends_with("/></g>"));
+        assert!(lines[1].starts_with("   <g><title>c</title><path"));
+        assert!(svg.contains("<g><title>c</title><circle"));
+
+        // The title is restored along with the other state:
+        assert!(lines[2].starts_with("   <path"));
+    }
+
+    #[test]
+    fn stroke_opacity() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            let faint = Stroke::new(Color::red(), 1.0).with_opacity(0.5);
+            canvas.set_stroke(&faint, 1.0);
+            canvas.draw_line(&[Point::new(0.0, 0.0), Point::new(10.0, 0.0)]);
+            canvas.set_stroke(&faint, 0.6);
+            canvas.draw_line(&[Point::new(0.0, 5.0), Point::new(10.0, 5.0)]);
+            canvas.set_stroke(&Stroke::new(Color::red(), 1.0), 1.0);
+            canvas.draw_line(&[Point::new(0.0, 9.0), Point::new(10.0, 9.0)]);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = svg.lines().filter(|l| l.contains("<path")).collect();
+
+        assert_eq!(3, lines.len());
+        assert!(lines[0].contains("stroke-opacity:0.5"), "{}", lines[0]);
+        assert!(lines[1].contains("stroke-opacity:0.3"), "{}", lines[1]);
+        assert!(!lines[2].contains("stroke-opacity"), "{}", lines[2]);
+    }
+
+    #[test]
+    fn dashed_stroke() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            let dashed = Stroke::new(Color::red(), 1.0).with_dash(&[4.0, 2.5]);
+            canvas.set_stroke(&dashed, 1.0);
+            canvas.draw_line(&[Point::new(0.0, 0.0), Point::new(10.0, 0.0)]);
+            canvas.set_pen(Color::red(), 1.0);
+            canvas.draw_line(&[Point::new(10.0, 0.0), Point::new(20.0, 0.0)]);
+            canvas.set_stroke(&Stroke::new(Color::red(), 1.0).with_dash(&[4.0, -1.0]), 1.0);
+            canvas.draw_line(&[Point::new(0.0, 5.0), Point::new(10.0, 5.0)]);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = svg.lines().filter(|l| l.contains("<path")).collect();
+
+        // A new pen draws solid lines, so the dashed line is not continued:
+        assert_eq!(3, lines.len());
+        assert!(lines[0].contains("M 0 0 L 10 0\""), "{}", lines[0]);
+        assert!(
+            lines[0].contains(";stroke-dasharray:4,2.5;"),
+            "{}",
+            lines[0]
+        );
+        assert!(!lines[1].contains("stroke-dasharray"), "{}", lines[1]);
+        assert!(!lines[2].contains("stroke-dasharray"), "{}", lines[2]);
+    }
+
+    #[test]
+    fn line_width() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            canvas.set_pen(Color::red(), 1.0 / 3.0);
+            canvas.set_line_width(2.5);
+            canvas.draw_line(&[Point::new(0.0, 0.0), Point::new(10.0, 0.0)]);
+            canvas.set_line_width(0.125);
+            canvas.draw_line(&[Point::new(10.0, 0.0), Point::new(20.0, 0.0)]);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = svg.lines().filter(|l| l.contains("<path")).collect();
+
+        // A different width starts a new path:
+        assert_eq!(2, lines.len());
+        assert!(
+            lines[0].contains("stroke-width:2.5;stroke-opacity:0.33"),
+            "{}",
+            lines[0]
+        );
+        assert!(lines[1].contains("stroke-width:0.13;"), "{}", lines[1]);
+    }
+
+    #[test]
+    fn clipped_line() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            canvas.set_clip_rect(Rect::new(10.0, 10.0, 100.0, 50.0));
+            canvas.draw_line(&[Point::new(20.0, 20.0), Point::new(200.0, 20.0)]);
+            canvas.clear_clip();
+            canvas.draw_line(&[Point::new(0.0, 0.0), Point::new(5.0, 5.0)]);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+
+        assert!(svg.contains(
+            r#"<clipPath id="clip1"><rect x="10" y="10" width="100" height="50" /></clipPath>"#
+        ));
+
+        // The line partly outside the clip rect must be inside the clipped group:
+        let group_start = svg.find(r#"<g clip-path="url(#clip1)">"#).unwrap();
+        let group_end = svg.find("</g>").unwrap();
+        let clipped_line = svg.find("L 200 20").unwrap();
+        let unclipped_line = svg.find("L 5 5").unwrap();
+        assert!(group_start < clipped_line && clipped_line < group_end);
+        assert!(group_end < unclipped_line);
+    }
+
+    #[test]
+    fn view_box_and_units() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            SvgOutput::new(&mut buffer)
+                .with_size(Size::new(300.0, 200.5))
+                .with_units(Units::Mm);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+        assert!(svg.contains(r#"width="300mm" height="200.5mm" viewBox="0 0 300 200.5">"#));
+
+        let mut buffer: Vec<u8> = vec![];
+        {
+            SvgOutput::new(&mut buffer).with_units(Units::Percent);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+        assert!(svg.contains(r#"width="100%" height="100%" viewBox="0 0 1000 1000">"#));
+    }
+
+    #[test]
+    fn shared_style_classes() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer).with_style_classes(true);
+            canvas.set_pen(Color::red(), 1.0);
+            canvas.draw_circle(&Point::new(5.0, 5.0), 2.0);
+            canvas.draw_line(&[Point::new(0.0, 0.0), Point::new(1.0, 1.0)]);
+            canvas.draw_line(&[Point::new(5.0, 0.0), Point::new(6.0, 1.0)]);
+            canvas.fill_circle(&Point::new(5.0, 5.0), 2.0);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+
+        let stroke = "stroke:rgb(255,0,0);stroke-width:1;fill:none";
+        assert_eq!(1, svg.matches(stroke).count());
+        assert!(svg.contains(&format!(".s1 {{ {} }}", stroke)));
+        assert_eq!(3, svg.matches(r#"class="s1""#).count());
+        assert!(svg.contains(r#"<circle cx="5" cy="5" r="2" class="s2" />"#));
+        assert!(!svg.contains("style=\""));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_roundtrip() {
+        use std::io::Read;
+
+        fn draw(canvas: &mut SvgOutput) {
+            canvas.set_pen(Color::red(), 0.5);
+            canvas.draw_line(&[Point::new(0.0, 0.0), Point::new(10.0, 10.0)]);
+            canvas.fill_circle(&Point::new(5.0, 5.0), 3.0);
+        }
+
+        let mut plain: Vec<u8> = vec![];
+        draw(&mut SvgOutput::new(&mut plain));
+        let mut compressed: Vec<u8> = vec![];
+        draw(&mut SvgOutput::new(&mut compressed).with_gzip(true));
+
+        let mut decompressed = String::new();
+        flate2::read::GzDecoder::new(&compressed[..])
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(String::from_utf8(plain).unwrap(), decompressed);
+    }
+
+    #[test]
+    fn background() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer)
+                .with_size(Size::new(40.0, 30.0))
+                .with_background(Some(Color::white()));
+            canvas.draw_line(&[Point::new(0.0, 0.0), Point::new(10.0, 10.0)]);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+        let background = svg
+            .find(r#"<rect x="0" y="0" width="40" height="30" fill="rgb(255,255,255)" />"#)
+            .unwrap();
+        assert!(background < svg.find("<path").unwrap());
+
+        let mut buffer: Vec<u8> = vec![];
+        {
+            SvgOutput::new(&mut buffer).with_background(None);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+        assert!(!svg.contains("<rect"));
+    }
+
+    #[test]
+    fn coordinate_precision() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer).with_precision(1);
+            canvas.draw_line(&[
+                Point::new(1.0 / 3.0, 2.0 / 3.0),
+                Point::new(10.06, -0.04),
+                Point::new(2.0, 7.25),
+            ]);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+        assert!(svg.contains(r#"<path d="M 0.3 0.7 L 10.1 0 L 2 7.3""#));
+
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            canvas.draw_line(&[Point::new(1.0 / 3.0, 2.0 / 3.0), Point::new(1.0, 2.5)]);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+        assert!(svg.contains(r#"<path d="M 0.33 0.67 L 1 2.5""#));
+    }
+
+    #[test]
+    fn merged_line_segments() {
+        let points = vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 2.0),
+            Point::new(2.0, 1.0),
+            Point::new(3.0, 4.0),
+            Point::new(4.0, 3.0),
+        ];
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            canvas.draw_line(&points);
+
+            // Separate, but connected, segments:
+            canvas.set_pen(Color::red(), 1.0);
+            for segment in points.windows(2) {
+                canvas.draw_line(segment);
+            }
+
+            // Not connected, or a different style, starts a new path:
+            canvas.draw_line(&[Point::new(10.0, 10.0), Point::new(11.0, 11.0)]);
+            canvas.set_pen(Color::green(), 1.0);
+            canvas.draw_line(&[Point::new(11.0, 11.0), Point::new(12.0, 12.0)]);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+
+        let expected = r#"<path d="M 0 0 L 1 2 L 2 1 L 3 4 L 4 3" style="stroke:rgb(0,0,0);stroke-width:1;fill:none" />"#;
+        assert!(svg.contains(expected));
+        let expected = r#"<path d="M 0 0 L 1 2 L 2 1 L 3 4 L 4 3" style="stroke:rgb(255,0,0);stroke-width:1;fill:none" />"#;
+        assert!(svg.contains(expected));
+        assert_eq!(4, svg.matches("<path").count());
+        assert!(svg.ends_with("/>\n</svg>\n"));
+    }
+
+    #[test]
+    fn bezier_path() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            canvas.draw_bezier(
+                &Point::new(0.0, 0.0),
+                &Point::new(10.0, 20.0),
+                &Point::new(30.0, 20.0),
+                &Point::new(40.0, 0.0),
+            );
+
+            // Control points on the line itself, this is a straight line:
+            canvas.draw_bezier(
+                &Point::new(0.0, 0.0),
+                &Point::new(1.0, 1.0),
+                &Point::new(2.0, 2.0),
+                &Point::new(3.0, 3.0),
+            );
+
+            let mut path = Path::new(Point::new(0.0, 0.0));
+            path.quad_to(Point::new(5.0, 10.0), Point::new(10.0, 0.0));
+            path.close();
+            canvas.draw_path(&path);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+
+        assert!(svg.contains(r#"<path d="M 0 0 C 10 20 30 20 40 0""#));
+        assert!(svg.contains(r#"<path d="M 0 0 C 1 1 2 2 3 3""#));
+        assert!(svg.contains(r#"<path d="M 0 0 Q 5 10 10 0 Z""#));
+    }
+
+    #[test]
+    fn filled_polygons() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            canvas.set_pen(Color::red(), 0.5);
+            let triangle = vec![
+                Point::new(0.0, 0.0),
+                Point::new(10.0, 0.0),
+                Point::new(5.0, 10.0),
+            ];
+            canvas.fill_polygon(&triangle);
+
+            // A bow tie shape, which intersects itself:
+            canvas.set_pen(Color::green(), 1.0);
+            let quad = vec![
+                Point::new(0.0, 0.0),
+                Point::new(10.0, 10.0),
+                Point::new(10.0, 0.0),
+                Point::new(0.0, 10.0),
+            ];
+            canvas.fill_polygon(&quad);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+
+        assert!(svg.contains(
+            r#"<polygon points="0,0 10,0 5,10" style="fill:rgb(255,0,0);fill-rule:nonzero;fill-opacity:0.5" />"#
+        ));
+        assert!(svg.contains(
+            r#"<polygon points="0,0 10,10 10,0 0,10" style="fill:rgb(0,255,0);fill-rule:nonzero" />"#
+        ));
+    }
+
+    #[test]
+    fn gradient_fill() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            let gradient = Gradient::linear(Point::new(0.0, 0.0), Point::new(0.0, 100.0))
+                .add_stop(0.0, Color::red(), 1.0)
+                .add_stop(1.0, Color::red(), 0.0);
+            let square = vec![
+                Point::new(0.0, 0.0),
+                Point::new(10.0, 0.0),
+                Point::new(10.0, 10.0),
+                Point::new(0.0, 10.0),
+            ];
+            canvas.set_gradient(&gradient);
+            canvas.fill_polygon(&square);
+            canvas.set_gradient(&gradient);
+            canvas.fill_circle(&Point::new(5.0, 5.0), 3.0);
+            canvas.set_gradient(&Gradient::radial(Point::new(5.0, 5.0), 5.0));
+            canvas.fill_polygon(&square);
+            canvas.set_pen(Color::black(), 1.0);
+            canvas.fill_polygon(&square);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+
+        assert_eq!(1, svg.matches("<linearGradient").count());
+        assert_eq!(1, svg.matches("<radialGradient").count());
+        assert!(svg.contains(r#"<stop offset="0" stop-color="rgb(255,0,0)" stop-opacity="1" />"#));
+        assert!(svg.contains(r#"<stop offset="1" stop-color="rgb(255,0,0)" stop-opacity="0" />"#));
+        assert_eq!(2, svg.matches("fill:url(#gradient1)").count());
+        assert_eq!(1, svg.matches("fill:url(#gradient2)").count());
+        assert!(svg.contains("fill:rgb(0,0,0)"));
+    }
+
+    #[test]
+    fn rotated_text() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            for orientation in &[
+                TextOrientation::Horizontal,
+                TextOrientation::VerticalUp,
+                TextOrientation::VerticalDown,
+            ] {
+                canvas.print_text_oriented(
+                    &Point::new(20.0, 300.0),
+                    HorizontalAnchor::Middle,
+                    VerticalAnchor::Top,
+                    *orientation,
+                    "Value",
+                );
+            }
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+
+        let anchors = r#"text-anchor="middle" dominant-baseline="hanging""#;
+        assert!(svg.contains(&format!(r#"<text x="20" y="300" {}>Value</text>"#, anchors)));
+        assert!(svg.contains(&format!(
+            r#"<text x="20" y="300" {} transform="rotate(-90 20 300)">Value</text>"#,
+            anchors
+        )));
+        assert!(svg.contains(&format!(
+            r#"<text x="20" y="300" {} transform="rotate(90 20 300)">Value</text>"#,
+            anchors
+        )));
+    }
+
+    #[test]
+    fn text_anchors() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            canvas.print_text(
+                &Point::new(1.0, 2.0),
+                HorizontalAnchor::Left,
+                VerticalAnchor::Bottom,
+                "a",
+            );
+            canvas.print_text(
+                &Point::new(1.0, 2.0),
+                HorizontalAnchor::Right,
+                VerticalAnchor::Middle,
+                "b",
+            );
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+
+        assert!(svg.contains(
+            r#"<text x="1" y="2" text-anchor="start" dominant-baseline="alphabetic">a</text>"#
+        ));
+        assert!(svg.contains(
+            r#"<text x="1" y="2" text-anchor="end" dominant-baseline="central">b</text>"#
+        ));
+    }
+
+    #[test]
+    fn escaped_text() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            let p = Point::new(1.0, 2.0);
+            canvas.print_text(&p, HorizontalAnchor::Left, VerticalAnchor::Top, "a<b & c");
+            canvas.print_text_oriented(
+                &p,
+                HorizontalAnchor::Left,
+                VerticalAnchor::Top,
+                TextOrientation::VerticalUp,
+                "<mV>",
+            );
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+
+        assert!(svg.contains(">a&lt;b &amp; c</text>"), "{}", svg);
+        assert!(svg.contains(">&lt;mV&gt;</text>"), "{}", svg);
+    }
+
+    #[test]
+    fn image_data_uri() {
+        let rgba = [255, 0, 0, 255, 0, 255, 0, 128];
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            canvas.draw_image(Point::new(10.0, 20.0), 30.0, 40.5, &rgba, 2, 1);
+
+            // Too little or too much data is not drawn:
+            canvas.draw_image(Point::new(0.0, 0.0), 1.0, 1.0, &rgba, 2, 2);
+            canvas.draw_image(Point::new(0.0, 0.0), 1.0, 1.0, &rgba, 1, 1);
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+
+        assert_eq!(1, svg.matches("<image ").count());
+        assert!(svg.contains(
+            r#"<image x="10" y="20" width="30" height="40.5" preserveAspectRatio="none" href="data:image/png;base64,iVBORw0KGgo"#
+        ));
+    }
+
+    #[test]
+    fn nested_save_restore() {
+        let mut buffer: Vec<u8> = vec![];
+        {
+            let mut canvas = SvgOutput::new(&mut buffer);
+            canvas.set_pen(Color::red(), 1.0);
+            canvas.save();
+            canvas.set_pen(Color::green(), 1.0);
+            canvas.set_clip_rect(Rect::new(0.0, 0.0, 10.0, 10.0));
+            canvas.save();
+            canvas.set_pen(Color::white(), 1.0);
+            canvas.clear_clip();
+            canvas.draw_line(&[Point::new(0.0, 0.0), Point::new(1.0, 1.0)]);
+            canvas.restore();
+            canvas.draw_line(&[Point::new(0.0, 0.0), Point::new(2.0, 2.0)]);
+            canvas.restore();
+            canvas.draw_line(&[Point::new(0.0, 0.0), Point::new(3.0, 3.0)]);
+
+            // Restoring too often is harmless:
+            canvas.restore();
+        }
+        let svg = String::from_utf8(buffer).unwrap();
+
+        let line1 = svg.find("L 1 1").unwrap();
+        let line2 = svg.find("L 2 2").unwrap();
+        let line3 = svg.find("L 3 3").unwrap();
+        assert!(svg[line1..line2].contains("stroke:rgb(255,255,255)"));
+        assert!(svg[line2..line3].contains("stroke:rgb(0,255,0)"));
+        assert!(svg[line3..].contains("stroke:rgb(255,0,0)"));
+
+        // The clip is re-applied for the second line only:
+        let group_start = svg.find(r#"<g clip-path="url(#clip2)">"#).unwrap();
+        let group_end = svg.rfind("</g>").unwrap();
+        assert!(line1 < group_start && group_start < line2);
+        assert!(line2 < group_end && group_end < line3);
     }
 }
@@ -0,0 +1,43 @@
+//! Setup shared by the tests of the render functions.
+
+use super::{ChartLayout, ChartOptions, SvgOutput, Transform};
+use crate::geometry::{Range, Size};
+
+/// Layout of a chart of 800 by 600 pixels, with the default options.
+///
+/// The plot area spans from 140 to 790 horizontally, and from 10 to 510
+/// vertically.
+pub fn layout() -> ChartLayout {
+    sized_layout(Size::new(800.0, 600.0))
+}
+
+/// Layout of a chart of the given size, with the default options.
+pub fn sized_layout(size: Size) -> ChartLayout {
+    let mut layout = ChartLayout::new(size);
+    layout.layout(&ChartOptions::default());
+    layout
+}
+
+/// The layout of `layout`, with a transform of the given ranges onto its plot area.
+pub fn layout_and_transform(x: (f64, f64), y: (f64, f64)) -> (ChartLayout, Transform) {
+    let layout = layout();
+    let transform = Transform::from_ranges(
+        &Range::new(x.0, x.1),
+        &Range::new(y.0, y.1),
+        &layout.plot_rect(),
+    );
+    (layout, transform)
+}
+
+/// Draw onto an SVG canvas, and get the resulting document.
+pub fn render_svg<F>(draw: F) -> String
+where
+    F: FnOnce(&mut SvgOutput),
+{
+    let mut buffer: Vec<u8> = vec![];
+    {
+        let mut output = SvgOutput::new(&mut buffer);
+        draw(&mut output);
+    }
+    String::from_utf8(buffer).unwrap()
+}
@@ -0,0 +1,335 @@
+//! Tick marker positions at human friendly values.
+
+use crate::chart::{log_ticks, scale_power};
+use crate::geometry::Range;
+
+/// Calculate evenly spaced ticks at round numbers, spanning the given range.
+///
+/// This is the loose labeling algorithm from Paul Heckbert's "Nice numbers
+/// for graph labels": the tick step is 1, 2 or 5 times a power of ten, and the
+/// first and last tick are placed at or just outside the range. The amount
+/// of ticks is approximately the target count.
+pub fn ticks(range: &Range<f64>, target_count: usize) -> Vec<f64> {
+    let low = range.begin().min(range.end());
+    let high = range.begin().max(range.end());
+    if !low.is_finite() || !high.is_finite() {
+        return vec![];
+    }
+
+    if high - low <= 0.0 {
+        return vec![low];
+    }
+
+    let target_count = target_count.max(2);
+    let (mantissa, exponent) = nice_number(high - low, false);
+    let nice_range = mantissa * 10.0_f64.powi(exponent);
+    let (mantissa, exponent) = nice_number(nice_range / (target_count - 1) as f64, true);
+    let step = mantissa * 10.0_f64.powi(exponent);
+    let first = (low / step).floor() as i64;
+    let last = (high / step).ceil() as i64;
+
+    // Calculate each tick from integers, to prevent accumulating rounding errors:
+    (first..=last)
+        .map(|index| {
+            if exponent < 0 {
+                (index as f64 * mantissa) / 10.0_f64.powi(-exponent)
+            } else {
+                (index as f64 * mantissa) * 10.0_f64.powi(exponent)
+            }
+        })
+        .collect()
+}
+
+/// Amount of ticks aimed for by `nice_range`.
+const NICE_RANGE_TICKS: usize = 5;
+
+/// Expand a data range outward, so it begins and ends at round numbers.
+///
+/// The range ends at the first and last tick `ticks` places for the data,
+/// with a target of 5 ticks. So 3 to 97 becomes 0 to 100, in steps of 20, and
+/// -0.3 to 4.2 becomes -1 to 5, in steps of 1. Equal values are widened by
+/// a half on both sides first, and without finite values a unit range is returned.
+pub fn nice_range(min: f64, max: f64) -> Range<f64> {
+    if !min.is_finite() || !max.is_finite() {
+        return Range::new(0.0, 1.0);
+    }
+
+    let (low, high) = if min == max {
+        (min - 0.5, max + 0.5)
+    } else {
+        (min.min(max), min.max(max))
+    };
+    let ticks = ticks(&Range::new(low, high), NICE_RANGE_TICKS);
+    match (ticks.first(), ticks.last()) {
+        (Some(first), Some(last)) => Range::new(*first, *last),
+        _ => Range::new(low, high),
+    }
+}
+
+/// Major ticks, and unlabeled minor ticks in between.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ticks {
+    pub major: Vec<f64>,
+    pub minor: Vec<f64>,
+}
+
+/// Calculate major ticks like `ticks`, and divide each interval between
+/// two major ticks with `minor_count` minor ticks.
+///
+/// With 4 minor ticks, the minor ticks are at a fifth of the major tick step.
+pub fn ticks_with_minor(range: &Range<f64>, target_count: usize, minor_count: usize) -> Ticks {
+    let major = ticks(range, target_count);
+    let minor = minor_ticks(&major, minor_count);
+    Ticks { major, minor }
+}
+
+/// Divide each interval between two consecutive major ticks with `minor_count` minor ticks.
+pub fn minor_ticks(major: &[f64], minor_count: usize) -> Vec<f64> {
+    let divisions = (minor_count + 1) as f64;
+    major
+        .windows(2)
+        .flat_map(|pair| {
+            let (low, high) = (pair[0], pair[1]);
+            (1..=minor_count).map(move |index| low + (high - low) * index as f64 / divisions)
+        })
+        .collect()
+}
+
+/// Calculate ticks for a logarithmic axis, with minor ticks at 2 up to 9
+/// times each decade.
+pub fn log_ticks_with_minor(range: &Range<f64>) -> Ticks {
+    let low = range.begin().min(range.end());
+    let high = range.begin().max(range.end());
+    let (major, _) = log_ticks(range);
+    if high <= 0.0 || !high.is_finite() {
+        return Ticks::default();
+    }
+
+    let low = if low > 0.0 { low } else { high / 1.0e6 };
+    let mut minor = vec![];
+    for exponent in low.log10().floor() as i32..=high.log10().ceil() as i32 {
+        for mantissa in 2..=9 {
+            let value = scale_power(mantissa as f64, exponent);
+            if value >= low * (1.0 - 1.0e-9) && value <= high * (1.0 + 1.0e-9) {
+                minor.push(value);
+            }
+        }
+    }
+
+    Ticks { major, minor }
+}
+
+/// How tick values are turned into labels, see `format_tick`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TickFormat {
+    /// The shortest decimal representation, such as `0.25` or `1500`.
+    #[default]
+    Plain,
+
+    /// Scientific notation with at most this many significant figures, such as `1.5e3`.
+    Scientific(usize),
+
+    /// An SI prefix with at most this many significant figures, such as `1.5k`.
+    SiPrefix(usize),
+
+    /// A fixed amount of decimals, such as `1500.00`.
+    Fixed(usize),
+}
+
+/// SI prefixes from 1e-24 up to 1e24, in steps of a thousand.
+const SI_PREFIXES: [&str; 17] = [
+    "y", "z", "a", "f", "p", "n", "µ", "m", "", "k", "M", "G", "T", "P", "E", "Z", "Y",
+];
+
+/// Format a tick value as label text.
+///
+/// Trailing zeros are left out of the scientific and SI prefix formats, so
+/// 1000 at 3 significant figures becomes `1k` instead of `1.00k`. Zero and
+/// non-finite values are formatted as plain numbers.
+pub fn format_tick(value: f64, style: TickFormat) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return match style {
+            TickFormat::Fixed(decimals) => format!("{:.*}", decimals, value),
+            _ => format!("{}", value),
+        };
+    }
+
+    match style {
+        TickFormat::Plain => format!("{}", value),
+        TickFormat::Fixed(decimals) => format!("{:.*}", decimals, value),
+        TickFormat::Scientific(significant) => {
+            let text = format!("{:.*e}", significant.max(1) - 1, value);
+            match text.find('e') {
+                Some(split) => format!("{}{}", trim_zeros(&text[..split]), &text[split..]),
+                None => text,
+            }
+        }
+        TickFormat::SiPrefix(significant) => {
+            let significant = significant.max(1);
+            let mut group = (value.abs().log10() / 3.0).floor() as i32;
+            loop {
+                group = group.clamp(-8, 8);
+                let scaled = value / 10.0_f64.powi(group * 3);
+                let magnitude = scaled.abs().log10().floor() as i32;
+                let decimals = (significant as i32 - 1 - magnitude).max(0) as usize;
+                let text = format!("{:.*}", decimals, scaled);
+
+                // Rounding can carry over into the next prefix, like 999.9 into 1000:
+                let rounded: f64 = text.parse().unwrap_or(scaled);
+                if rounded.abs() >= 1000.0 && group < 8 {
+                    group += 1;
+                    continue;
+                }
+                let prefix = SI_PREFIXES[(group + 8) as usize];
+                return format!("{}{}", trim_zeros(&text), prefix);
+            }
+        }
+    }
+}
+
+/// Remove trailing zeros after the decimal point, and the point itself when nothing remains.
+fn trim_zeros(text: &str) -> &str {
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        text
+    }
+}
+
+/// Find a nice number close to the given value.
+///
+/// The result is returned as mantissa (1, 2, 5 or 10) and power of ten. When
+/// rounding, the closest nice number is taken, otherwise the next larger one.
+fn nice_number(value: f64, round: bool) -> (f64, i32) {
+    let exponent = value.log10().floor() as i32;
+    let fraction = value / 10.0_f64.powi(exponent);
+    let mantissa = if round {
+        if fraction < 1.5 {
+            1.0
+        } else if fraction < 3.0 {
+            2.0
+        } else if fraction < 7.0 {
+            5.0
+        } else {
+            10.0
+        }
+    } else if fraction <= 1.0 {
+        1.0
+    } else if fraction <= 2.0 {
+        2.0
+    } else if fraction <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    (mantissa, exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        format_tick, log_ticks_with_minor, nice_range, ticks, ticks_with_minor, TickFormat,
+    };
+    use crate::geometry::Range;
+
+    #[test]
+    fn thousand() {
+        let t = ticks(&Range::new(0.0, 1000.0), 5);
+        assert_ticks(&[0.0, 200.0, 400.0, 600.0, 800.0, 1000.0], &t);
+    }
+
+    #[test]
+    fn crossing_zero() {
+        let t = ticks(&Range::new(-3.0, 7.0), 5);
+        assert_ticks(&[-4.0, -2.0, 0.0, 2.0, 4.0, 6.0, 8.0], &t);
+    }
+
+    #[test]
+    fn small_values() {
+        let t = ticks(&Range::new(0.001, 0.005), 5);
+        assert_ticks(&[0.001, 0.002, 0.003, 0.004, 0.005], &t);
+    }
+
+    #[test]
+    fn negative_and_huge() {
+        let t = ticks(&Range::new(-5.0e12, -1.0e12), 3);
+        assert_ticks(&[-6.0e12, -4.0e12, -2.0e12, 0.0], &t);
+
+        let t = ticks(&Range::new(10.0, 0.0), 3);
+        assert_ticks(&[0.0, 5.0, 10.0], &t);
+    }
+
+    #[test]
+    fn nice_ranges() {
+        let range = nice_range(3.0, 97.0);
+        assert_eq!((0.0, 100.0), (range.begin(), range.end()));
+
+        let range = nice_range(-0.3, 4.2);
+        assert_eq!((-1.0, 5.0), (range.begin(), range.end()));
+
+        // Reversed, or already round:
+        let range = nice_range(1000.0, 0.0);
+        assert_eq!((0.0, 1000.0), (range.begin(), range.end()));
+
+        let range = nice_range(3.0, 3.0);
+        assert!(range.begin() <= 2.5 && range.end() >= 3.5);
+
+        let range = nice_range(f64::NAN, 1.0);
+        assert_eq!((0.0, 1.0), (range.begin(), range.end()));
+    }
+
+    #[test]
+    fn linear_minor_ticks() {
+        let t = ticks_with_minor(&Range::new(0.0, 800.0), 5, 4);
+        assert_ticks(&[0.0, 200.0, 400.0, 600.0, 800.0], &t.major);
+        let expected: Vec<f64> = (0..=20)
+            .filter(|i| i % 5 != 0)
+            .map(|i| i as f64 * 40.0)
+            .collect();
+        assert_ticks(&expected, &t.minor);
+
+        let t = ticks_with_minor(&Range::new(0.0, 800.0), 5, 0);
+        assert_eq!(5, t.major.len());
+        assert!(t.minor.is_empty());
+    }
+
+    #[test]
+    fn log_minor_ticks() {
+        let t = log_ticks_with_minor(&Range::new(1.0, 100.0));
+        assert_ticks(&[1.0, 10.0, 100.0], &t.major);
+        assert_ticks(
+            &[
+                2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0,
+                90.0,
+            ],
+            &t.minor,
+        );
+    }
+
+    #[test]
+    fn si_prefixes() {
+        assert_eq!("1.23M", format_tick(1234567.0, TickFormat::SiPrefix(3)));
+        assert_eq!("123µ", format_tick(0.000123, TickFormat::SiPrefix(3)));
+        assert_eq!("-1.5k", format_tick(-1500.0, TickFormat::SiPrefix(3)));
+        assert_eq!("1k", format_tick(999.96, TickFormat::SiPrefix(3)));
+        assert_eq!("42", format_tick(42.0, TickFormat::SiPrefix(3)));
+        assert_eq!("0", format_tick(0.0, TickFormat::SiPrefix(3)));
+    }
+
+    #[test]
+    fn other_formats() {
+        assert_eq!("1.23", format_tick(1.23456, TickFormat::Fixed(2)));
+        assert_eq!("2.00", format_tick(2.0, TickFormat::Fixed(2)));
+        assert_eq!("1500", format_tick(1500.0, TickFormat::Fixed(0)));
+        assert_eq!("1.23e6", format_tick(1234567.0, TickFormat::Scientific(3)));
+        assert_eq!("1e-3", format_tick(0.001, TickFormat::Scientific(3)));
+        assert_eq!("0.25", format_tick(0.25, TickFormat::Plain));
+    }
+
+    fn assert_ticks(expected: &[f64], actual: &[f64]) {
+        assert_eq!(expected.len(), actual.len(), "{:?}", actual);
+        for (e, a) in expected.iter().zip(actual.iter()) {
+            assert!((e - a).abs() <= e.abs() * 1.0e-12, "{:?}", actual);
+        }
+    }
+}
@@ -2,53 +2,218 @@
 
 use super::ChartLayout;
 use crate::chart::ValueAxis;
+use crate::geometry::{Point, Range, Rect};
 use crate::time::TimeStamp;
 
+/// An affine transformation of 2D coordinates.
+///
+/// A point (x, y) is mapped onto:
+/// (xx * x + xy * y + x0, yx * x + yy * y + y0)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transform {
+    xx: f64,
+    yx: f64,
+    xy: f64,
+    yy: f64,
+    x0: f64,
+    y0: f64,
+}
+
+impl Transform {
+    pub fn new(xx: f64, yx: f64, xy: f64, yy: f64, x0: f64, y0: f64) -> Self {
+        Transform {
+            xx,
+            yx,
+            xy,
+            yy,
+            x0,
+            y0,
+        }
+    }
+
+    /// The transform which leaves every point where it is.
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    pub fn translation(dx: f64, dy: f64) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, dx, dy)
+    }
+
+    pub fn scaling(sx: f64, sy: f64) -> Self {
+        Self::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    /// Rotate around the origin by the given angle in radians.
+    ///
+    /// The x axis is rotated towards the y axis, which is clockwise
+    /// on screen, since screen y grows downward.
+    pub fn rotation(radians: f64) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// Create the transform from data coordinates to screen pixels.
+    ///
+    /// The x range is mapped from left to right onto the plot rectangle.
+    /// Since screen y grows downward, the y axis is flipped: the begin of
+    /// the y range ends up at the bottom of the plot rectangle, and the end
+    /// at the top.
+    ///
+    /// Use `invert` on the result to go from screen pixels back to data,
+    /// for example to find the data point under the mouse. Empty ranges
+    /// result in a transform which cannot be inverted.
+    pub fn from_ranges(data_x: &Range<f64>, data_y: &Range<f64>, plot_rect: &Rect) -> Self {
+        let x_domain = data_x.end() - data_x.begin();
+        let y_domain = data_y.end() - data_y.begin();
+        let sx = if x_domain == 0.0 {
+            0.0
+        } else {
+            plot_rect.width() / x_domain
+        };
+        let sy = if y_domain == 0.0 {
+            0.0
+        } else {
+            -plot_rect.height() / y_domain
+        };
+        Transform::new(
+            sx,
+            0.0,
+            0.0,
+            sy,
+            plot_rect.left() - sx * data_x.begin(),
+            plot_rect.bottom() - sy * data_y.begin(),
+        )
+    }
+
+    /// Combine two transforms into one.
+    ///
+    /// The resulting transform first applies `other`, and then `self`,
+    /// just like the mathematical composition `self ∘ other`.
+    pub fn compose(&self, other: &Transform) -> Transform {
+        Transform {
+            xx: self.xx * other.xx + self.xy * other.yx,
+            yx: self.yx * other.xx + self.yy * other.yx,
+            xy: self.xx * other.xy + self.xy * other.yy,
+            yy: self.yx * other.xy + self.yy * other.yy,
+            x0: self.xx * other.x0 + self.xy * other.y0 + self.x0,
+            y0: self.yx * other.x0 + self.yy * other.y0 + self.y0,
+        }
+    }
+
+    /// Chain two transforms: first apply `self`, and then `other`.
+    ///
+    /// For example, rotating around a point is done by translating that
+    /// point to the origin, rotating, and translating back again.
+    pub fn then(&self, other: &Transform) -> Transform {
+        other.compose(self)
+    }
+
+    /// Calculate the inverse transform, if this transform can be inverted.
+    pub fn invert(&self) -> Option<Transform> {
+        // Compare with the size of the terms, so tiny scales can be inverted,
+        // but the rounding error of nearly singular matrices cannot:
+        let determinant = self.xx * self.yy - self.xy * self.yx;
+        let scale = (self.xx * self.yy).abs() + (self.xy * self.yx).abs();
+        if determinant.abs() <= f64::EPSILON * scale || !determinant.is_finite() {
+            return None;
+        }
+
+        let xx = self.yy / determinant;
+        let yx = -self.yx / determinant;
+        let xy = -self.xy / determinant;
+        let yy = self.xx / determinant;
+        let x0 = -(xx * self.x0 + xy * self.y0);
+        let y0 = -(yx * self.x0 + yy * self.y0);
+        Some(Transform::new(xx, yx, xy, yy, x0, y0))
+    }
+
+    /// Test if x only depends on x and y only on y, so there is no rotation or shear.
+    pub fn is_axis_aligned(&self) -> bool {
+        self.yx == 0.0 && self.xy == 0.0
+    }
+
+    pub fn apply_point(&self, point: Point) -> Point {
+        Point::new(
+            self.xx * point.x() + self.xy * point.y() + self.x0,
+            self.yx * point.x() + self.yy * point.y() + self.y0,
+        )
+    }
+
+    /// Transform a rectangle, and return the bounding box of the result.
+    ///
+    /// The returned rectangle always has a non-negative width and height,
+    /// even when the transform flips an axis.
+    pub fn apply_rect(&self, rect: &Rect) -> Rect {
+        let corners = [
+            self.apply_point(Point::new(rect.left(), rect.top())),
+            self.apply_point(Point::new(rect.right(), rect.top())),
+            self.apply_point(Point::new(rect.right(), rect.bottom())),
+            self.apply_point(Point::new(rect.left(), rect.bottom())),
+        ];
+        let left = corners.iter().map(|p| p.x()).fold(f64::INFINITY, f64::min);
+        let right = corners
+            .iter()
+            .map(|p| p.x())
+            .fold(f64::NEG_INFINITY, f64::max);
+        let top = corners.iter().map(|p| p.y()).fold(f64::INFINITY, f64::min);
+        let bottom = corners
+            .iter()
+            .map(|p| p.y())
+            .fold(f64::NEG_INFINITY, f64::max);
+        Rect::new(left, top, right - left, bottom - top)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
 /// Calculate how many domain values a covered by the given amount of pixels.
+///
+/// The pixels are counted from the left of the plot, since on a logarithmic
+/// axis the same amount of pixels covers more values further to the right.
 pub fn x_pixels_to_domain(layout: &ChartLayout, axis: &ValueAxis, pixels: f64) -> f64 {
-    let domain = axis.domain();
-    if layout.plot_width < 1.0 {
-        0.0
-    } else {
-        let a = domain / layout.plot_width;
-        pixels * a
-    }
+    let start = layout.plot_left;
+    x_pixel_to_domain(start + pixels, axis, layout) - x_pixel_to_domain(start, axis, layout)
 }
 
 pub fn x_pixel_to_domain(pixel: f64, axis: &ValueAxis, layout: &ChartLayout) -> f64 {
-    let domain = axis.domain();
     if layout.plot_width < 1.0 {
         0.0
     } else {
-        let a = domain / layout.plot_width;
-        a * (pixel - layout.plot_left) + axis.begin()
+        axis.fraction_to_value((pixel - layout.plot_left) / layout.plot_width)
     }
 }
 
 /// Take an y pixel and transform it to a domain value on the given axis.
 pub fn y_pixel_to_domain(pixel: f64, axis: &ValueAxis, layout: &ChartLayout) -> f64 {
-    let domain = axis.domain();
     if layout.plot_height < 1.0 {
         0.0
     } else {
-        let a = domain / layout.plot_height;
-        axis.begin() - a * (pixel - layout.plot_bottom)
+        axis.fraction_to_value((layout.plot_bottom - pixel) / layout.plot_height)
     }
 }
 
+/// Convert an x value into a pixel value.
+///
+/// Values which cannot be shown on the axis, such as non-positive
+/// values on a logarithmic axis, are clipped to the left.
 pub fn x_domain_to_pixel(t: &TimeStamp, axis: &ValueAxis, layout: &ChartLayout) -> f64 {
-    let x = t.amount;
-    let domain = axis.domain();
-    let a = (layout.plot_width) / domain;
-    let x_pixel = a * (x - axis.begin()) + layout.plot_left;
+    let fraction = axis.value_to_fraction(t.amount).unwrap_or(0.0);
+    let x_pixel = layout.plot_left + fraction * layout.plot_width;
     clip(x_pixel, layout.plot_left, layout.plot_right)
 }
 
 /// Convert a y value into a proper pixel y value given an axis and a chart layout.
+///
+/// Values which cannot be shown on the axis, such as non-positive
+/// values on a logarithmic axis, are clipped to the bottom.
 pub fn y_domain_to_pixel(y: f64, axis: &ValueAxis, layout: &ChartLayout) -> f64 {
-    let domain = axis.domain();
-    let a = layout.plot_height / domain;
-    let y_pixel = layout.plot_bottom - a * (y - axis.begin());
+    let fraction = axis.value_to_fraction(y).unwrap_or(0.0);
+    let y_pixel = layout.plot_bottom - fraction * layout.plot_height;
     clip(y_pixel, layout.plot_top, layout.plot_bottom)
 }
 
@@ -66,10 +231,11 @@ fn clip(value: f64, lower: f64, upper: f64) -> f64 {
 #[cfg(test)]
 mod tests {
     use super::super::{ChartLayout, ChartOptions};
-    use super::{x_domain_to_pixel, x_pixel_to_domain};
+    use super::Transform;
+    use super::{x_domain_to_pixel, x_pixel_to_domain, x_pixels_to_domain};
     use super::{y_domain_to_pixel, y_pixel_to_domain};
-    use crate::chart::ValueAxis;
-    use crate::geometry::Size;
+    use crate::chart::{AxisScale, ValueAxis};
+    use crate::geometry::{Point, Range, Rect, Size};
     use crate::time::TimeStamp;
 
     #[test]
@@ -89,6 +255,25 @@ mod tests {
         assert_almost_eq(value.amount, value2, 1.0e-9);
     }
 
+    #[test]
+    fn x_pixels_on_log_axis() {
+        let mut axis = ValueAxis::default();
+        axis.options.scale = AxisScale::Log10;
+        axis.set_limits(1.0, 1000.0);
+        let options = ChartOptions::default();
+        let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+        layout.layout(&options);
+
+        // A third of the plot spans a decade, from 1 to 10:
+        let third = layout.plot_width / 3.0;
+        assert_almost_eq(9.0, x_pixels_to_domain(&layout, &axis, third), 1.0e-9);
+        assert_almost_eq(-0.9, x_pixels_to_domain(&layout, &axis, -third), 1.0e-9);
+        assert_almost_eq(0.0, x_pixels_to_domain(&layout, &axis, 0.0), 1.0e-9);
+
+        axis.options.scale = AxisScale::Linear;
+        assert_almost_eq(333.0, x_pixels_to_domain(&layout, &axis, third), 1.0e-9);
+    }
+
     #[test]
     fn y_axis_roundtrips() {
         let mut axis = ValueAxis::default();
@@ -106,6 +291,113 @@ mod tests {
         assert_almost_eq(value, value2, 1.0e-9);
     }
 
+    #[test]
+    fn transform_invert_roundtrips() {
+        let transform = Transform::new(2.0, 0.5, -1.0, 3.0, 10.0, -20.0);
+        let inverse = transform.invert().unwrap();
+
+        let point = Point::new(7.0, -3.0);
+        let point2 = inverse.apply_point(transform.apply_point(point));
+        assert_almost_eq(point.x(), point2.x(), 1.0e-9);
+        assert_almost_eq(point.y(), point2.y(), 1.0e-9);
+
+        assert!(Transform::scaling(0.0, 1.0).invert().is_none());
+        assert!(Transform::new(1.0, 2.0, 2.0, 4.0, 0.0, 0.0)
+            .invert()
+            .is_none());
+
+        // Data in nanoseconds on a small screen has tiny scale factors:
+        let transform = Transform::scaling(1.0e-9, 1.0e-10);
+        let inverse = transform.invert().unwrap();
+        assert_almost_eq(3.0e9, inverse.apply_point(Point::new(3.0, 0.0)).x(), 1.0e-3);
+        assert!(Transform::scaling(f64::INFINITY, 1.0).invert().is_none());
+    }
+
+    #[test]
+    fn transform_composition_order() {
+        let scale = Transform::scaling(2.0, 2.0);
+        let translate = Transform::translation(10.0, 0.0);
+
+        // First translate, then scale:
+        let p = scale.compose(&translate).apply_point(Point::new(1.0, 1.0));
+        assert_eq!(Point::new(22.0, 2.0), p);
+
+        // First scale, then translate:
+        let p = translate.compose(&scale).apply_point(Point::new(1.0, 1.0));
+        assert_eq!(Point::new(12.0, 2.0), p);
+    }
+
+    #[test]
+    fn rotation() {
+        let p = Transform::rotation(std::f64::consts::FRAC_PI_2).apply_point(Point::new(1.0, 0.0));
+        assert_almost_eq(0.0, p.x(), 1.0e-12);
+        assert_almost_eq(1.0, p.y(), 1.0e-12);
+
+        // Rotate a quarter turn around (10, 5):
+        let transform = Transform::translation(-10.0, -5.0)
+            .then(&Transform::rotation(std::f64::consts::FRAC_PI_2))
+            .then(&Transform::translation(10.0, 5.0));
+        let p = transform.apply_point(Point::new(12.0, 5.0));
+        assert_almost_eq(10.0, p.x(), 1.0e-12);
+        assert_almost_eq(7.0, p.y(), 1.0e-12);
+        let p = transform.apply_point(Point::new(10.0, 5.0));
+        assert_almost_eq(10.0, p.x(), 1.0e-12);
+        assert_almost_eq(5.0, p.y(), 1.0e-12);
+    }
+
+    #[test]
+    fn chaining_and_scaling() {
+        let transform = Transform::scaling(2.0, 3.0).then(&Transform::translation(1.0, -1.0));
+        assert_eq!(
+            Point::new(3.0, 2.0),
+            transform.apply_point(Point::new(1.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn transform_rect_with_flipped_axis() {
+        let transform = Transform::scaling(2.0, -1.0);
+        let rect = transform.apply_rect(&Rect::new(1.0, 1.0, 2.0, 3.0));
+        assert_eq!(Rect::new(2.0, -4.0, 4.0, 3.0), rect);
+    }
+
+    #[test]
+    fn transform_from_ranges_maps_corners() {
+        let data_x = Range::new(-5.0, 15.0);
+        let data_y = Range::new(100.0, 200.0);
+        let plot_rect = Rect::new(50.0, 10.0, 400.0, 300.0);
+        let transform = Transform::from_ranges(&data_x, &data_y, &plot_rect);
+        let inverse = transform.invert().unwrap();
+
+        let corners = vec![
+            (
+                (data_x.begin(), data_y.begin()),
+                (plot_rect.left(), plot_rect.bottom()),
+            ),
+            (
+                (data_x.end(), data_y.begin()),
+                (plot_rect.right(), plot_rect.bottom()),
+            ),
+            (
+                (data_x.end(), data_y.end()),
+                (plot_rect.right(), plot_rect.top()),
+            ),
+            (
+                (data_x.begin(), data_y.end()),
+                (plot_rect.left(), plot_rect.top()),
+            ),
+        ];
+        for ((x, y), (pixel_x, pixel_y)) in corners {
+            let pixel = transform.apply_point(Point::new(x, y));
+            assert_almost_eq(pixel_x, pixel.x(), 1.0e-9);
+            assert_almost_eq(pixel_y, pixel.y(), 1.0e-9);
+
+            let data = inverse.apply_point(pixel);
+            assert_almost_eq(x, data.x(), 1.0e-9);
+            assert_almost_eq(y, data.y(), 1.0e-9);
+        }
+    }
+
     fn assert_almost_eq(v1: f64, v2: f64, tolerance: f64) {
         assert!((v1 - v2).abs() < tolerance);
     }
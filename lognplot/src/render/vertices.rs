@@ -0,0 +1,219 @@
+//! Line geometry of series, as flat vertex buffers for a GPU.
+//!
+//! Instead of drawing onto a `Canvas`, the series is turned into screen
+//! space vertices, which can be uploaded to a vertex buffer as is. The
+//! lines are clipped to the plot area, so no clipping is needed later on.
+
+use super::{Series, Transform};
+use crate::geometry::{Point, Rect};
+
+/// Index which ends a line strip, also known as primitive restart index.
+pub const PRIMITIVE_RESTART: u32 = u32::MAX;
+
+/// How the vertices make up lines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Topology {
+    /// Each pair of vertices is a line segment, there are no indices.
+    LineList,
+
+    /// Connected lines, given by indices, with `PRIMITIVE_RESTART` between two strips.
+    LineStrip,
+}
+
+/// Vertices as x and y pairs in pixels, with optional indices.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VertexBuffer {
+    pub vertices: Vec<f32>,
+    pub indices: Vec<u32>,
+}
+
+impl VertexBuffer {
+    /// Amount of vertices, each of which takes two floats.
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len() / 2
+    }
+
+    fn push(&mut self, point: &Point) {
+        self.vertices.push(point.x() as f32);
+        self.vertices.push(point.y() as f32);
+    }
+}
+
+/// Turn the line of a series into vertices, clipped to the given rectangle.
+///
+/// Points with non-finite values break the line, like in `draw_series`, and
+/// so does leaving the clip rectangle. Invisible series result in an empty
+/// buffer.
+pub fn series_to_vertices(
+    series: &Series,
+    transform: &Transform,
+    clip: &Rect,
+    topology: Topology,
+) -> VertexBuffer {
+    let mut buffer = VertexBuffer::default();
+    if !series.visible {
+        return buffer;
+    }
+
+    for strip in clipped_strips(series, transform, clip) {
+        match topology {
+            Topology::LineList => {
+                for segment in strip.windows(2) {
+                    buffer.push(&segment[0]);
+                    buffer.push(&segment[1]);
+                }
+            }
+            Topology::LineStrip => {
+                if !buffer.indices.is_empty() {
+                    buffer.indices.push(PRIMITIVE_RESTART);
+                }
+                for point in &strip {
+                    buffer.indices.push(buffer.vertex_count() as u32);
+                    buffer.push(point);
+                }
+            }
+        }
+    }
+
+    buffer
+}
+
+/// Split the line of a series into connected parts within the clip rectangle.
+fn clipped_strips(series: &Series, transform: &Transform, clip: &Rect) -> Vec<Vec<Point>> {
+    let mut strips = vec![];
+    let runs = series
+        .points
+        .split(|(x, y)| !x.is_finite() || !y.is_finite());
+    for run in runs {
+        let points: Vec<Point> = series
+            .interpolation
+            .apply(run)
+            .into_iter()
+            .map(|(x, y)| transform.apply_point(Point::new(x, y)))
+            .collect();
+
+        let mut strip: Vec<Point> = vec![];
+        for segment in points.windows(2) {
+            match clip_segment(&segment[0], &segment[1], clip) {
+                Some((start, end)) => {
+                    // Continue the strip when the segment starts where the last one ended:
+                    if !strip.is_empty() && strip.last() != Some(&start) {
+                        strips.push(std::mem::take(&mut strip));
+                    }
+                    if strip.is_empty() {
+                        strip.push(start);
+                    }
+                    strip.push(end);
+                }
+                None if !strip.is_empty() => strips.push(std::mem::take(&mut strip)),
+                None => {}
+            }
+        }
+        if !strip.is_empty() {
+            strips.push(strip);
+        }
+    }
+    strips
+}
+
+/// Clip a line segment to a rectangle, using the Liang-Barsky algorithm.
+///
+/// Returns the part of the segment within the rectangle, or None when the
+/// segment lies outside the rectangle. Ends inside the rectangle are
+/// returned unchanged.
+fn clip_segment(p0: &Point, p1: &Point, clip: &Rect) -> Option<(Point, Point)> {
+    let dx = p1.x() - p0.x();
+    let dy = p1.y() - p0.y();
+    let mut t0: f64 = 0.0;
+    let mut t1: f64 = 1.0;
+    let edges = [
+        (-dx, p0.x() - clip.left()),
+        (dx, clip.right() - p0.x()),
+        (-dy, p0.y() - clip.top()),
+        (dy, clip.bottom() - p0.y()),
+    ];
+    for &(p, q) in edges.iter() {
+        if p == 0.0 {
+            // Parallel to this edge, and entirely on the outside:
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+    }
+
+    if t0 > t1 {
+        return None;
+    }
+
+    let at = |t: f64| {
+        if t == 0.0 {
+            *p0
+        } else if t == 1.0 {
+            *p1
+        } else {
+            Point::new(p0.x() + t * dx, p0.y() + t * dy)
+        }
+    };
+    Some((at(t0), at(t1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{series_to_vertices, Topology, PRIMITIVE_RESTART};
+    use crate::geometry::Rect;
+    use crate::render::{Series, Transform};
+    use crate::style::Color;
+
+    #[test]
+    fn inside() {
+        let series = Series::new("a", vec![(1.0, 1.0), (2.0, 4.0), (3.0, 2.0)], Color::red());
+        let clip = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let transform = Transform::identity();
+
+        let strip = series_to_vertices(&series, &transform, &clip, Topology::LineStrip);
+        assert_eq!(3, strip.vertex_count());
+        assert_eq!(vec![1.0, 1.0, 2.0, 4.0, 3.0, 2.0], strip.vertices);
+        assert_eq!(vec![0, 1, 2], strip.indices);
+
+        let list = series_to_vertices(&series, &transform, &clip, Topology::LineList);
+        assert_eq!(4, list.vertex_count());
+        assert!(list.indices.is_empty());
+    }
+
+    #[test]
+    fn clipped() {
+        // Leaves the clip rectangle on the right, and comes back in:
+        let series = Series::new(
+            "a",
+            vec![(0.0, 5.0), (5.0, 5.0), (15.0, 5.0), (20.0, 5.0), (5.0, 2.0)],
+            Color::red(),
+        );
+        let clip = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let buffer =
+            series_to_vertices(&series, &Transform::identity(), &clip, Topology::LineStrip);
+
+        assert_eq!(5, buffer.vertex_count());
+        assert_eq!(
+            vec![0.0, 5.0, 5.0, 5.0, 10.0, 5.0, 10.0, 3.0, 5.0, 2.0],
+            buffer.vertices
+        );
+        assert_eq!(vec![0, 1, 2, PRIMITIVE_RESTART, 3, 4], buffer.indices);
+        assert!(buffer.vertices.iter().all(|v| *v >= 0.0 && *v <= 10.0));
+
+        // Entirely off screen, or broken up by gaps:
+        let series = Series::new(
+            "b",
+            vec![(20.0, 5.0), (30.0, 5.0), (f64::NAN, 1.0), (1.0, 1.0)],
+            Color::red(),
+        );
+        let buffer = series_to_vertices(&series, &Transform::identity(), &clip, Topology::LineList);
+        assert_eq!(0, buffer.vertex_count());
+    }
+}
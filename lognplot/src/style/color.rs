@@ -1,7 +1,7 @@
 use std::str::FromStr;
 
 /// Color indication
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Color {
     r: u8,
     g: u8,
@@ -44,6 +44,69 @@ impl Color {
     pub fn b(&self) -> u8 {
         self.b
     }
+
+    /// Paint this color, with the given alpha, over an opaque background color.
+    ///
+    /// This is the source-over compositing operation.
+    pub fn blend_over(&self, alpha: f64, background: &Color) -> Color {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let mix = |top: u8, bottom: u8| -> u8 {
+            (top as f64 * alpha + bottom as f64 * (1.0 - alpha)).round() as u8
+        };
+        Color::new(
+            mix(self.r, background.r),
+            mix(self.g, background.g),
+            mix(self.b, background.b),
+        )
+    }
+
+    /// Relative luminance, from 0 for black to 1 for white, as defined by WCAG 2.
+    pub fn relative_luminance(&self) -> f64 {
+        let linear = |channel: u8| -> f64 {
+            let value = channel as f64 / 255.0;
+            if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// Contrast ratio between two colors, from 1 for equal luminance up to 21.
+    pub fn contrast_ratio(&self, other: &Color) -> f64 {
+        let a = self.relative_luminance() + 0.05;
+        let b = other.relative_luminance() + 0.05;
+        a.max(b) / a.min(b)
+    }
+
+    /// Gray with the same relative luminance as this color.
+    pub fn to_grayscale(&self) -> Color {
+        let luminance = self.relative_luminance();
+        let encoded = if luminance <= 0.0031308 {
+            luminance * 12.92
+        } else {
+            1.055 * luminance.powf(1.0 / 2.4) - 0.055
+        };
+        let level = (encoded * 255.0).round().clamp(0.0, 255.0) as u8;
+        Color::new(level, level, level)
+    }
+
+    /// Reduce the saturation, from 0 keeping the color as is, to 1 for `to_grayscale`.
+    pub fn desaturate(&self, amount: f64) -> Color {
+        self.to_grayscale().blend_over(amount, self)
+    }
+
+    /// Black or white, whichever is the most readable as text on this background color.
+    pub fn contrasting_text(&self) -> Color {
+        let black = Color::black();
+        let white = Color::white();
+        if self.contrast_ratio(&black) >= self.contrast_ratio(&white) {
+            black
+        } else {
+            white
+        }
+    }
 }
 
 impl Default for Color {
@@ -79,3 +142,55 @@ impl FromStr for Color {
         Ok(Self::new(r, g, b))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Color;
+
+    #[test]
+    fn luminance() {
+        assert_eq!(0.0, Color::black().relative_luminance());
+        assert!((Color::white().relative_luminance() - 1.0).abs() < 1.0e-9);
+        assert!((Color::red().relative_luminance() - 0.2126).abs() < 1.0e-9);
+        assert!((Color::black().contrast_ratio(&Color::white()) - 21.0).abs() < 1.0e-9);
+    }
+
+    #[test]
+    fn contrasting_text() {
+        // Dark backgrounds:
+        assert_eq!(Color::white(), Color::black().contrasting_text());
+        assert_eq!(Color::white(), Color::new(0, 0, 255).contrasting_text());
+        assert_eq!(Color::white(), Color::new(40, 40, 60).contrasting_text());
+
+        // Light backgrounds:
+        assert_eq!(Color::black(), Color::white().contrasting_text());
+        assert_eq!(Color::black(), Color::green().contrasting_text());
+        assert_eq!(Color::black(), Color::new(255, 255, 200).contrasting_text());
+    }
+
+    #[test]
+    fn grayscale() {
+        let red = Color::red();
+        let gray = red.to_grayscale();
+        assert_eq!(gray.r(), gray.g());
+        assert_eq!(gray.r(), gray.b());
+        assert!((gray.relative_luminance() - red.relative_luminance()).abs() < 0.005);
+        assert_eq!(Color::new(127, 127, 127), gray);
+
+        // Grays stay the same:
+        assert_eq!(Color::gray(), Color::gray().to_grayscale());
+        assert_eq!(Color::white(), Color::white().to_grayscale());
+    }
+
+    #[test]
+    fn desaturate() {
+        let red = Color::red();
+        assert_eq!(red, red.desaturate(0.0));
+        assert_eq!(red.to_grayscale(), red.desaturate(1.0));
+
+        // Halfway towards gray:
+        let half = red.desaturate(0.5);
+        assert_eq!(Color::new(191, 64, 64), half);
+        assert!(half.r() < red.r() && half.g() > red.g());
+    }
+}
@@ -0,0 +1,127 @@
+use super::Color;
+
+/// A mapping from values between 0 and 1 to colors.
+///
+/// The perceptual colormaps are sampled from the matplotlib lookup tables.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Colormap {
+    /// Perceptually uniform map from dark blue via green to yellow.
+    Viridis,
+
+    /// Perceptually uniform map from black via purple to light yellow.
+    Magma,
+
+    /// Perceptually uniform map from blue via purple to yellow.
+    Plasma,
+
+    /// Map from black to white.
+    Grayscale,
+}
+
+/// Samples of the colormaps, at equal distances.
+const VIRIDIS: [(u8, u8, u8); 9] = [
+    (68, 1, 84),
+    (71, 44, 122),
+    (59, 81, 139),
+    (44, 113, 142),
+    (33, 144, 141),
+    (39, 173, 129),
+    (92, 200, 99),
+    (170, 220, 50),
+    (253, 231, 37),
+];
+
+const MAGMA: [(u8, u8, u8); 9] = [
+    (0, 0, 4),
+    (28, 16, 68),
+    (79, 18, 123),
+    (129, 37, 129),
+    (181, 54, 122),
+    (229, 80, 100),
+    (251, 135, 97),
+    (254, 194, 135),
+    (252, 253, 191),
+];
+
+const PLASMA: [(u8, u8, u8); 9] = [
+    (13, 8, 135),
+    (76, 2, 161),
+    (126, 3, 168),
+    (169, 35, 149),
+    (204, 71, 120),
+    (230, 108, 92),
+    (248, 149, 64),
+    (253, 197, 39),
+    (240, 249, 33),
+];
+
+const GRAYSCALE: [(u8, u8, u8); 2] = [(0, 0, 0), (255, 255, 255)];
+
+impl Colormap {
+    /// Get the color at t, which is limited to the range 0 to 1.
+    pub fn sample(&self, t: f64) -> Color {
+        let table: &[(u8, u8, u8)] = match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Magma => &MAGMA,
+            Colormap::Plasma => &PLASMA,
+            Colormap::Grayscale => &GRAYSCALE,
+        };
+        interpolate(table, t)
+    }
+}
+
+/// Interpolate linearly between equally spaced colors.
+fn interpolate(table: &[(u8, u8, u8)], t: f64) -> Color {
+    let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+    let position = t * (table.len() - 1) as f64;
+    let index = (position.floor() as usize).min(table.len() - 2);
+    let fraction = position - index as f64;
+    let (r1, g1, b1) = table[index];
+    let (r2, g2, b2) = table[index + 1];
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * fraction).round() as u8;
+    Color::new(mix(r1, r2), mix(g1, g2), mix(b1, b2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Colormap;
+    use crate::style::Color;
+
+    fn luminance(color: &Color) -> f64 {
+        0.2126 * color.r() as f64 + 0.7152 * color.g() as f64 + 0.0722 * color.b() as f64
+    }
+
+    #[test]
+    fn grayscale() {
+        assert_eq!(Color::black(), Colormap::Grayscale.sample(0.0));
+        assert_eq!(Color::new(128, 128, 128), Colormap::Grayscale.sample(0.5));
+        assert_eq!(Color::white(), Colormap::Grayscale.sample(1.0));
+        assert_eq!(Color::white(), Colormap::Grayscale.sample(7.0));
+        assert_eq!(Color::black(), Colormap::Grayscale.sample(f64::NAN));
+    }
+
+    #[test]
+    fn endpoints() {
+        assert_eq!(Color::new(68, 1, 84), Colormap::Viridis.sample(0.0));
+        assert_eq!(Color::new(33, 144, 141), Colormap::Viridis.sample(0.5));
+        assert_eq!(Color::new(253, 231, 37), Colormap::Viridis.sample(1.0));
+        assert_eq!(Color::new(0, 0, 4), Colormap::Magma.sample(0.0));
+        assert_eq!(Color::new(252, 253, 191), Colormap::Magma.sample(1.0));
+        assert_eq!(Color::new(13, 8, 135), Colormap::Plasma.sample(-1.0));
+        assert_eq!(Color::new(240, 249, 33), Colormap::Plasma.sample(1.0));
+    }
+
+    #[test]
+    fn monotonic_luminance() {
+        for colormap in &[Colormap::Grayscale, Colormap::Viridis, Colormap::Magma] {
+            let luminances: Vec<f64> = (0..=100)
+                .map(|i| luminance(&colormap.sample(i as f64 / 100.0)))
+                .collect();
+            assert!(
+                luminances.windows(2).all(|w| w[0] <= w[1]),
+                "{:?}",
+                colormap
+            );
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use super::Color;
+use crate::geometry::Point;
+
+/// The shape of a gradient.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GradientKind {
+    /// Colors change along the line from start to end.
+    Linear { start: Point, end: Point },
+
+    /// Colors change from the center outwards.
+    Radial { center: Point, radius: f64 },
+}
+
+/// A color at some position in the gradient.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorStop {
+    /// Offset in the gradient, between 0.0 and 1.0
+    pub offset: f64,
+    pub color: Color,
+    pub alpha: f64,
+}
+
+/// A color gradient, which can be used to fill shapes.
+///
+/// Coordinates are in canvas pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub stops: Vec<ColorStop>,
+}
+
+impl Gradient {
+    pub fn linear(start: Point, end: Point) -> Self {
+        Gradient {
+            kind: GradientKind::Linear { start, end },
+            stops: vec![],
+        }
+    }
+
+    pub fn radial(center: Point, radius: f64) -> Self {
+        Gradient {
+            kind: GradientKind::Radial { center, radius },
+            stops: vec![],
+        }
+    }
+
+    /// Add a color stop to this gradient.
+    pub fn add_stop(mut self, offset: f64, color: Color, alpha: f64) -> Self {
+        self.stops.push(ColorStop {
+            offset,
+            color,
+            alpha,
+        });
+        self
+    }
+}
@@ -2,7 +2,13 @@
 //! Elements here, are paint, stroke style, colors, etc..
 
 mod color;
+mod colormap;
+mod gradient;
 mod stroke;
+mod theme;
 
 pub use color::Color;
+pub use colormap::Colormap;
+pub use gradient::{ColorStop, Gradient, GradientKind};
 pub use stroke::Stroke;
+pub use theme::Theme;
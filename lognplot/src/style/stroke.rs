@@ -6,11 +6,52 @@ use std::str::FromStr;
 pub struct Stroke {
     pub color: Color,
     pub width: f64,
+
+    /// Opacity of the line, from 0.0 (invisible) to 1.0 (opaque).
+    pub opacity: f64,
+
+    /// Alternating lengths of dashes and gaps, empty for a solid line.
+    pub dash: Vec<f64>,
 }
 
 impl Stroke {
-    fn new(color: Color, width: f64) -> Self {
-        Stroke { color, width }
+    /// Create an opaque stroke.
+    pub fn new(color: Color, width: f64) -> Self {
+        Stroke {
+            color,
+            width,
+            opacity: 1.0,
+            dash: vec![],
+        }
+    }
+
+    /// Use the given opacity for this stroke.
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Use the given dash pattern, of alternating dash and gap lengths.
+    pub fn with_dash(mut self, dash: &[f64]) -> Self {
+        self.dash = dash.to_vec();
+        self
+    }
+
+    /// Test if this stroke has a usable dash pattern.
+    ///
+    /// The pattern must have at least one length, and all lengths must be
+    /// finite and positive. Strokes with another pattern are drawn solid.
+    pub fn is_dashed(&self) -> bool {
+        !self.dash.is_empty() && self.dash.iter().all(|d| d.is_finite() && *d > 0.0)
+    }
+
+    /// The dash pattern to draw this stroke with, empty for a solid line.
+    pub fn dash_pattern(&self) -> &[f64] {
+        if self.is_dashed() {
+            &self.dash
+        } else {
+            &[]
+        }
     }
 }
 
@@ -0,0 +1,67 @@
+use super::Color;
+
+/// Colors and font sizes used to draw a chart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// Fill of the whole canvas, around the plot area.
+    pub background: Color,
+
+    /// Fill of the plot area, below the curves.
+    pub plot_background: Color,
+
+    /// Color of the grid lines.
+    pub grid: Color,
+
+    /// Color of the tick markers, the plot frame and the cursor.
+    pub axis: Color,
+
+    /// Color of tick labels, the title and the legend.
+    pub text: Color,
+
+    /// Font size of labels and of the legend.
+    pub font_size: f64,
+}
+
+impl Theme {
+    /// Dark text and lines on a white background.
+    pub fn light() -> Self {
+        Theme {
+            background: Color::white(),
+            plot_background: Color::white(),
+            grid: Color::gray(),
+            axis: Color::black(),
+            text: Color::black(),
+            font_size: 10.0,
+        }
+    }
+
+    /// Light text and lines on a dark background.
+    pub fn dark() -> Self {
+        Theme {
+            background: Color::new(30, 30, 30),
+            plot_background: Color::new(20, 20, 20),
+            grid: Color::new(70, 70, 70),
+            axis: Color::new(200, 200, 200),
+            text: Color::new(230, 230, 230),
+            font_size: 10.0,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Theme;
+
+    #[test]
+    fn presets_differ() {
+        assert_eq!(Theme::light(), Theme::default());
+        assert_ne!(Theme::light().background, Theme::dark().background);
+        assert_ne!(Theme::light().text, Theme::dark().text);
+    }
+}
@@ -0,0 +1,146 @@
+//! Drawing with a canvas implemented outside of the crate.
+
+use lognplot::geometry::{Path, Point, Range, Rect, Size};
+use lognplot::render::{
+    draw_grid, Canvas, ChartLayout, ChartOptions, GridOptions, HorizontalAnchor, TextOrientation,
+    Transform, VerticalAnchor,
+};
+use lognplot::style::{Color, Gradient};
+
+/// A canvas outside of the render module, which only logs what is drawn.
+#[derive(Default)]
+struct MockCanvas {
+    calls: Vec<String>,
+}
+
+impl Canvas for MockCanvas {
+    fn set_pen(&mut self, color: Color, alpha: f64) {
+        self.calls.push(format!("pen {:?} {}", color, alpha));
+    }
+
+    fn set_line_width(&mut self, width: f64) {
+        self.calls.push(format!("width {}", width));
+    }
+
+    fn print_text(
+        &mut self,
+        _p: &Point,
+        _horizontal_anchor: HorizontalAnchor,
+        _vertical_anchor: VerticalAnchor,
+        text: &str,
+    ) {
+        self.calls.push(format!("text {}", text));
+    }
+
+    fn text_size(&self, text: &str) -> Size {
+        Size::new(text.len() as f64 * 8.0, 16.0)
+    }
+
+    fn draw_line(&mut self, points: &[Point]) {
+        let points: Vec<String> = points
+            .iter()
+            .map(|p| format!("{},{}", p.x(), p.y()))
+            .collect();
+        self.calls.push(format!("line {}", points.join(" ")));
+    }
+
+    fn draw_polygon(&mut self, _points: &[Point]) {
+        self.calls.push("polygon".to_owned());
+    }
+
+    fn draw_circle(&mut self, _center: &Point, _radius: f64) {
+        self.calls.push("circle".to_owned());
+    }
+
+    fn draw_path(&mut self, _path: &Path) {
+        self.calls.push("path".to_owned());
+    }
+
+    fn fill_polygon(&mut self, _points: &[Point]) {
+        self.calls.push("fill polygon".to_owned());
+    }
+
+    fn fill_circle(&mut self, _center: &Point, _radius: f64) {
+        self.calls.push("fill circle".to_owned());
+    }
+
+    fn draw_image(
+        &mut self,
+        _top_left: Point,
+        _width: f64,
+        _height: f64,
+        _rgba: &[u8],
+        _img_w: u32,
+        _img_h: u32,
+    ) {
+        self.calls.push("image".to_owned());
+    }
+
+    fn set_gradient(&mut self, _gradient: &Gradient) {
+        self.calls.push("gradient".to_owned());
+    }
+
+    fn set_clip_rect(&mut self, rect: Rect) {
+        self.calls.push(format!(
+            "clip {} {} {} {}",
+            rect.left(),
+            rect.top(),
+            rect.width(),
+            rect.height()
+        ));
+    }
+
+    fn clear_clip(&mut self) {
+        self.calls.push("clear clip".to_owned());
+    }
+
+    fn save(&mut self) {
+        self.calls.push("save".to_owned());
+    }
+
+    fn restore(&mut self) {
+        self.calls.push("restore".to_owned());
+    }
+}
+
+#[test]
+fn external_canvas() {
+    let mut layout = ChartLayout::new(Size::new(800.0, 600.0));
+    layout.layout(&ChartOptions::default());
+    let transform = Transform::from_ranges(
+        &Range::new(0.0, 10.0),
+        &Range::new(0.0, 100.0),
+        &layout.plot_rect(),
+    );
+
+    let mut canvas = MockCanvas::default();
+    let options = GridOptions::default();
+    draw_grid(&mut canvas, &layout, &transform, &[5.0], &[50.0], &options);
+
+    let pen = format!("pen {:?} 1", options.stroke.color);
+    assert_eq!(
+        vec![
+            "save".to_owned(),
+            "clip 140 10 650 500".to_owned(),
+            pen.clone(),
+            "width 1".to_owned(),
+            "line 465,10 465,510".to_owned(),
+            pen,
+            "width 1".to_owned(),
+            "line 140,260 790,260".to_owned(),
+            "restore".to_owned(),
+        ],
+        canvas.calls
+    );
+
+    // Rotated text falls back to horizontal text:
+    canvas.calls.clear();
+    canvas.print_text_oriented(
+        &Point::new(10.0, 300.0),
+        HorizontalAnchor::Middle,
+        VerticalAnchor::Top,
+        TextOrientation::VerticalUp,
+        "volts",
+    );
+    assert_eq!(vec!["text volts".to_owned()], canvas.calls);
+}
@@ -7,7 +7,6 @@ mod memory;
 mod romtable;
 mod target;
 mod tpiu;
-mod trace_protocol;
 
 // Public API:
 pub use memory::{MemoryAccess, MemoryAddress};
@@ -23,8 +22,8 @@ use identification::read_identification;
 use itm::Itm;
 use itm::ITM_PID;
 use romtable::read_rom_table;
+pub use swvtrace::{TraceDataDecoder, TracePacket};
 use tpiu::{Tpiu, TPIU_PID};
-pub use trace_protocol::{TraceDataDecoder, TracePacket};
 
 pub type CoreSightResult<T> = Result<T, CoreSightError>;
 
@@ -0,0 +1,27 @@
+//! Conversion of timestamps into time.
+
+/// Convert an amount of timestamp ticks into seconds.
+///
+/// The frequency is that of the timestamp counter, which is not always the
+/// processor clock. The ITM increments its local timestamp counter on the
+/// processor clock, or on the TPIU clock when `SWOENA` is set in `ITM_TCR`,
+/// divided by the prescaler selected with `TSPrescale` in `ITM_TCR`, which
+/// is 1, 4, 16 or 64. So a 64 MHz processor with a prescaler of 4 gives a
+/// timestamp frequency of 16 MHz.
+pub fn ticks_to_seconds(ts: u64, trace_clock_hz: f64) -> f64 {
+    ts as f64 / trace_clock_hz
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ticks_to_seconds;
+
+    #[test]
+    fn one_megahertz() {
+        assert_eq!(1.5, ticks_to_seconds(1_500_000, 1.0e6));
+        assert_eq!(0.0, ticks_to_seconds(0, 1.0e6));
+
+        // A prescaler of 4 on a 64 MHz clock:
+        assert_eq!(1.0e-6, ticks_to_seconds(16, 64.0e6 / 4.0));
+    }
+}
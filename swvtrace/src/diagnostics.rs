@@ -0,0 +1,30 @@
+//! Diagnostics about the decoding, sent to `tracing` or `log`.
+//!
+//! With the `tracing` feature, events carry the packet kind and the offset
+//! of the byte in the stream as fields. Otherwise, the message is logged
+//! with the `log` crate, when its feature is enabled. Without either, the
+//! arguments are still checked, so all builds see the same code.
+
+#[cfg(feature = "tracing")]
+macro_rules! decode_event {
+    ($level:ident, $kind:expr, $offset:expr, $($arg:tt)+) => {
+        ::tracing::$level!(kind = $kind, offset = $offset, $($arg)+)
+    };
+}
+
+#[cfg(all(feature = "log", not(feature = "tracing")))]
+macro_rules! decode_event {
+    ($level:ident, $kind:expr, $offset:expr, $($arg:tt)+) => {
+        {
+            let _ = ($kind, $offset);
+            ::log::$level!($($arg)+)
+        }
+    };
+}
+
+#[cfg(not(any(feature = "log", feature = "tracing")))]
+macro_rules! decode_event {
+    ($level:ident, $kind:expr, $offset:expr, $($arg:tt)+) => {
+        let _ = ($kind, $offset, format_args!($($arg)+));
+    };
+}
@@ -0,0 +1,136 @@
+//! Binary messages, framed by a delimiter byte, sent over an ITM stimulus port.
+
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::trace_protocol::{TraceDataDecoder, TracePacket};
+
+/// Maximum size of a frame, longer frames are dropped.
+const MAX_FRAME_BYTES: usize = 4096;
+
+/// Decode frames of bytes, ended by a delimiter, sent on a single ITM stimulus port.
+///
+/// This is like `ItmTextDecoder`, but for arbitrary binary data. Frames
+/// may be split over several ITM packets, and over several calls to
+/// `feed`. The returned frames do not include the delimiter. A frame which
+/// grows larger than 4096 bytes is dropped, up to its delimiter.
+pub struct ItmFrameDecoder {
+    decoder: TraceDataDecoder,
+    port: usize,
+    delimiter: u8,
+
+    /// Bytes of the frame which is not complete yet.
+    partial: Vec<u8>,
+
+    /// The partial frame grew too large, skip bytes up to the next delimiter.
+    discarding: bool,
+    frames: VecDeque<Vec<u8>>,
+}
+
+impl ItmFrameDecoder {
+    /// Create a decoder for frames on the given stimulus port, ended by the delimiter.
+    pub fn new(port: usize, delimiter: u8) -> Self {
+        // No port filter, since the decoder would then also collect the
+        // data as text. The port is checked when taking the packets instead.
+        ItmFrameDecoder {
+            decoder: TraceDataDecoder::new(),
+            port,
+            delimiter,
+            partial: vec![],
+            discarding: false,
+            frames: VecDeque::new(),
+        }
+    }
+
+    /// Feed trace data into the decoder.
+    pub fn feed(&mut self, data: Vec<u8>) {
+        self.decoder.feed(data);
+        while let Some(packet) = self.decoder.pull() {
+            if let TracePacket::ItmData { id, payload } = packet {
+                if id == self.port {
+                    self.extend(&payload);
+                }
+            }
+        }
+    }
+
+    fn extend(&mut self, payload: &[u8]) {
+        for &b in payload {
+            if b == self.delimiter {
+                let frame = core::mem::take(&mut self.partial);
+                if !core::mem::replace(&mut self.discarding, false) {
+                    self.frames.push_back(frame);
+                }
+            } else if !self.discarding {
+                self.partial.push(b);
+                if self.partial.len() > MAX_FRAME_BYTES {
+                    self.partial.clear();
+                    self.discarding = true;
+                }
+            }
+        }
+    }
+
+    /// Take the next complete frame, without its delimiter.
+    pub fn pull_frame(&mut self) -> Option<Vec<u8>> {
+        self.frames.pop_front()
+    }
+}
+
+impl Iterator for ItmFrameDecoder {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        self.pull_frame()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ItmFrameDecoder, MAX_FRAME_BYTES};
+
+    /// Create an ITM data packet of four bytes for the given port.
+    fn itm_word(id: u8, data: [u8; 4]) -> Vec<u8> {
+        let mut packet = vec![(id << 3) | 3];
+        packet.extend(&data);
+        packet
+    }
+
+    #[test]
+    fn frame_split_over_packets() {
+        let mut decoder = ItmFrameDecoder::new(1, 0);
+
+        // The delimiter is in the second packet, followed by the next frame:
+        let mut trace_data = itm_word(1, [0xde, 0xad, 0xbe, 0xef]);
+        trace_data.extend(itm_word(2, [9, 0, 9, 0]));
+        trace_data.extend(itm_word(1, [0x01, 0x02, 0x00, 0x7f]));
+        decoder.feed(trace_data);
+        assert_eq!(
+            Some(vec![0xde, 0xad, 0xbe, 0xef, 0x01, 0x02]),
+            decoder.pull_frame()
+        );
+        assert_eq!(None, decoder.pull_frame());
+
+        // Complete the next frame in another feed, with an empty frame after it:
+        let word = itm_word(1, [0x80, 0x00, 0x00, 0x05]);
+        decoder.feed(word[..2].to_vec());
+        decoder.feed(word[2..].to_vec());
+        assert_eq!(
+            vec![vec![0x7f, 0x80], vec![]],
+            decoder.collect::<Vec<Vec<u8>>>()
+        );
+    }
+
+    #[test]
+    fn too_large_frame() {
+        let mut decoder = ItmFrameDecoder::new(0, b'\n');
+        let words = MAX_FRAME_BYTES / 4 + 1;
+        for _ in 0..words {
+            decoder.feed(itm_word(0, [b'x'; 4]));
+        }
+        decoder.feed(itm_word(0, [b'x', b'\n', b'o', b'\n']));
+        assert_eq!(Some(b"o".to_vec()), decoder.pull_frame());
+        assert_eq!(None, decoder.pull_frame());
+    }
+}
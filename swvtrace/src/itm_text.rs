@@ -0,0 +1,85 @@
+//! Text output, such as printf, sent over an ITM stimulus port.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::trace_protocol::TraceDataDecoder;
+
+/// Decode lines of text sent on a single ITM stimulus port.
+///
+/// Lines may be split over several calls to `feed`, even in the middle of
+/// a UTF-8 character. Invalid UTF-8 is replaced by the replacement character.
+pub struct ItmTextDecoder {
+    decoder: TraceDataDecoder,
+    port: usize,
+}
+
+impl ItmTextDecoder {
+    /// Create a decoder for text on the given stimulus port.
+    pub fn new(port: usize) -> Self {
+        let mut decoder = TraceDataDecoder::new();
+        decoder.set_port_filter(&[port]);
+        ItmTextDecoder { decoder, port }
+    }
+
+    /// Feed trace data into the decoder.
+    pub fn feed(&mut self, data: Vec<u8>) {
+        self.decoder.feed(data);
+
+        // The text is collected by the decoder, so drop the packets:
+        while self.decoder.pull().is_some() {}
+    }
+
+    /// Take the next complete line of text, without its line ending.
+    pub fn pull_line(&mut self) -> Option<String> {
+        self.decoder.channel_string(self.port)
+    }
+}
+
+impl Iterator for ItmTextDecoder {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.pull_line()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ItmTextDecoder;
+
+    /// Create ITM data packets, one byte each, for the given port.
+    fn itm_bytes(id: u8, data: &[u8]) -> Vec<u8> {
+        data.iter().flat_map(|b| vec![(id << 3) | 1, *b]).collect()
+    }
+
+    #[test]
+    fn line_split_over_feeds() {
+        let mut decoder = ItmTextDecoder::new(0);
+        decoder.feed(itm_bytes(0, b"Hello, "));
+        assert_eq!(None, decoder.pull_line());
+
+        let mut trace_data = itm_bytes(1, b"other port\n");
+        trace_data.extend(itm_bytes(0, b"world!\nBye"));
+        decoder.feed(trace_data);
+        assert_eq!(Some("Hello, world!".to_owned()), decoder.pull_line());
+        assert_eq!(None, decoder.pull_line());
+
+        decoder.feed(itm_bytes(0, b"\n"));
+        assert_eq!(vec!["Bye".to_owned()], decoder.collect::<Vec<String>>());
+    }
+
+    #[test]
+    fn utf8() {
+        let mut decoder = ItmTextDecoder::new(2);
+
+        // A character split over two feeds:
+        let text = "°C\n".as_bytes();
+        decoder.feed(itm_bytes(2, &text[..1]));
+        decoder.feed(itm_bytes(2, &text[1..]));
+        assert_eq!(Some("°C".to_owned()), decoder.pull_line());
+
+        decoder.feed(itm_bytes(2, &[b'a', 0xff, b'b', b'\n']));
+        assert_eq!(Some("a\u{fffd}b".to_owned()), decoder.pull_line());
+    }
+}
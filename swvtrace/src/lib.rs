@@ -0,0 +1,23 @@
+//! Decoder for the trace protocol of the serial wire output (SWO) pin.
+//!
+//! Without the default `std` feature, this crate is `no_std`, and only
+//! needs the `alloc` crate. The default `log` feature logs decoding
+//! problems using the `log` crate. The `tracing` feature sends them to
+//! `tracing` instead, as events with structured fields.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[macro_use]
+mod diagnostics;
+
+mod clock;
+mod itm_frames;
+mod itm_text;
+mod trace_protocol;
+
+pub use clock::ticks_to_seconds;
+pub use itm_frames::ItmFrameDecoder;
+pub use itm_text::ItmTextDecoder;
+pub use trace_protocol::{DecoderStateKind, DecoderStats, TraceDataDecoder, TracePacket};
@@ -0,0 +1,1304 @@
+//! Trace protocol for the SWO pin.
+//!
+//! Refer to appendix E in the ARMv7-M architecture reference manual.
+//! Also a good reference is itmdump.c from openocd:
+//! https://github.com/arduino/OpenOCD/blob/master/contrib/itmdump.c
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::clock::ticks_to_seconds;
+
+#[derive(Debug, PartialEq)]
+pub enum TracePacket {
+    /// A sync package to enable synchronization in the byte stream.
+    Sync,
+
+    Overflow,
+
+    TimeStamp {
+        tc: usize,
+        ts: usize,
+    },
+
+    /// ITM trace data.
+    ItmData {
+        id: usize,
+        payload: Vec<u8>,
+    },
+
+    /// Hardware trace packet.
+    ///
+    /// Id indicates what's going on.
+    /// - 0: event counter wrapping
+    /// - 1: exception tracing
+    /// - 2: PC samping
+    /// - 0b10xxy: event packet
+    ///     - comparator xx (0..3) data
+    ///     - y=1 data was written, y=0 data was read
+    DwtData {
+        id: usize,
+        payload: Vec<u8>,
+    },
+
+    /// An extension packet.
+    Extension {
+        data: Vec<u8>,
+    },
+
+    /// A reserved packet.
+    Reserved {
+        data: Vec<u8>,
+    },
+}
+
+/// Trace data decoder.
+///
+/// This is a sans-io style decoder.
+/// See also: https://sans-io.readthedocs.io/how-to-sans-io.html
+pub struct TraceDataDecoder {
+    incoming: VecDeque<u8>,
+    packets: VecDeque<TracePacket>,
+    state: DecoderState,
+    stats: DecoderStats,
+
+    /// Sum of all timestamps pulled so far.
+    timestamp: u64,
+
+    /// When set, only ITM data from these stimulus ports is emitted.
+    port_filter: Option<Vec<usize>>,
+
+    /// ITM data per filtered stimulus port, which is not yet taken as a line.
+    channels: BTreeMap<usize, Vec<u8>>,
+
+    /// Maximum size of extension and reserved packets, including the header.
+    max_extension_bytes: usize,
+
+    /// Accept sync packets with any amount of zero bytes, see `set_relaxed_sync`.
+    relaxed_sync: bool,
+}
+
+/// Default maximum size of extension and reserved packets.
+const DEFAULT_MAX_EXTENSION_BYTES: usize = 5;
+
+/// Maximum amount of continuation bytes in a timestamp packet.
+const MAX_TIMESTAMP_BYTES: usize = 4;
+
+/// Maximum amount of bytes collected per stimulus port.
+const MAX_CHANNEL_BYTES: usize = 4096;
+
+/// Counters to diagnose the health of the trace link.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DecoderStats {
+    /// Amount of bytes processed by the decoder.
+    pub bytes: usize,
+
+    /// Amount of sync packets decoded.
+    pub sync_packets: usize,
+
+    /// Amount of overflow packets, which indicate the trace buffer was full.
+    pub overflow_packets: usize,
+
+    /// Amount of invalid bytes or packets.
+    pub decode_errors: usize,
+}
+
+/// The kind of packet the decoder is in the middle of, see `TraceDataDecoder::current_state`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecoderStateKind {
+    /// Waiting for the header of the next packet.
+    Header,
+    Syncing,
+    ItmData,
+    DwtData,
+    Extension,
+    Reserved,
+    TimeStamp,
+
+    /// Dropping the rest of an extension or reserved packet which was too long.
+    Skipping,
+}
+
+enum DecoderState {
+    Header,
+    Syncing(usize),
+    ItmData {
+        id: usize,
+        payload: Vec<u8>,
+        size: usize,
+    },
+    DwtData {
+        id: usize,
+        payload: Vec<u8>,
+        size: usize,
+    },
+    Extension(Vec<u8>),
+    Reserved(Vec<u8>),
+    TimeStamp {
+        tc: usize,
+        ts: Vec<u8>,
+    },
+    Skipping,
+}
+
+impl Default for TraceDataDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TraceDataDecoder {
+    pub fn new() -> Self {
+        TraceDataDecoder {
+            incoming: VecDeque::new(),
+            packets: VecDeque::new(),
+            state: DecoderState::Header,
+            stats: DecoderStats::default(),
+            timestamp: 0,
+            port_filter: None,
+            channels: BTreeMap::new(),
+            max_extension_bytes: DEFAULT_MAX_EXTENSION_BYTES,
+            relaxed_sync: false,
+        }
+    }
+
+    /// Set the maximum size of extension and reserved packets, including the header.
+    ///
+    /// The default is 5 bytes. A packet which still has the continuation
+    /// bit set at this size is dropped up to its last byte, and counted as
+    /// a single decode error.
+    pub fn set_max_extension_bytes(&mut self, max_extension_bytes: usize) {
+        self.max_extension_bytes = max_extension_bytes.max(2);
+    }
+
+    /// Accept sync packets with any amount of zero bytes before the 0x80 byte.
+    ///
+    /// A sync packet is five 0x00 bytes followed by 0x80. By default, a
+    /// sync packet with another amount of zero bytes is dropped, and counted
+    /// as a decode error. Some probes send extra zero bytes though. With
+    /// relaxed sync, any run of zero bytes followed by 0x80 is taken as a
+    /// sync packet, so the decoder resynchronizes on it.
+    pub fn set_relaxed_sync(&mut self, relaxed: bool) {
+        self.relaxed_sync = relaxed;
+    }
+
+    /// Only emit ITM data from the given stimulus ports.
+    ///
+    /// ITM data from other ports is dropped. Other packets, such as DWT
+    /// data and timestamps, are not affected. The data of the given ports
+    /// is also collected per port, see `channel_string`.
+    pub fn set_port_filter(&mut self, ports: &[usize]) {
+        self.channels.retain(|id, _| ports.contains(id));
+        self.port_filter = Some(ports.to_vec());
+    }
+
+    /// Emit ITM data from all stimulus ports again, and stop collecting text.
+    pub fn clear_port_filter(&mut self) {
+        self.channels.clear();
+        self.port_filter = None;
+    }
+
+    /// Take the next complete line of text received on the given stimulus port.
+    ///
+    /// The ITM data of each port in the port filter is collected, until a
+    /// newline is received. Without a port filter, nothing is collected,
+    /// so data which is never taken does not pile up. Per port, only the
+    /// last 4096 bytes are kept. The returned line does not include the
+    /// line ending. Invalid UTF-8 is replaced by the replacement character.
+    pub fn channel_string(&mut self, id: usize) -> Option<String> {
+        self.process_incoming();
+        let buffer = self.channels.get_mut(&id)?;
+        let end = buffer.iter().position(|b| *b == b'\n')?;
+        let mut line: Vec<u8> = buffer.drain(..=end).collect();
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        Some(String::from_utf8_lossy(&line).into_owned())
+    }
+
+    /// Get the kind of packet which is partially decoded.
+    ///
+    /// When this is `Header`, the decoder is idle, otherwise it waits for
+    /// more bytes of the current packet.
+    pub fn current_state(&self) -> DecoderStateKind {
+        match &self.state {
+            DecoderState::Header => DecoderStateKind::Header,
+            DecoderState::Syncing(_) => DecoderStateKind::Syncing,
+            DecoderState::ItmData { .. } => DecoderStateKind::ItmData,
+            DecoderState::DwtData { .. } => DecoderStateKind::DwtData,
+            DecoderState::Extension(_) => DecoderStateKind::Extension,
+            DecoderState::Reserved(_) => DecoderStateKind::Reserved,
+            DecoderState::TimeStamp { .. } => DecoderStateKind::TimeStamp,
+            DecoderState::Skipping => DecoderStateKind::Skipping,
+        }
+    }
+
+    /// Get the decoder statistics, for bytes processed so far.
+    pub fn stats(&self) -> &DecoderStats {
+        &self.stats
+    }
+
+    /// Set all statistics back to zero.
+    pub fn reset_stats(&mut self) {
+        self.stats = DecoderStats::default();
+    }
+
+    /// Decode a complete buffer of trace data at once.
+    ///
+    /// A partial packet at the end of the data is dropped.
+    pub fn decode_all(data: &[u8]) -> Vec<TracePacket> {
+        let mut decoder = TraceDataDecoder::new();
+        decoder.feed(data.to_vec());
+        decoder.packets.drain(..).collect()
+    }
+
+    /// Feed trace data into the decoder.
+    ///
+    /// The data is decoded right away, so `pending` reflects the
+    /// packets which are complete after this data.
+    pub fn feed(&mut self, data: Vec<u8>) {
+        self.incoming.extend(&data);
+        self.process_incoming();
+    }
+
+    /// Get the amount of completely decoded packets, ready to be pulled.
+    pub fn pending(&self) -> usize {
+        self.packets.len()
+    }
+
+    /// Test if there are any decoded packets, ready to be pulled.
+    pub fn has_pending(&self) -> bool {
+        !self.packets.is_empty()
+    }
+
+    /// Take all bytes which did not result in a packet yet.
+    ///
+    /// This includes the bytes of a partially decoded packet, such as its
+    /// header, so the result is the tail of the fed data which was not
+    /// decoded. The decoder then waits for the header of a new packet.
+    pub fn drain_incoming(&mut self) -> Vec<u8> {
+        let state = core::mem::replace(&mut self.state, DecoderState::Header);
+        let mut data = match state {
+            DecoderState::Header | DecoderState::Skipping => vec![],
+            DecoderState::Syncing(amount) => vec![0; amount],
+            DecoderState::ItmData { id, payload, size } => {
+                let mut data = vec![((id as u8) << 3) | size_code(size)];
+                data.extend(payload);
+                data
+            }
+            DecoderState::DwtData { id, payload, size } => {
+                let mut data = vec![((id as u8) << 3) | 0x4 | size_code(size)];
+                data.extend(payload);
+                data
+            }
+            DecoderState::Extension(data) | DecoderState::Reserved(data) => data,
+            DecoderState::TimeStamp { tc, ts } => {
+                // All bytes so far had the continuation bit set:
+                let mut data = vec![0xc0 | ((tc as u8) << 4)];
+                data.extend(ts.iter().map(|b| b | 0x80));
+                data
+            }
+        };
+        data.extend(self.incoming.drain(..));
+        data
+    }
+
+    /// Position in the stream of the byte being decoded, counted like the `bytes` statistic.
+    fn offset(&self) -> usize {
+        self.stats.bytes.saturating_sub(1)
+    }
+
+    fn next_byte(&mut self) -> Option<u8> {
+        self.incoming.pop_front()
+    }
+
+    /// Pull the next item from the decoder.
+    pub fn pull(&mut self) -> Option<TracePacket> {
+        // Process any bytes:
+        self.process_incoming();
+        let packet = self.packets.pop_front();
+        if let Some(TracePacket::TimeStamp { ts, .. }) = &packet {
+            self.timestamp = self.timestamp.wrapping_add(*ts as u64);
+        }
+        packet
+    }
+
+    /// Pull all decoded packets at once, in order.
+    ///
+    /// Like calling `pull` until it returns None, so timestamps are summed
+    /// as well. A partially decoded packet stays in the decoder, and is
+    /// completed by data fed later on.
+    pub fn drain(&mut self) -> Vec<TracePacket> {
+        self.process_incoming();
+        let mut packets = Vec::with_capacity(self.packets.len());
+        while let Some(packet) = self.pull() {
+            packets.push(packet);
+        }
+        packets
+    }
+
+    /// Pull the next item from the decoder, together with its absolute timestamp.
+    ///
+    /// Timestamp packets contain the time passed since the previous timestamp
+    /// packet. These are summed into a running total, which is attached to
+    /// every packet until the next timestamp packet.
+    ///
+    /// The `tc` field of a timestamp tells if the timestamp or the
+    /// data was delayed, for example when the trace buffer was full. These
+    /// timestamps are summed like the others, so the timeline stays in order,
+    /// but the time attached to the packets around it is less accurate.
+    pub fn pull_timed(&mut self) -> Option<(u64, TracePacket)> {
+        let packet = self.pull()?;
+        Some((self.timestamp, packet))
+    }
+
+    /// Pull the next item like `pull_timed`, with the timestamp in seconds.
+    ///
+    /// The frequency is that of the timestamp counter, see `ticks_to_seconds`.
+    pub fn pull_seconds(&mut self, trace_clock_hz: f64) -> Option<(f64, TracePacket)> {
+        let (ts, packet) = self.pull_timed()?;
+        Some((ticks_to_seconds(ts, trace_clock_hz), packet))
+    }
+
+    fn process_incoming(&mut self) {
+        while let Some(b) = self.next_byte() {
+            self.process_byte(b);
+        }
+    }
+
+    fn process_byte(&mut self, b: u8) {
+        self.stats.bytes += 1;
+        match &self.state {
+            DecoderState::Header => {
+                self.decode_first_byte(b);
+            }
+            DecoderState::Syncing(amount) => {
+                let amount = *amount;
+                self.handle_sync_byte(b, amount);
+            }
+            DecoderState::ItmData { payload, size, id } => {
+                let mut payload = payload.clone();
+                let id = *id;
+                let size = *size;
+                payload.push(b);
+                self.handle_itm(id, payload, size);
+            }
+            DecoderState::DwtData { payload, size, id } => {
+                let mut payload = payload.clone();
+                let id = *id;
+                let size = *size;
+                payload.push(b);
+                self.handle_dwt(id, payload, size);
+            }
+            DecoderState::Extension(data) => {
+                let data = data.clone();
+                self.handle_extension(data, b);
+            }
+            DecoderState::Reserved(data) => {
+                let data = data.clone();
+                self.handle_reserved(data, b);
+            }
+            DecoderState::TimeStamp { tc, ts } => {
+                let tc = *tc;
+                let ts = ts.clone();
+                self.handle_timestamp(b, tc, ts);
+            }
+            DecoderState::Skipping => {
+                // The last byte of the packet has no continuation bit:
+                if (b & 0x80) == 0 {
+                    self.state = DecoderState::Header;
+                }
+            }
+        }
+    }
+
+    fn emit(&mut self, packet: TracePacket) {
+        if let TracePacket::ItmData { id, payload } = &packet {
+            if let Some(ports) = &self.port_filter {
+                if !ports.contains(id) {
+                    return;
+                }
+
+                let buffer = self.channels.entry(*id).or_default();
+                buffer.extend(payload);
+                if buffer.len() > MAX_CHANNEL_BYTES {
+                    let excess = buffer.len() - MAX_CHANNEL_BYTES;
+                    buffer.drain(..excess);
+                }
+            }
+        }
+
+        self.packets.push_back(packet);
+    }
+
+    fn decode_first_byte(&mut self, header: u8) {
+        // let header: u8 = 0;
+
+        // Figure out what we are dealing with!
+        // See table E-1
+        if header == 0x70 {
+            // warn!("Overflow!");
+            self.stats.overflow_packets += 1;
+            self.emit(TracePacket::Overflow);
+        } else if header == 0x0 {
+            decode_event!(info, "sync", self.offset(), "Sync!");
+            self.state = DecoderState::Syncing(1);
+        // Read ~5 zero bytes (0x00) followed by 0x80
+        // TracePacket::Sync
+        } else {
+            // Check low 4 bits now.
+            let nibble = header & 0xf;
+            match nibble {
+                0 => {
+                    decode_event!(trace, "timestamp", self.offset(), "Timestamp!");
+                    if header & 0x80 == 0 {
+                        // Short form timestamp
+                        let ts = ((header >> 4) & 0x7) as usize;
+                        let tc = 0;
+                        if ts == 0 {
+                            decode_event!(
+                                warn,
+                                "timestamp",
+                                self.offset(),
+                                "Invalid short timestamp!"
+                            );
+                            self.stats.decode_errors += 1;
+                        } else {
+                            self.emit(TracePacket::TimeStamp { tc, ts });
+                        }
+                        self.state = DecoderState::Header;
+                    } else if header & 0xc0 == 0xc0 {
+                        let tc = ((header >> 4) & 0x3) as usize;
+                        self.state = DecoderState::TimeStamp { tc, ts: vec![] };
+                    } else {
+                        decode_event!(warn, "header", self.offset(), "Invalid data byte!");
+                        self.stats.decode_errors += 1;
+                        self.state = DecoderState::Header;
+                    }
+                }
+                0x4 => {
+                    decode_event!(trace, "reserved", self.offset(), "Reserverd");
+                    self.state = DecoderState::Reserved(vec![header]);
+                }
+                0x8 => {
+                    decode_event!(trace, "extension", self.offset(), "Extension!");
+                    self.state = DecoderState::Extension(vec![header]);
+                }
+                x => {
+                    match extract_size(x) {
+                        Err(msg) => {
+                            decode_event!(warn, "header", self.offset(), "Bad size: {}", msg);
+                            self.stats.decode_errors += 1;
+                            self.state = DecoderState::Header;
+                        }
+                        Ok(size) => {
+                            let id = (header >> 3) as usize;
+                            if x & 0x4 == 0x4 {
+                                // DWT source / hardware source
+                                decode_event!(
+                                    trace,
+                                    "dwt",
+                                    self.offset(),
+                                    "DWT data! {:?} bytes",
+                                    size
+                                );
+                                self.state = DecoderState::DwtData {
+                                    id,
+                                    payload: vec![],
+                                    size,
+                                };
+                            } else {
+                                // ITM data
+                                decode_event!(
+                                    trace,
+                                    "itm",
+                                    self.offset(),
+                                    "Software ITM data {:?} bytes",
+                                    size
+                                );
+                                self.state = DecoderState::ItmData {
+                                    id,
+                                    payload: vec![],
+                                    size,
+                                };
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_sync_byte(&mut self, b: u8, amount: usize) {
+        match b {
+            0x0 => {
+                if self.relaxed_sync {
+                    self.state = DecoderState::Syncing(amount.saturating_add(1));
+                } else if amount > 6 {
+                    decode_event!(
+                        warn,
+                        "sync",
+                        self.offset(),
+                        "Too many zero bytes in sync packet."
+                    );
+                    self.stats.decode_errors += 1;
+                    self.state = DecoderState::Header;
+                } else {
+                    self.state = DecoderState::Syncing(amount + 1);
+                }
+            }
+            0x80 => {
+                if amount == 5 || self.relaxed_sync {
+                    self.stats.sync_packets += 1;
+                    self.emit(TracePacket::Sync);
+                } else {
+                    decode_event!(
+                        warn,
+                        "sync",
+                        self.offset(),
+                        "Invalid amount of zero bytes in sync packet."
+                    );
+                    self.stats.decode_errors += 1;
+                }
+                self.state = DecoderState::Header;
+            }
+            x => {
+                decode_event!(
+                    warn,
+                    "sync",
+                    self.offset(),
+                    "Invalid character in sync packet stream: 0x{:02X}.",
+                    x
+                );
+                self.stats.decode_errors += 1;
+                self.state = DecoderState::Header;
+            }
+        }
+    }
+
+    fn handle_timestamp(&mut self, b: u8, tc: usize, mut ts_bytes: Vec<u8>) {
+        let continuation = (b & 0x80) > 0;
+        ts_bytes.push(b & 0x7f);
+        if continuation {
+            if ts_bytes.len() < MAX_TIMESTAMP_BYTES {
+                self.state = DecoderState::TimeStamp { tc, ts: ts_bytes };
+            } else {
+                decode_event!(
+                    warn,
+                    "timestamp",
+                    self.offset(),
+                    "Timestamp packet too long."
+                );
+                self.stats.decode_errors += 1;
+                self.state = DecoderState::Header;
+            }
+        } else {
+            let mut ts = 0;
+            ts_bytes.reverse();
+            for ts_byte in ts_bytes {
+                ts <<= 7;
+                ts |= ts_byte as usize;
+            }
+            self.emit(TracePacket::TimeStamp { tc, ts });
+            self.state = DecoderState::Header;
+        }
+    }
+
+    fn handle_itm(&mut self, id: usize, payload: Vec<u8>, size: usize) {
+        if payload.len() == size {
+            self.emit(TracePacket::ItmData { id, payload });
+            self.state = DecoderState::Header;
+        } else {
+            self.state = DecoderState::ItmData { id, payload, size }
+        }
+    }
+
+    fn handle_dwt(&mut self, id: usize, payload: Vec<u8>, size: usize) {
+        if payload.len() == size {
+            self.emit(TracePacket::DwtData { id, payload });
+            self.state = DecoderState::Header;
+        } else {
+            self.state = DecoderState::DwtData { id, payload, size }
+        }
+    }
+
+    fn handle_extension(&mut self, mut data: Vec<u8>, b: u8) {
+        let is_continuation = (b & 0x80) > 0;
+        data.push(b);
+        if !is_continuation {
+            self.emit(TracePacket::Extension { data });
+            self.state = DecoderState::Header;
+        } else if data.len() < self.max_extension_bytes {
+            self.state = DecoderState::Extension(data);
+        } else {
+            decode_event!(
+                warn,
+                "extension",
+                self.offset(),
+                "Extension packet longer than {} bytes.",
+                data.len()
+            );
+            self.stats.decode_errors += 1;
+            self.state = DecoderState::Skipping;
+        }
+    }
+
+    fn handle_reserved(&mut self, mut data: Vec<u8>, b: u8) {
+        let is_continuation = (b & 0x80) > 0;
+        data.push(b);
+        if !is_continuation {
+            self.emit(TracePacket::Reserved { data });
+            self.state = DecoderState::Header;
+        } else if data.len() < self.max_extension_bytes {
+            self.state = DecoderState::Reserved(data);
+        } else {
+            decode_event!(
+                warn,
+                "reserved",
+                self.offset(),
+                "Reserved packet longer than {} bytes.",
+                data.len()
+            );
+            self.stats.decode_errors += 1;
+            self.state = DecoderState::Skipping;
+        }
+    }
+}
+
+/// The inverse of `extract_size`.
+fn size_code(size: usize) -> u8 {
+    match size {
+        1 => 0b01,
+        2 => 0b10,
+        _ => 0b11,
+    }
+}
+
+fn extract_size(c: u8) -> Result<usize, String> {
+    match c & 0b11 {
+        0b01 => Ok(1),
+        0b10 => Ok(2),
+        0b11 => Ok(4),
+        _ => Err("Invalid".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DecoderState, DecoderStateKind, DecoderStats, TraceDataDecoder, TracePacket,
+        MAX_CHANNEL_BYTES,
+    };
+
+    #[test]
+    fn example_capture1() {
+        // Example trace, containing ITM trace data, timestamps and DWT trace data.
+        let trace_data: Vec<u8> = vec![
+            3, 65, 0, 0, 0, 192, 204, 244, 109, 3, 66, 0, 0, 0, 192, 29, 3, 67, 0, 0, 0, 112, 71,
+            86, 0, 0, 8, 112, 143, 226, 239, 127, 91, 240, 196, 8,
+        ];
+
+        let mut decoder = TraceDataDecoder::new();
+
+        decoder.feed(trace_data);
+        assert_eq!(
+            Some(TracePacket::ItmData {
+                id: 0,
+                payload: vec![65, 0, 0, 0]
+            }),
+            decoder.pull()
+        );
+        assert_eq!(
+            Some(TracePacket::TimeStamp { tc: 0, ts: 1800780 }),
+            decoder.pull()
+        );
+        assert_eq!(
+            Some(TracePacket::ItmData {
+                id: 0,
+                payload: vec![66, 0, 0, 0]
+            }),
+            decoder.pull()
+        );
+        assert_eq!(
+            Some(TracePacket::TimeStamp { tc: 0, ts: 29 }),
+            decoder.pull()
+        );
+        assert_eq!(
+            Some(TracePacket::ItmData {
+                id: 0,
+                payload: vec![67, 0, 0, 0]
+            }),
+            decoder.pull()
+        );
+        assert_eq!(Some(TracePacket::Overflow), decoder.pull());
+        assert_eq!(
+            Some(TracePacket::DwtData {
+                id: 8,
+                payload: vec![86, 0, 0, 8]
+            }),
+            decoder.pull()
+        );
+        assert_eq!(Some(TracePacket::Overflow), decoder.pull());
+        assert_eq!(
+            Some(TracePacket::DwtData {
+                id: 17,
+                payload: vec![226, 239, 127, 91]
+            }),
+            decoder.pull()
+        );
+        assert_eq!(
+            Some(TracePacket::TimeStamp { tc: 3, ts: 1092 }),
+            decoder.pull()
+        );
+        assert_eq!(None, decoder.pull());
+    }
+
+    #[test]
+    fn drain_example_capture1() {
+        let trace_data: Vec<u8> = vec![
+            3, 65, 0, 0, 0, 192, 204, 244, 109, 3, 66, 0, 0, 0, 192, 29, 3, 67, 0, 0, 0, 112, 71,
+            86, 0, 0, 8, 112, 143, 226, 239, 127, 91, 240, 196, 8,
+        ];
+        let itm = |payload: Vec<u8>| TracePacket::ItmData { id: 0, payload };
+
+        // Leave the last timestamp partially decoded:
+        let mut decoder = TraceDataDecoder::new();
+        let (head, tail) = trace_data.split_at(trace_data.len() - 1);
+        decoder.feed(head.to_vec());
+        assert_eq!(
+            vec![
+                itm(vec![65, 0, 0, 0]),
+                TracePacket::TimeStamp { tc: 0, ts: 1800780 },
+                itm(vec![66, 0, 0, 0]),
+                TracePacket::TimeStamp { tc: 0, ts: 29 },
+                itm(vec![67, 0, 0, 0]),
+                TracePacket::Overflow,
+                TracePacket::DwtData {
+                    id: 8,
+                    payload: vec![86, 0, 0, 8]
+                },
+                TracePacket::Overflow,
+                TracePacket::DwtData {
+                    id: 17,
+                    payload: vec![226, 239, 127, 91]
+                },
+            ],
+            decoder.drain()
+        );
+        assert!(decoder.drain().is_empty());
+        assert_eq!(DecoderStateKind::TimeStamp, decoder.current_state());
+
+        decoder.feed(tail.to_vec());
+        assert_eq!(
+            vec![TracePacket::TimeStamp { tc: 3, ts: 1092 }],
+            decoder.drain()
+        );
+
+        // The timestamps are summed, like when pulling:
+        assert_eq!(None, decoder.pull_timed());
+        decoder.feed(vec![0x70]);
+        assert_eq!(
+            Some((1800780 + 29 + 1092, TracePacket::Overflow)),
+            decoder.pull_timed()
+        );
+    }
+
+    #[test]
+    fn drain_partial_packets() {
+        let mut decoder = TraceDataDecoder::new();
+        decoder.feed(vec![0x70, 0x47, 86, 0]);
+        assert_eq!(vec![0x47, 86, 0], decoder.drain_incoming());
+        assert_eq!(Some(TracePacket::Overflow), decoder.pull());
+        assert!(decoder.drain_incoming().is_empty());
+
+        // After draining, decoding starts at a new packet:
+        decoder.feed(vec![0, 8, 0x70]);
+        assert_eq!(Some(TracePacket::Overflow), decoder.pull());
+
+        let partials: Vec<Vec<u8>> = vec![
+            vec![0, 0, 0],
+            vec![0x01],
+            vec![0x0a, 0x12],
+            vec![0x88, 0x81],
+            vec![0xe0, 0x81, 0xff],
+        ];
+        for partial in partials {
+            decoder.feed(partial.clone());
+            assert_eq!(None, decoder.pull());
+            assert_eq!(partial, decoder.drain_incoming());
+        }
+    }
+
+    #[test]
+    fn current_state() {
+        let mut decoder = TraceDataDecoder::new();
+        assert_eq!(DecoderStateKind::Header, decoder.current_state());
+
+        // ITM header for 2 bytes on port 1:
+        decoder.feed(vec![0x0a]);
+        assert_eq!(DecoderStateKind::ItmData, decoder.current_state());
+        decoder.feed(vec![0x12]);
+        assert_eq!(DecoderStateKind::ItmData, decoder.current_state());
+        decoder.feed(vec![0x34]);
+        assert_eq!(DecoderStateKind::Header, decoder.current_state());
+
+        decoder.feed(vec![0, 0]);
+        assert_eq!(DecoderStateKind::Syncing, decoder.current_state());
+        decoder.feed(vec![0, 0, 0, 0x80]);
+        assert_eq!(DecoderStateKind::Header, decoder.current_state());
+
+        decoder.feed(vec![0xc0]);
+        assert_eq!(DecoderStateKind::TimeStamp, decoder.current_state());
+    }
+
+    #[test]
+    fn relaxed_sync() {
+        for zeros in [6, 7] {
+            let mut data = vec![0; zeros];
+            data.push(0x80);
+            data.push(0x70);
+
+            let mut decoder = TraceDataDecoder::new();
+            decoder.feed(data.clone());
+            assert_eq!(Some(TracePacket::Overflow), decoder.pull());
+            assert_eq!(1, decoder.stats().decode_errors);
+            assert_eq!(0, decoder.stats().sync_packets);
+
+            let mut decoder = TraceDataDecoder::new();
+            decoder.set_relaxed_sync(true);
+            decoder.feed(data);
+            assert_eq!(Some(TracePacket::Sync), decoder.pull());
+            assert_eq!(Some(TracePacket::Overflow), decoder.pull());
+            assert_eq!(0, decoder.stats().decode_errors);
+            assert_eq!(1, decoder.stats().sync_packets);
+        }
+
+        // The canonical sync packet, and a long run of zeros:
+        let mut decoder = TraceDataDecoder::new();
+        decoder.set_relaxed_sync(true);
+        decoder.feed(vec![0, 0, 0, 0, 0, 0x80]);
+        decoder.feed(vec![0; 100]);
+        decoder.feed(vec![0x80]);
+        assert_eq!(Some(TracePacket::Sync), decoder.pull());
+        assert_eq!(Some(TracePacket::Sync), decoder.pull());
+        assert_eq!(None, decoder.pull());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_fields() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        /// Collect the kind and offset fields of all events.
+        #[derive(Clone, Default)]
+        struct Capture(Arc<Mutex<Vec<(String, u64)>>>);
+
+        #[derive(Default)]
+        struct Fields {
+            kind: String,
+            offset: u64,
+        }
+
+        impl Visit for Fields {
+            fn record_u64(&mut self, field: &Field, value: u64) {
+                if field.name() == "offset" {
+                    self.offset = value;
+                }
+            }
+
+            fn record_str(&mut self, field: &Field, value: &str) {
+                if field.name() == "kind" {
+                    self.kind = value.to_owned();
+                }
+            }
+
+            fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+        }
+
+        impl Subscriber for Capture {
+            fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, _span: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+
+            fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                let mut fields = Fields::default();
+                event.record(&mut fields);
+                self.0.lock().unwrap().push((fields.kind, fields.offset));
+            }
+
+            fn enter(&self, _span: &Id) {}
+
+            fn exit(&self, _span: &Id) {}
+        }
+
+        let capture = Capture::default();
+        tracing::subscriber::with_default(capture.clone(), || {
+            // An overflow, followed by a sync packet with too few zero bytes:
+            TraceDataDecoder::decode_all(&[0x70, 0, 0, 0x80]);
+        });
+
+        let events = capture.0.lock().unwrap();
+        assert_eq!(
+            vec![("sync".to_owned(), 1), ("sync".to_owned(), 3)],
+            *events
+        );
+    }
+
+    #[test]
+    fn decode_all() {
+        let trace_data: Vec<u8> = vec![
+            3, 65, 0, 0, 0, 192, 204, 244, 109, 3, 66, 0, 0, 0, 192, 29, 3, 67, 0, 0, 0, 112, 71,
+            86, 0, 0, 8, 112, 143, 226, 239, 127, 91, 240, 196, 8,
+        ];
+
+        let mut decoder = TraceDataDecoder::new();
+        decoder.feed(trace_data.clone());
+        let mut packets = vec![];
+        while let Some(packet) = decoder.pull() {
+            packets.push(packet);
+        }
+
+        assert_eq!(10, packets.len());
+        assert_eq!(packets, TraceDataDecoder::decode_all(&trace_data));
+
+        // Trailing partial packets are dropped:
+        let mut trace_data = trace_data;
+        trace_data.extend(&[3, 68, 0]);
+        assert_eq!(packets, TraceDataDecoder::decode_all(&trace_data));
+    }
+
+    #[test]
+    fn example_capture2() {
+        // Example trace, containing ITM trace data, timestamps and DWT trace data.
+        let trace_data: Vec<u8> = vec![
+            71, 68, 0, 0, 8, 135, 215, 2, 0, 0, 192, 161, 245, 109, 71, 72, 0, 0, 8, 112, 71, 96,
+            0, 0, 8, 112, 143, 216, 2, 0, 0, 240, 197,
+        ];
+
+        let mut decoder = TraceDataDecoder::new();
+
+        decoder.feed(trace_data);
+        assert_eq!(
+            Some(TracePacket::DwtData {
+                id: 8,
+                payload: vec![68, 0, 0, 8]
+            }),
+            decoder.pull()
+        );
+        assert_eq!(
+            Some(TracePacket::DwtData {
+                id: 16,
+                payload: vec![215, 2, 0, 0]
+            }),
+            decoder.pull()
+        );
+        assert_eq!(
+            Some(TracePacket::TimeStamp { tc: 0, ts: 1800865 }),
+            decoder.pull()
+        );
+        assert_eq!(
+            Some(TracePacket::DwtData {
+                id: 8,
+                payload: vec![72, 0, 0, 8]
+            }),
+            decoder.pull()
+        );
+        assert_eq!(Some(TracePacket::Overflow), decoder.pull());
+        assert_eq!(
+            Some(TracePacket::DwtData {
+                id: 8,
+                payload: vec![96, 0, 0, 8]
+            }),
+            decoder.pull()
+        );
+        assert_eq!(Some(TracePacket::Overflow), decoder.pull());
+        assert_eq!(
+            Some(TracePacket::DwtData {
+                id: 17,
+                payload: vec![216, 2, 0, 0]
+            }),
+            decoder.pull()
+        );
+        assert_eq!(None, decoder.pull());
+    }
+
+    #[test]
+    fn extension_limit() {
+        let mut decoder = TraceDataDecoder::new();
+
+        // An extension packet using all 5 bytes:
+        decoder.feed(vec![0x88, 0x81, 0x82, 0x83, 0x04]);
+        assert_eq!(
+            Some(TracePacket::Extension {
+                data: vec![0x88, 0x81, 0x82, 0x83, 0x04]
+            }),
+            decoder.pull()
+        );
+        assert_eq!(0, decoder.stats().decode_errors);
+
+        // Still continued at 5 bytes, the rest of the packet is skipped:
+        decoder.feed(vec![0x88, 0x81, 0x82, 0x83, 0x84]);
+        assert_eq!(DecoderStateKind::Skipping, decoder.current_state());
+        decoder.feed(vec![0x85, 0x86, 0x07, 0x70]);
+        assert_eq!(Some(TracePacket::Overflow), decoder.pull());
+        assert_eq!(None, decoder.pull());
+        assert_eq!(1, decoder.stats().decode_errors);
+
+        // The same for reserved packets:
+        decoder.feed(vec![0x84, 0x81, 0x82, 0x83, 0x84, 0x85, 0x06]);
+        decoder.feed(vec![3, 65, 0, 0, 0]);
+        assert_eq!(
+            Some(TracePacket::ItmData {
+                id: 0,
+                payload: vec![65, 0, 0, 0]
+            }),
+            decoder.pull()
+        );
+        assert_eq!(None, decoder.pull());
+        assert_eq!(2, decoder.stats().decode_errors);
+    }
+
+    #[test]
+    fn configurable_extension_limit() {
+        let mut decoder = TraceDataDecoder::new();
+        decoder.set_max_extension_bytes(7);
+
+        let data = vec![0x88, 0x81, 0x82, 0x83, 0x84, 0x85, 0x06];
+        decoder.feed(data.clone());
+        assert_eq!(Some(TracePacket::Extension { data }), decoder.pull());
+
+        decoder.feed(vec![0x84, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86]);
+        assert_eq!(None, decoder.pull());
+        assert_eq!(1, decoder.stats().decode_errors);
+    }
+
+    /// Amount of bytes held by a partially decoded packet.
+    fn partial_size(decoder: &TraceDataDecoder) -> usize {
+        match &decoder.state {
+            DecoderState::Header | DecoderState::Syncing(_) | DecoderState::Skipping => 0,
+            DecoderState::ItmData { payload, .. } | DecoderState::DwtData { payload, .. } => {
+                payload.len()
+            }
+            DecoderState::Extension(data) | DecoderState::Reserved(data) => data.len(),
+            DecoderState::TimeStamp { ts, .. } => ts.len(),
+        }
+    }
+
+    #[test]
+    fn endless_streams() {
+        let mut decoder = TraceDataDecoder::new();
+        decoder.set_port_filter(&[0]);
+
+        // Continuation bytes forever, and a line which never ends:
+        decoder.feed(vec![0xc0; 10_000]);
+        decoder.feed(vec![0x88; 10_000]);
+
+        // End the skipped extension packet:
+        decoder.feed(vec![0x05]);
+        for _ in 0..10_000 {
+            decoder.feed(vec![0x01, b'x']);
+        }
+        while decoder.pull().is_some() {}
+
+        assert!(partial_size(&decoder) < 10);
+        assert_eq!(MAX_CHANNEL_BYTES, decoder.channels[&0].len());
+
+        // The newline pushes out the oldest byte:
+        decoder.feed(vec![0x01, b'\n']);
+        assert_eq!(
+            Some(MAX_CHANNEL_BYTES - 1),
+            decoder.channel_string(0).map(|l| l.len())
+        );
+    }
+
+    #[test]
+    fn timeline() {
+        let trace_data: Vec<u8> = vec![
+            3, 65, 0, 0, 0, 192, 204, 244, 109, 3, 66, 0, 0, 0, 192, 29, 3, 67, 0, 0, 0, 112, 71,
+            86, 0, 0, 8, 112, 143, 226, 239, 127, 91, 240, 196, 8,
+        ];
+
+        let mut decoder = TraceDataDecoder::new();
+        decoder.feed(trace_data.clone());
+
+        let mut timeline = vec![];
+        while let Some((timestamp, packet)) = decoder.pull_timed() {
+            timeline.push((timestamp, packet));
+        }
+
+        let times: Vec<u64> = timeline.iter().map(|(t, _)| *t).collect();
+        assert_eq!(
+            vec![
+                0, 1800780, 1800780, 1800809, 1800809, 1800809, 1800809, 1800809, 1800809, 1801901
+            ],
+            times
+        );
+        assert_eq!(
+            (
+                1800780,
+                TracePacket::ItmData {
+                    id: 0,
+                    payload: vec![66, 0, 0, 0]
+                }
+            ),
+            timeline[2]
+        );
+        assert_eq!(
+            (1801901, TracePacket::TimeStamp { tc: 3, ts: 1092 }),
+            timeline[9]
+        );
+
+        // The same timeline, at a timestamp frequency of 1 MHz:
+        let mut decoder = TraceDataDecoder::new();
+        decoder.feed(trace_data);
+        let seconds: Vec<f64> = core::iter::from_fn(|| decoder.pull_seconds(1.0e6))
+            .map(|(t, _)| t)
+            .collect();
+        assert_eq!(10, seconds.len());
+        assert_eq!(1.800780, seconds[2]);
+        assert_eq!(1.801901, seconds[9]);
+    }
+
+    /// Create ITM data packets, one byte each, for the given port.
+    fn itm_bytes(id: u8, text: &str) -> Vec<u8> {
+        text.bytes().flat_map(|b| vec![(id << 3) | 1, b]).collect()
+    }
+
+    #[test]
+    fn port_filter() {
+        let mut decoder = TraceDataDecoder::new();
+        decoder.set_port_filter(&[1]);
+
+        let mut trace_data = itm_bytes(0, "a");
+        trace_data.extend(itm_bytes(1, "b"));
+        trace_data.extend(vec![0x70]);
+        decoder.feed(trace_data);
+
+        assert_eq!(
+            Some(TracePacket::ItmData {
+                id: 1,
+                payload: vec![b'b']
+            }),
+            decoder.pull()
+        );
+        assert_eq!(Some(TracePacket::Overflow), decoder.pull());
+        assert_eq!(None, decoder.pull());
+
+        decoder.clear_port_filter();
+        decoder.feed(itm_bytes(0, "c"));
+        assert_eq!(
+            Some(TracePacket::ItmData {
+                id: 0,
+                payload: vec![b'c']
+            }),
+            decoder.pull()
+        );
+    }
+
+    #[test]
+    fn channel_lines() {
+        let mut decoder = TraceDataDecoder::new();
+        decoder.set_port_filter(&[0, 1]);
+        let port0 = itm_bytes(0, "hello\nworld\r\n");
+        let port1 = itm_bytes(1, "x=1\nx=");
+        let mut trace_data = vec![];
+        for (a, b) in port0.chunks(2).zip(port1.chunks(2)) {
+            trace_data.extend(a);
+            trace_data.extend(b);
+        }
+        trace_data.extend(&port0[port1.len()..]);
+        decoder.feed(trace_data);
+
+        assert_eq!(Some("hello".to_owned()), decoder.channel_string(0));
+        assert_eq!(Some("world".to_owned()), decoder.channel_string(0));
+        assert_eq!(None, decoder.channel_string(0));
+        assert_eq!(Some("x=1".to_owned()), decoder.channel_string(1));
+        assert_eq!(None, decoder.channel_string(1));
+        assert_eq!(None, decoder.channel_string(2));
+
+        // Complete the partial line on port 1:
+        decoder.feed(itm_bytes(1, "2\n"));
+        assert_eq!(Some("x=2".to_owned()), decoder.channel_string(1));
+
+        // Filtered ports are not collected:
+        decoder.set_port_filter(&[0]);
+        decoder.feed(itm_bytes(1, "dropped\n"));
+        assert_eq!(None, decoder.channel_string(1));
+
+        // Without a filter, no text is collected:
+        decoder.clear_port_filter();
+        decoder.feed(itm_bytes(0, "ignored\n"));
+        assert_eq!(None, decoder.channel_string(0));
+    }
+
+    #[test]
+    fn pending_packets() {
+        let mut decoder = TraceDataDecoder::new();
+        assert!(!decoder.has_pending());
+
+        // A partial ITM packet:
+        decoder.feed(vec![3, 65, 0]);
+        assert_eq!(0, decoder.pending());
+        assert!(!decoder.has_pending());
+
+        // The rest of the ITM packet, and a partial timestamp:
+        decoder.feed(vec![0, 0, 192, 204]);
+        assert_eq!(1, decoder.pending());
+        assert!(decoder.has_pending());
+
+        decoder.feed(vec![244, 109, 112]);
+        assert_eq!(3, decoder.pending());
+
+        assert_eq!(
+            Some(TracePacket::ItmData {
+                id: 0,
+                payload: vec![65, 0, 0, 0]
+            }),
+            decoder.pull()
+        );
+        assert_eq!(2, decoder.pending());
+        decoder.pull();
+        decoder.pull();
+        assert!(!decoder.has_pending());
+    }
+
+    #[test]
+    fn statistics() {
+        let trace_data: Vec<u8> = vec![
+            3, 65, 0, 0, 0, 192, 204, 244, 109, 3, 66, 0, 0, 0, 192, 29, 3, 67, 0, 0, 0, 112, 71,
+            86, 0, 0, 8, 112, 143, 226, 239, 127, 91, 240, 196, 8,
+        ];
+
+        let mut decoder = TraceDataDecoder::new();
+        decoder.feed(trace_data);
+        while decoder.pull().is_some() {}
+
+        assert_eq!(2, decoder.stats().overflow_packets);
+        assert_eq!(36, decoder.stats().bytes);
+        assert_eq!(0, decoder.stats().sync_packets);
+        assert_eq!(0, decoder.stats().decode_errors);
+
+        // A sync packet, followed by a broken sync packet:
+        decoder.feed(vec![0, 0, 0, 0, 0, 0x80, 0, 0x42]);
+        while decoder.pull().is_some() {}
+        assert_eq!(1, decoder.stats().sync_packets);
+        assert_eq!(1, decoder.stats().decode_errors);
+
+        decoder.reset_stats();
+        assert_eq!(&DecoderStats::default(), decoder.stats());
+    }
+}
@@ -0,0 +1,59 @@
+//! Property tests feeding arbitrary bytes into the trace decoder.
+
+use proptest::prelude::*;
+use swvtrace::{TraceDataDecoder, TracePacket};
+
+/// Up to 50 chunks of up to 200 arbitrary bytes.
+fn chunks() -> impl Strategy<Value = Vec<Vec<u8>>> {
+    prop::collection::vec(prop::collection::vec(any::<u8>(), 0..200), 0..50)
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_bytes(
+        chunks in chunks(),
+        ports in prop::collection::vec(0..32usize, 0..3),
+        max_extension_bytes in 0..10usize,
+        relaxed_sync in any::<bool>(),
+    ) {
+        let mut decoder = TraceDataDecoder::new();
+        if !ports.is_empty() {
+            decoder.set_port_filter(&ports);
+        }
+        decoder.set_max_extension_bytes(max_extension_bytes);
+        decoder.set_relaxed_sync(relaxed_sync);
+
+        let mut fed = 0;
+        for chunk in chunks {
+            fed += chunk.len();
+            decoder.feed(chunk);
+            prop_assert_eq!(fed, decoder.stats().bytes);
+
+            for _ in 0..decoder.pending() / 2 {
+                prop_assert!(decoder.pull_timed().is_some());
+            }
+            for &port in &ports {
+                while let Some(line) = decoder.channel_string(port) {
+                    prop_assert!(line.chars().count() <= 4096);
+                }
+            }
+        }
+
+        while decoder.pull().is_some() {}
+        prop_assert_eq!(0, decoder.pending());
+        prop_assert!(!decoder.has_pending());
+    }
+
+    #[test]
+    fn split_anywhere(chunks in chunks()) {
+        let data: Vec<u8> = chunks.concat();
+        let expected = TraceDataDecoder::decode_all(&data);
+
+        let mut decoder = TraceDataDecoder::new();
+        for chunk in chunks {
+            decoder.feed(chunk);
+        }
+        let packets: Vec<TracePacket> = decoder.drain();
+        prop_assert_eq!(expected, packets);
+    }
+}